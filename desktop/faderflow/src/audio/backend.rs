@@ -1,37 +1,101 @@
-use super::session::AudioSession;
+use super::error::AudioError;
+use super::session::{AudioLevel, AudioSession};
+use super::session_key::SessionKey;
 use std::sync::mpsc;
 
 #[derive(Debug, Clone)]
 pub enum AudioUpdate {
-    VolumeChanged(String, f32),
-    MuteChanged(String, bool),
+    VolumeChanged(SessionKey, f32),
+    MuteChanged(SessionKey, bool),
+    BalanceChanged(SessionKey, f32),
     SessionAdded(AudioSession),
-    SessionRemoved(String),
+    SessionRemoved(SessionKey),
     DefaultDeviceChanged(String), // friendly name of new default output
+    EndpointVolumeChanged(f32),   // master volume of the default output
+    EndpointMuteChanged(bool),
+    OutputDeviceAdded(String),    // friendly name, as in get_output_devices
+    OutputDeviceRemoved(String),
+    OutputDeviceStateChanged(String, DeviceState),
+    SessionLevels(Vec<(SessionKey, AudioLevel)>), // one batch per meter period
+    EndpointLevel(AudioLevel),                     // level of the default output
+    /// The sound server went away under a running backend; nothing it
+    /// reports is live until it's initialized again. Only the PipeWire
+    /// backend loses its connection for good; Pulse reconnects by itself.
+    #[cfg_attr(not(feature = "pipewire"), allow(dead_code))]
+    BackendLost(AudioError),
+}
+
+/// Whether an output device that is present can play audio right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeviceState {
+    #[default]
+    Active,
+    /// Jack detection says nothing is plugged into the port
+    Unplugged,
 }
 
 pub trait AudioBackend: Send + Sync {
     /// Initialize the audio backend
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    fn initialize(&mut self) -> Result<(), AudioError>;
+
+    /// Get all current playback sessions
+    fn get_sessions(&self) -> Result<Vec<AudioSession>, AudioError>;
 
-    /// Get all current audio sessions
-    fn get_sessions(&self) -> Result<Vec<AudioSession>, Box<dyn std::error::Error>>;
+    /// Get all app recording streams (keys of kind Recording)
+    fn get_recording_streams(&self) -> Result<Vec<AudioSession>, AudioError>;
 
-    /// Set volume for a session (0.0 to 1.0)
-    fn set_volume(&mut self, session_id: &str, volume: f32) -> Result<(), Box<dyn std::error::Error>>;
+    /// Get all capture devices as controllable targets (keys of kind Input)
+    fn get_input_devices(&self) -> Result<Vec<AudioSession>, AudioError>;
+
+    /// Set volume (or gain, for recording targets) for a session of any kind (0.0 to 1.0)
+    fn set_volume(&mut self, session: &SessionKey, volume: f32) -> Result<(), AudioError>;
 
     /// Set mute state for a session
-    fn set_mute(&mut self, session_id: &str, muted: bool) -> Result<(), Box<dyn std::error::Error>>;
+    fn set_mute(&mut self, session: &SessionKey, muted: bool) -> Result<(), AudioError>;
+
+    /// Set left/right balance of a playback session, -1.0 (left) to 1.0
+    /// (right). The louder side keeps the session volume.
+    fn set_balance(&mut self, _session: &SessionKey, _balance: f32) -> Result<(), AudioError> {
+        Err(AudioError::NotSupported("Balance control".into()))
+    }
+
+    /// Route every stream of a playback session to the named output device
+    fn move_session_to_device(&mut self, session: &SessionKey, device: &str) -> Result<(), AudioError>;
+
+    /// Get master volume (0.0 to 1.0) and mute state of the default output device
+    fn get_endpoint_volume(&self) -> Result<(f32, bool), AudioError>;
+
+    /// Set master volume of the default output device (0.0 to 1.0)
+    fn set_endpoint_volume(&mut self, volume: f32) -> Result<(), AudioError>;
+
+    /// Set master mute of the default output device
+    fn set_endpoint_mute(&mut self, muted: bool) -> Result<(), AudioError>;
 
     /// Start listening for audio events
-    fn start_listening(&mut self, sender: mpsc::Sender<AudioUpdate>) -> Result<(), Box<dyn std::error::Error>>;
+    fn start_listening(&mut self, sender: mpsc::Sender<AudioUpdate>) -> Result<(), AudioError>;
 
     /// Stop listening for audio events
-    fn stop_listening(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    fn stop_listening(&mut self) -> Result<(), AudioError>;
+
+    /// Start reporting `SessionLevels` and `EndpointLevel` about 30 times a
+    /// second through `sender`. Metering is optional; backends without it
+    /// keep this default.
+    fn start_metering(&mut self, _sender: mpsc::Sender<AudioUpdate>) -> Result<(), AudioError> {
+        Err(AudioError::NotSupported("Level metering".into()))
+    }
+
+    /// Stop level reports
+    fn stop_metering(&mut self) -> Result<(), AudioError> {
+        Ok(())
+    }
 
     /// Get all active output (render) devices by friendly name
-    fn get_output_devices(&self) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+    fn get_output_devices(&self) -> Result<Vec<String>, AudioError>;
 
     /// Get the friendly name of the current default output device
     fn get_default_output_device(&self) -> Option<String>;
+
+    /// Ask the OS to make the named output device the default. Completion is
+    /// reported through `AudioUpdate::DefaultDeviceChanged`, not by returning.
+    fn set_default_output_device(&mut self, name: &str) -> Result<(), AudioError>;
 }
\ No newline at end of file
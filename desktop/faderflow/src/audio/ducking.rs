@@ -0,0 +1,297 @@
+// Automatic ducking: while a trigger app (voice chat) is audible, every
+// other playback session is turned down by a set amount. The attenuation
+// ramps in dB with separate attack and release times, and holds briefly
+// between words so the music doesn't pump.
+//
+// Session volumes stay what the user set; the duck is a gain on top that
+// only reaches the backend (and optionally the faders).
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::audio::{AudioSession, SessionKey, SessionKind};
+
+/// Silence this long before the release starts
+const HOLD_MS: u64 = 400;
+/// Applied gains this close to the envelope count as current
+const GAIN_EPSILON: f32 = 0.005;
+/// Backend echoes of our own writes arrive within this window
+const ECHO_WINDOW_MS: u64 = 250;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuckingSettings {
+    pub enabled: bool,
+    /// Executables whose audio triggers the duck, matched like routes
+    pub triggers: Vec<String>,
+    /// Attenuation of the other sessions, in dB (negative)
+    pub amount_db: f32,
+    /// Trigger peak level that counts as talking, in dBFS
+    pub threshold_db: f32,
+    pub attack_ms: u32,
+    pub release_ms: u32,
+    /// Show the duck on the motorised faders instead of only in the mixer
+    pub move_faders: bool,
+}
+
+impl Default for DuckingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            triggers: Vec::new(),
+            amount_db: -12.0,
+            threshold_db: -45.0,
+            attack_ms: 150,
+            release_ms: 800,
+            move_faders: false,
+        }
+    }
+}
+
+pub struct Ducker {
+    pub settings: DuckingSettings,
+    /// Current attenuation, from 0 down to `amount_db`
+    depth_db: f32,
+    last_heard: Option<Instant>,
+    last_tick: Option<Instant>,
+    /// Gain last written to the backend per ducked session, and when
+    applied: HashMap<SessionKey, (f32, Instant)>,
+}
+
+impl Ducker {
+    pub fn new(settings: DuckingSettings) -> Self {
+        Self { settings, depth_db: 0.0, last_heard: None, last_tick: None, applied: HashMap::new() }
+    }
+
+    pub fn is_trigger(&self, key: &SessionKey) -> bool {
+        key.kind == SessionKind::Playback && self.settings.triggers.contains(&key.exe)
+    }
+
+    /// Whether a session is turned down while a trigger talks.
+    pub fn ducks(&self, key: &SessionKey) -> bool {
+        self.settings.enabled
+            && key.kind == SessionKind::Playback
+            && !key.is_master()
+            && !self.is_trigger(key)
+    }
+
+    pub fn is_ducking(&self) -> bool {
+        self.gain() < 1.0 - GAIN_EPSILON
+    }
+
+    pub fn gain(&self) -> f32 {
+        10f32.powf(self.depth_db / 20.0)
+    }
+
+    pub fn gain_for(&self, key: &SessionKey) -> f32 {
+        if self.ducks(key) { self.gain() } else { 1.0 }
+    }
+
+    /// Advance the envelope by the time since the last tick.
+    pub fn tick(&mut self, sessions: &HashMap<SessionKey, AudioSession>) {
+        self.tick_at(sessions, Instant::now());
+    }
+
+    fn tick_at(&mut self, sessions: &HashMap<SessionKey, AudioSession>, now: Instant) {
+        let elapsed_ms = self.last_tick.map_or(0.0, |t| (now - t).as_secs_f32() * 1000.0);
+        self.last_tick = Some(now);
+
+        let threshold = 10f32.powf(self.settings.threshold_db / 20.0);
+        let talking = self.settings.enabled && sessions.values()
+            .any(|s| self.is_trigger(&s.id) && !s.is_muted && s.level.peak >= threshold);
+        if talking { self.last_heard = Some(now); }
+        let held = self.last_heard.is_some_and(|t| now - t < Duration::from_millis(HOLD_MS));
+
+        let amount = self.settings.amount_db.min(0.0);
+        let (target, time_ms) = if self.settings.enabled && (talking || held) {
+            (amount, self.settings.attack_ms)
+        } else {
+            (0.0, self.settings.release_ms)
+        };
+        // Full-depth ramps take `time_ms`; partial ones proportionally less
+        let step = amount.abs() * elapsed_ms / time_ms.max(1) as f32;
+        self.depth_db = if self.depth_db > target {
+            (self.depth_db - step).max(target)
+        } else {
+            (self.depth_db + step).min(target)
+        };
+    }
+
+    /// Sessions whose backend volume lags the envelope, ducked or restored.
+    pub fn stale<'a>(&'a self, sessions: &'a HashMap<SessionKey, AudioSession>) -> impl Iterator<Item = &'a SessionKey> {
+        sessions.keys().filter(move |k| {
+            let applied = self.applied.get(*k).map_or(1.0, |(g, _)| *g);
+            (applied - self.gain_for(k)).abs() > GAIN_EPSILON
+        })
+    }
+
+    /// Backend volume for a user volume, noting the gain as applied.
+    pub fn backend_volume(&mut self, key: &SessionKey, volume: f32) -> f32 {
+        let gain = self.gain_for(key);
+        if gain < 1.0 {
+            self.applied.insert(key.clone(), (gain, Instant::now()));
+        } else {
+            self.applied.remove(key);
+        }
+        volume * gain
+    }
+
+    /// User volume behind a volume the backend reports.
+    pub fn user_volume(&self, key: &SessionKey, backend_volume: f32) -> f32 {
+        match self.applied.get(key) {
+            Some((gain, _)) if *gain > 0.0 => (backend_volume / gain).min(1.0),
+            _ => backend_volume,
+        }
+    }
+
+    /// Backend reports for this session are likely echoes of a ramp step.
+    pub fn settling(&self, key: &SessionKey) -> bool {
+        self.applied.get(key)
+            .is_some_and(|(_, t)| t.elapsed() < Duration::from_millis(ECHO_WINDOW_MS))
+    }
+
+    /// Volume a fader should show for a user volume.
+    pub fn fader_volume(&self, key: &SessionKey, volume: f32) -> f32 {
+        if self.settings.move_faders { volume * self.gain_for(key) } else { volume }
+    }
+
+    /// User volume for a fader position's volume; the inverse of `fader_volume`.
+    pub fn volume_for_fader(&self, key: &SessionKey, volume: f32) -> f32 {
+        let gain = self.gain_for(key);
+        if self.settings.move_faders && gain > 0.0 { (volume / gain).min(1.0) } else { volume }
+    }
+
+    pub fn forget(&mut self, key: &SessionKey) {
+        self.applied.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::AudioLevel;
+
+    const VOICE: &str = "/usr/bin/discord";
+    const MUSIC: &str = "/usr/bin/spotify";
+
+    fn ducker() -> Ducker {
+        Ducker::new(DuckingSettings {
+            enabled: true,
+            triggers: vec![VOICE.into()],
+            amount_db: -12.0,
+            attack_ms: 100,
+            release_ms: 400,
+            ..Default::default()
+        })
+    }
+
+    fn sessions(voice_peak: f32) -> HashMap<SessionKey, AudioSession> {
+        [(VOICE, voice_peak), (MUSIC, 0.5)].into_iter()
+            .map(|(exe, peak)| {
+                let mut s = AudioSession::new(SessionKey::new(exe), exe.into(), 0.8, false, 0);
+                s.level = AudioLevel { peak, rms: peak };
+                (s.id.clone(), s)
+            })
+            .collect()
+    }
+
+    fn assert_depth(ducker: &Ducker, db: f32) {
+        assert!((ducker.depth_db - db).abs() < 1e-3, "depth {} dB, expected {db}", ducker.depth_db);
+    }
+
+    #[test]
+    fn attack_hold_release() {
+        let mut d = ducker();
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+
+        // Attack: full depth over attack_ms
+        d.tick_at(&sessions(0.5), at(0));
+        assert_depth(&d, 0.0);
+        d.tick_at(&sessions(0.5), at(50));
+        assert_depth(&d, -6.0);
+        d.tick_at(&sessions(0.5), at(200));
+        assert_depth(&d, -12.0);
+
+        // Hold through a pause shorter than HOLD_MS
+        d.tick_at(&sessions(0.0), at(300));
+        assert_depth(&d, -12.0);
+
+        // Release once the hold is over: full depth over release_ms
+        d.tick_at(&sessions(0.0), at(200 + HOLD_MS));
+        assert_depth(&d, -12.0 + 12.0 * (HOLD_MS - 100) as f32 / 400.0);
+        d.tick_at(&sessions(0.0), at(2000));
+        assert_depth(&d, 0.0);
+        assert!(!d.is_ducking());
+    }
+
+    #[test]
+    fn quiet_or_muted_triggers_dont_duck() {
+        let mut d = ducker();
+        let t0 = Instant::now();
+        let mut muted = sessions(0.5);
+        muted.get_mut(&SessionKey::new(VOICE)).unwrap().is_muted = true;
+        d.tick_at(&muted, t0);
+        d.tick_at(&muted, t0 + Duration::from_millis(500));
+        d.tick_at(&sessions(0.001), t0 + Duration::from_millis(1000));
+        assert_depth(&d, 0.0);
+    }
+
+    #[test]
+    fn only_other_playback_is_ducked() {
+        let mut d = ducker();
+        d.depth_db = -12.0;
+        assert!(d.gain_for(&SessionKey::new(MUSIC)) < 0.3);
+        assert_eq!(d.gain_for(&SessionKey::new(VOICE)), 1.0);
+        assert_eq!(d.gain_for(&SessionKey::master()), 1.0);
+        assert_eq!(d.gain_for(&SessionKey::new(MUSIC).with_kind(SessionKind::Recording)), 1.0);
+        d.settings.enabled = false;
+        assert_eq!(d.gain_for(&SessionKey::new(MUSIC)), 1.0);
+    }
+
+    #[test]
+    fn backend_volumes_map_back_to_user_volumes() {
+        let mut d = ducker();
+        let music = SessionKey::new(MUSIC);
+        let all = sessions(0.0);
+        d.depth_db = -12.0;
+
+        assert_eq!(d.stale(&all).collect::<Vec<_>>(), [&music]);
+        let written = d.backend_volume(&music, 0.8);
+        assert!((written - 0.8 * d.gain()).abs() < 1e-6);
+        assert!((d.user_volume(&music, written) - 0.8).abs() < 1e-6);
+        assert_eq!(d.stale(&all).count(), 0);
+
+        // Restored: nothing applied, reports pass straight through
+        d.depth_db = 0.0;
+        assert_eq!(d.backend_volume(&music, 0.8), 0.8);
+        assert_eq!(d.user_volume(&music, 0.6), 0.6);
+    }
+
+    #[test]
+    fn echoes_of_ramp_steps_are_recognised() {
+        let mut d = ducker();
+        let music = SessionKey::new(MUSIC);
+        d.depth_db = -6.0;
+        assert!(!d.settling(&music));
+        d.backend_volume(&music, 0.5);
+        assert!(d.settling(&music));
+
+        let long_ago = Instant::now().checked_sub(Duration::from_millis(ECHO_WINDOW_MS + 50)).unwrap();
+        d.applied.insert(music.clone(), (d.gain(), long_ago));
+        assert!(!d.settling(&music));
+        d.forget(&music);
+        assert!(!d.settling(&music));
+    }
+
+    #[test]
+    fn faders_show_the_duck_only_when_asked() {
+        let mut d = ducker();
+        let music = SessionKey::new(MUSIC);
+        d.depth_db = -12.0;
+        assert_eq!(d.fader_volume(&music, 0.8), 0.8);
+        d.settings.move_faders = true;
+        let shown = d.fader_volume(&music, 0.8);
+        assert!(shown < 0.8);
+        assert!((d.volume_for_fader(&music, shown) - 0.8).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+
+use super::session_key::SessionKey;
+
+/// Why a backend call failed, coarse enough for the UI to react to.
+#[derive(Debug, Clone)]
+pub enum AudioError {
+    /// This platform or backend can't do it at all; holds what "it" is
+    NotSupported(String),
+    /// The session went away before the call reached it
+    SessionGone(SessionKey),
+    /// The sound server or audio service can't be reached
+    BackendUnavailable(String),
+    /// The OS refused access to the audio system
+    PermissionDenied(String),
+    /// Any other failure the backend reported
+    Other(String),
+}
+
+impl AudioError {
+    /// Whether the backend as a whole is down, rather than one call failing.
+    pub fn is_backend_failure(&self) -> bool {
+        matches!(self, AudioError::BackendUnavailable(_) | AudioError::PermissionDenied(_))
+    }
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::NotSupported(what) => write!(f, "{what} isn't supported"),
+            AudioError::SessionGone(key) => write!(f, "audio session {key} is gone"),
+            AudioError::BackendUnavailable(why) => write!(f, "audio system unavailable: {why}"),
+            AudioError::PermissionDenied(why) => write!(f, "access to the audio system denied: {why}"),
+            AudioError::Other(why) => f.write_str(why),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+impl From<std::io::Error> for AudioError {
+    fn from(e: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+        match e.kind() {
+            ErrorKind::PermissionDenied => AudioError::PermissionDenied(e.to_string()),
+            ErrorKind::NotFound
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof
+            | ErrorKind::TimedOut => AudioError::BackendUnavailable(e.to_string()),
+            _ => AudioError::Other(e.to_string()),
+        }
+    }
+}
@@ -1,7 +1,13 @@
 mod session;
+mod session_key;
 mod backend;
+mod error;
+mod ducking;
 pub mod platform;
 
-pub use session::AudioSession;
-pub use backend::{AudioBackend, AudioUpdate};
+pub use session::{balance_from_sides, balance_sides, AudioLevel, AudioSession};
+pub use session_key::{SessionKey, SessionKind, Grouping, MASTER_LABEL, session_labels, set_grouping_rules, grouping_rules};
+pub use backend::{AudioBackend, AudioUpdate, DeviceState};
+pub use error::AudioError;
+pub use ducking::{Ducker, DuckingSettings};
 pub use platform::create_backend;
\ No newline at end of file
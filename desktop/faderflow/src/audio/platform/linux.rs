@@ -1,46 +1,1238 @@
 #[cfg(target_os = "linux")]
-use crate::audio::{AudioBackend, AudioSession, AudioUpdate};
-use std::sync::mpsc;
+use crate::audio::{
+    balance_from_sides, balance_sides, AudioBackend, AudioError, AudioLevel, AudioSession, AudioUpdate,
+    DeviceState, SessionKey, SessionKind,
+};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
 
-pub struct LinuxAudioBackend;
+use pulseaudio::protocol::{
+    self, ChannelMap, ChannelPosition, ChannelVolume, Command, CommandReply, Prop, Props, SinkInputInfo, SourceInfo,
+    SourceOutputInfo, SubscriptionEventFacility, SubscriptionEventType, SubscriptionMask, Volume,
+};
+use pulseaudio::protocol::stream::{BufferAttr, StreamFlags};
+
+const CLIENT_NAME: &CStr = c"FaderFlow";
+/// Server-side alias that always resolves to the current default sink
+const DEFAULT_SINK: &CStr = c"@DEFAULT_SINK@";
+const RECONNECT_DELAY_MS: u64 = 2000;
+/// Tags our own meter streams so they don't show up as recording apps
+const METER_APP_ID: &CStr = c"faderflow.meter";
+/// Meters record mono float at a low rate: plenty for a level, and cheap at
+/// ~30 fragments a second per stream
+const METER_RATE: u32 = 6000;
+const METER_INTERVAL_MS: u64 = 33;
+/// How often the meter thread picks up new or vanished sink-inputs
+const METER_RESYNC_MS: u64 = 500;
+
+// ── Connection ────────────────────────────────────────────────────────────────
+
+/// One authenticated connection to the PulseAudio (or pipewire-pulse) server,
+/// speaking the native protocol over the unix socket.
+struct PulseConnection {
+    sock: BufReader<UnixStream>,
+    seq: u32,
+    version: u16,
+}
+
+impl PulseConnection {
+    fn connect() -> Result<Self, AudioError> {
+        let path = pulseaudio::socket_path_from_env()
+            .ok_or_else(|| AudioError::BackendUnavailable("PulseAudio socket not found".into()))?;
+        let mut sock = BufReader::new(UnixStream::connect(path)?);
+
+        let cookie = pulseaudio::cookie_path_from_env()
+            .and_then(|p| std::fs::read(p).ok())
+            .unwrap_or_default();
+        let auth = protocol::AuthParams {
+            version: protocol::MAX_VERSION,
+            supports_shm: false,
+            supports_memfd: false,
+            cookie,
+        };
+        protocol::write_command_message(sock.get_mut(), 0, &Command::Auth(auth), protocol::MAX_VERSION)?;
+        let (_, reply) = protocol::read_reply_message::<protocol::AuthReply>(&mut sock, protocol::MAX_VERSION)?;
+        let version = protocol::MAX_VERSION.min(reply.version);
+
+        let mut props = Props::new();
+        props.set(Prop::ApplicationName, CLIENT_NAME);
+        protocol::write_command_message(sock.get_mut(), 1, &Command::SetClientName(props), version)?;
+        let _ = protocol::read_reply_message::<protocol::SetClientNameReply>(&mut sock, version)?;
+
+        Ok(Self { sock, seq: 2, version })
+    }
+
+    fn next_seq(&mut self) -> u32 {
+        let seq = self.seq;
+        self.seq = self.seq.wrapping_add(1);
+        seq
+    }
+
+    /// Send a command and read its typed reply.
+    fn request<R: CommandReply>(&mut self, cmd: Command) -> Result<R, AudioError> {
+        let seq = self.next_seq();
+        protocol::write_command_message(self.sock.get_mut(), seq, &cmd, self.version)?;
+        let (_, reply) = protocol::read_reply_message::<R>(&mut self.sock, self.version)?;
+        Ok(reply)
+    }
+
+    /// Send a command that the server answers with an empty ACK.
+    fn command(&mut self, cmd: Command) -> Result<(), AudioError> {
+        let seq = self.next_seq();
+        protocol::write_command_message(self.sock.get_mut(), seq, &cmd, self.version)?;
+        protocol::read_ack_message(&mut self.sock)?;
+        Ok(())
+    }
+}
+
+impl From<protocol::ProtocolError> for AudioError {
+    fn from(e: protocol::ProtocolError) -> Self {
+        use protocol::PulseError;
+        match e {
+            protocol::ProtocolError::Io(io) => io.into(),
+            protocol::ProtocolError::Timeout => AudioError::BackendUnavailable(e.to_string()),
+            protocol::ProtocolError::ServerError(code) => match code {
+                PulseError::AccessDenied | PulseError::AuthKey => AudioError::PermissionDenied(code.to_string()),
+                PulseError::ConnectionRefused | PulseError::ConnectionTerminated | PulseError::Killed
+                | PulseError::InvalidServer | PulseError::Timeout => AudioError::BackendUnavailable(code.to_string()),
+                PulseError::NotSupported | PulseError::NotImplemented | PulseError::NoExtension => {
+                    AudioError::NotSupported(code.to_string())
+                }
+                _ => AudioError::Other(code.to_string()),
+            },
+            other => AudioError::Other(other.to_string()),
+        }
+    }
+}
+
+// ── Shared state ──────────────────────────────────────────────────────────────
+
+#[derive(Clone)]
+struct StreamEntry {
+    session_id: SessionKey,
+    channel_map: ChannelMap,
+    volume: f32,
+    /// Left/right balance read from the per-channel volumes
+    balance: f32,
+    muted: bool,
+}
+
+/// Cached master volume of the default sink, diffed like `inputs`.
+#[derive(Clone, Copy)]
+struct EndpointEntry {
+    index: u32,
+    channels: u8,
+    volume: f32,
+    muted: bool,
+}
+
+/// Last known name and jack state of a sink, for hotplug diffs.
+#[derive(Clone, PartialEq)]
+struct OutputEntry {
+    name: String,
+    state: DeviceState,
+}
+
+/// Command connection plus the last known view of every sink-input,
+/// source-output and (non-monitor) source. The listener thread diffs server
+/// events against these, so our own set_volume/set_mute calls (which update
+/// the cache first) don't echo back.
+#[derive(Default)]
+struct PulseState {
+    conn: Option<PulseConnection>,
+    inputs: HashMap<u32, StreamEntry>,
+    recordings: HashMap<u32, StreamEntry>,
+    sources: HashMap<u32, StreamEntry>,
+    default_sink: Option<String>,
+    master: Option<EndpointEntry>,
+    outputs: HashMap<u32, OutputEntry>,
+}
+
+impl PulseState {
+    /// Run `f` against the command connection, reconnecting lazily. A failed
+    /// call drops the connection so the next one starts fresh (server restart).
+    fn with_conn<T>(
+        &mut self,
+        f: impl FnOnce(&mut PulseConnection) -> Result<T, AudioError>,
+    ) -> Result<T, AudioError> {
+        with_conn(&mut self.conn, f)
+    }
+
+    fn entries_mut(&mut self, kind: SessionKind) -> &mut HashMap<u32, StreamEntry> {
+        match kind {
+            SessionKind::Playback => &mut self.inputs,
+            SessionKind::Recording => &mut self.recordings,
+            SessionKind::Input => &mut self.sources,
+        }
+    }
+
+    fn sink_description(&mut self, name: &str) -> Option<String> {
+        let name = CString::new(name).ok()?;
+        let info: protocol::SinkInfo = self.with_conn(|c| c.request(Command::GetSinkInfo(
+            protocol::GetSinkInfo { index: None, name: Some(name) },
+        ))).ok()?;
+        Some(sink_display_name(&info))
+    }
+
+    /// Re-read the default sink's volume into the cache.
+    fn refresh_master(&mut self) -> Result<EndpointEntry, AudioError> {
+        let info = with_conn(&mut self.conn, read_default_sink)?;
+        let entry = master_from_info(&info);
+        self.master = Some(entry);
+        Ok(entry)
+    }
+
+    /// Re-read every sink into the hotplug cache.
+    fn refresh_outputs(&mut self) -> Result<(), AudioError> {
+        let sinks: protocol::SinkInfoList = self.with_conn(|c| c.request(Command::GetSinkInfoList))?;
+        self.outputs = sinks.iter().map(|s| (s.index, output_from_info(s))).collect();
+        Ok(())
+    }
+}
+
+/// `PulseState::with_conn` for a connection kept outside the shared state.
+fn with_conn<T>(
+    slot: &mut Option<PulseConnection>,
+    f: impl FnOnce(&mut PulseConnection) -> Result<T, AudioError>,
+) -> Result<T, AudioError> {
+    if slot.is_none() {
+        *slot = Some(PulseConnection::connect()?);
+    }
+    let result = f(slot.as_mut().unwrap());
+    if result.is_err() {
+        *slot = None;
+    }
+    result
+}
+
+fn read_default_sink(conn: &mut PulseConnection) -> Result<protocol::SinkInfo, AudioError> {
+    conn.request(Command::GetSinkInfo(
+        protocol::GetSinkInfo { index: None, name: Some(DEFAULT_SINK.to_owned()) },
+    ))
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn prop_str(props: &Props, prop: Prop) -> Option<String> {
+    let bytes = props.get(prop)?;
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+    let s = String::from_utf8_lossy(bytes).trim().to_string();
+    if s.is_empty() { None } else { Some(s) }
+}
+
+fn is_meter_stream(props: &Props) -> bool {
+    prop_str(props, Prop::ApplicationId).as_deref() == METER_APP_ID.to_str().ok()
+}
+
+fn cstr_lossy(s: &CStr) -> String {
+    s.to_string_lossy().into_owned()
+}
+
+/// Pulse volumes are "software" volumes where NORM is 100%. Like pavucontrol
+/// we show the loudest channel, clamped to the slider's 0–100% range.
+fn volume_to_f32(cv: &ChannelVolume) -> f32 {
+    let max = cv.channels().iter().map(|v| v.as_u32()).max().unwrap_or(0);
+    (max as f32 / Volume::NORM.as_u32() as f32).clamp(0.0, 1.0)
+}
+
+fn volume_from_f32(volume: f32, channels: u8) -> ChannelVolume {
+    let raw = (volume.clamp(0.0, 1.0) * Volume::NORM.as_u32() as f32).round() as u32;
+    let mut cv = ChannelVolume::empty();
+    for _ in 0..channels.max(1) {
+        cv.push(Volume::from_u32_clamped(raw));
+    }
+    cv
+}
+
+/// Which side of the room a channel sits on; centre, LFE and the like are neither.
+fn channel_side(position: ChannelPosition) -> Option<bool> {
+    use ChannelPosition::*;
+    match position {
+        FrontLeft | RearLeft | FrontLeftOfCenter | SideLeft | TopFrontLeft | TopRearLeft => Some(false),
+        FrontRight | RearRight | FrontRightOfCenter | SideRight | TopFrontRight | TopRearRight => Some(true),
+        _ => None,
+    }
+}
+
+/// Balance as pavucontrol sees it: the loudest left channel against the
+/// loudest right one. Maps without both sides are centred.
+fn balance_from_cvolume(cv: &ChannelVolume, map: &ChannelMap) -> f32 {
+    let (mut left, mut right) = (None::<u32>, None::<u32>);
+    for (position, v) in map.into_iter().zip(cv.channels()) {
+        let side = match channel_side(position) {
+            Some(true) => &mut right,
+            Some(false) => &mut left,
+            None => continue,
+        };
+        *side = Some(side.unwrap_or(0).max(v.as_u32()));
+    }
+    match (left, right) {
+        (Some(l), Some(r)) => balance_from_sides(l as f32, r as f32),
+        _ => 0.0,
+    }
+}
+
+/// Per-channel volume for a stream: `volume` on the louder side, scaled
+/// down on the other by `balance`. Centre channels keep the full volume.
+fn stream_volume(volume: f32, balance: f32, map: &ChannelMap) -> ChannelVolume {
+    if map.num_channels() == 0 { return volume_from_f32(volume, 1); }
+    let (left, right) = balance_sides(balance);
+    let norm = Volume::NORM.as_u32() as f32;
+    let mut cv = ChannelVolume::empty();
+    for position in map {
+        let factor = match channel_side(position) {
+            Some(false) => left,
+            Some(true) => right,
+            None => 1.0,
+        };
+        let raw = (volume.clamp(0.0, 1.0) * factor * norm).round() as u32;
+        cv.push(Volume::from_u32_clamped(raw));
+    }
+    cv
+}
+
+fn sink_display_name(info: &protocol::SinkInfo) -> String {
+    info.description
+        .as_deref()
+        .map(cstr_lossy)
+        .unwrap_or_else(|| cstr_lossy(&info.name))
+}
+
+fn output_from_info(info: &protocol::SinkInfo) -> OutputEntry {
+    // The active port's jack detection; ports without it count as plugged in
+    let unplugged = info.ports.get(info.active_port)
+        .is_some_and(|p| p.available == protocol::port_info::PortAvailable::No);
+    OutputEntry {
+        name: sink_display_name(info),
+        state: if unplugged { DeviceState::Unplugged } else { DeviceState::Active },
+    }
+}
+
+fn master_from_info(info: &protocol::SinkInfo) -> EndpointEntry {
+    EndpointEntry {
+        index: info.index,
+        channels: info.channel_map.num_channels(),
+        volume: volume_to_f32(&info.cvolume),
+        muted: info.muted,
+    }
+}
+
+/// Fold a fresh view of one sink into the cache and report how it differs.
+fn update_output(st: &mut PulseState, idx: u32, info: &protocol::SinkInfo, sender: &mpsc::Sender<AudioUpdate>) {
+    let entry = output_from_info(info);
+    let prev = st.outputs.insert(idx, entry.clone());
+    if prev.as_ref() == Some(&entry) { return; }
+    match prev {
+        // A renamed sink is a different pick-list entry
+        Some(prev) if prev.name == entry.name => {
+            let _ = sender.send(AudioUpdate::OutputDeviceStateChanged(entry.name, entry.state));
+            return;
+        }
+        Some(prev) => { let _ = sender.send(AudioUpdate::OutputDeviceRemoved(prev.name)); }
+        None => {}
+    }
+    let _ = sender.send(AudioUpdate::OutputDeviceAdded(entry.name.clone()));
+    if entry.state != DeviceState::Active {
+        let _ = sender.send(AudioUpdate::OutputDeviceStateChanged(entry.name, entry.state));
+    }
+}
+
+fn source_display_name(info: &SourceInfo) -> String {
+    info.description
+        .as_deref()
+        .map(cstr_lossy)
+        .unwrap_or_else(|| cstr_lossy(&info.name))
+}
+
+fn session_from_info(info: &SinkInputInfo) -> (AudioSession, StreamEntry) {
+    stream_session(
+        SessionKind::Playback, info.index, &info.name, &info.props,
+        &info.cvolume, info.muted, &info.channel_map,
+    )
+}
+
+fn recording_from_info(info: &SourceOutputInfo) -> (AudioSession, StreamEntry) {
+    stream_session(
+        SessionKind::Recording, info.index, &info.name, &info.props,
+        &info.cvolume, info.muted, &info.channel_map,
+    )
+}
+
+fn source_from_info(info: &SourceInfo) -> (AudioSession, StreamEntry) {
+    let display_name = source_display_name(info);
+    let key = SessionKey::input_device(display_name.clone());
+    let volume = volume_to_f32(&info.cvolume);
+    let session = AudioSession::new(key.clone(), display_name, volume, info.muted, 0);
+    let entry = StreamEntry {
+        session_id: key,
+        channel_map: info.channel_map,
+        volume,
+        balance: 0.0,
+        muted: info.muted,
+    };
+    (session, entry)
+}
+
+/// Sink-inputs and source-outputs carry the same client props.
+fn stream_session(
+    kind: SessionKind,
+    index: u32,
+    name: &CStr,
+    props: &Props,
+    cvolume: &ChannelVolume,
+    muted: bool,
+    channel_map: &ChannelMap,
+) -> (AudioSession, StreamEntry) {
+    let process_id = prop_str(props, Prop::ApplicationProcessId)
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(0);
+    let binary = prop_str(props, Prop::ApplicationProcessBinary);
+
+    let display_name = prop_str(props, Prop::ApplicationName)
+        .or_else(|| binary.clone())
+        .unwrap_or_else(|| {
+            let name = cstr_lossy(name);
+            if name.is_empty() { format!("Stream {index}") } else { name }
+        });
+
+    // Full binary path from /proc when we can see the process, otherwise
+    // whatever the client reported about itself
+    let exe_path = (process_id != 0)
+        .then(|| std::fs::read_link(format!("/proc/{process_id}/exe")).ok())
+        .flatten()
+        .map(|p| p.to_string_lossy().into_owned())
+        .or(binary);
+
+    let key = SessionKey::for_stream(
+        exe_path.as_deref().unwrap_or(&display_name),
+        process_id,
+        index.to_string(),
+    ).with_kind(kind);
+
+    let volume = volume_to_f32(cvolume);
+    let mut session = AudioSession::new(
+        key.clone(),
+        display_name,
+        volume,
+        muted,
+        process_id,
+    );
+    session.exe_path = exe_path;
+    session.balance = balance_from_cvolume(cvolume, channel_map);
+
+    let entry = StreamEntry {
+        session_id: key,
+        channel_map: *channel_map,
+        volume,
+        balance: session.balance,
+        muted,
+    };
+    (session, entry)
+}
+
+/// Streams fold into sessions per their key's grouping rule; keep the first
+/// stream's view of each session.
+fn collect_sessions(
+    views: impl Iterator<Item = (u32, (AudioSession, StreamEntry))>,
+) -> (Vec<AudioSession>, HashMap<u32, StreamEntry>) {
+    let mut sessions: Vec<AudioSession> = Vec::new();
+    let mut entries = HashMap::new();
+    for (index, (session, entry)) in views {
+        entries.insert(index, entry);
+        if !sessions.iter().any(|s| s.id == session.id) {
+            sessions.push(session);
+        }
+    }
+    (sessions, entries)
+}
+
+// ── Event listener ────────────────────────────────────────────────────────────
+
+/// Fold a fresh view of one stream/source into `entries` and announce what
+/// changed: a new session, or a volume/mute change of a known one.
+fn upsert_entry(
+    entries: &mut HashMap<u32, StreamEntry>,
+    idx: u32,
+    session: AudioSession,
+    entry: StreamEntry,
+    sender: &mpsc::Sender<AudioUpdate>,
+) {
+    match entries.insert(idx, entry.clone()) {
+        None => {
+            let known = entries.iter()
+                .any(|(i, e)| *i != idx && e.session_id == entry.session_id);
+            if !known {
+                let _ = sender.send(AudioUpdate::SessionAdded(session));
+            }
+        }
+        Some(prev) => {
+            if (prev.volume - entry.volume).abs() > 0.001 {
+                let _ = sender.send(AudioUpdate::VolumeChanged(entry.session_id.clone(), entry.volume));
+            }
+            if (prev.balance - entry.balance).abs() > 0.01 {
+                let _ = sender.send(AudioUpdate::BalanceChanged(entry.session_id.clone(), entry.balance));
+            }
+            if prev.muted != entry.muted {
+                let _ = sender.send(AudioUpdate::MuteChanged(entry.session_id, entry.muted));
+            }
+        }
+    }
+}
+
+fn remove_entry(entries: &mut HashMap<u32, StreamEntry>, idx: u32, sender: &mpsc::Sender<AudioUpdate>) {
+    if let Some(gone) = entries.remove(&idx) {
+        if !entries.values().any(|e| e.session_id == gone.session_id) {
+            let _ = sender.send(AudioUpdate::SessionRemoved(gone.session_id));
+        }
+    }
+}
+
+fn remove_output(st: &mut PulseState, idx: u32, sender: &mpsc::Sender<AudioUpdate>) {
+    if let Some(gone) = st.outputs.remove(&idx) {
+        let _ = sender.send(AudioUpdate::OutputDeviceRemoved(gone.name));
+    }
+}
+
+/// Cache a fresh view of the master volume and report what changed.
+fn update_master(st: &mut PulseState, master: EndpointEntry, sender: &mpsc::Sender<AudioUpdate>) {
+    let prev = st.master.replace(master);
+    if prev.is_none_or(|p| (p.volume - master.volume).abs() > 0.001) {
+        let _ = sender.send(AudioUpdate::EndpointVolumeChanged(master.volume));
+    }
+    if prev.is_none_or(|p| p.muted != master.muted) {
+        let _ = sender.send(AudioUpdate::EndpointMuteChanged(master.muted));
+    }
+}
+
+/// Follow the server's default sink; the master target moves with it.
+fn update_default_sink(
+    st: &mut PulseState,
+    name: Option<String>,
+    info: Option<&protocol::SinkInfo>,
+    sender: &mpsc::Sender<AudioUpdate>,
+) {
+    if name.is_some() && name != st.default_sink {
+        st.default_sink = name;
+        let display = info.map(sink_display_name).unwrap_or_else(|| "Unknown Device".into());
+        let _ = sender.send(AudioUpdate::DefaultDeviceChanged(display));
+        st.master = None;
+    }
+    if let Some(info) = info {
+        update_master(st, master_from_info(info), sender);
+    }
+}
+
+/// Default sink name plus its info, when the server has one.
+fn read_default(conn: &mut PulseConnection) -> Result<(Option<String>, Option<protocol::SinkInfo>), AudioError> {
+    let info: protocol::ServerInfo = conn.request(Command::GetServerInfo)?;
+    let name = info.default_sink_name.as_deref().map(cstr_lossy);
+    let sink = if name.is_some() { read_default_sink(conn).ok() } else { None };
+    Ok((name, sink))
+}
+
+type StreamViews = Vec<(u32, (AudioSession, StreamEntry))>;
+
+/// Everything the listener caches, listed in one go. Events missed while
+/// the subscription was down only show up in a listing like this.
+struct Snapshot {
+    inputs: StreamViews,
+    recordings: StreamViews,
+    sources: StreamViews,
+    sinks: protocol::SinkInfoList,
+    default_sink: Option<String>,
+    default_info: Option<protocol::SinkInfo>,
+}
+
+fn read_snapshot(conn: &mut PulseConnection) -> Result<Snapshot, AudioError> {
+    let inputs: protocol::SinkInputInfoList = conn.request(Command::GetSinkInputInfoList)?;
+    let recordings: protocol::SourceOutputInfoList = conn.request(Command::GetSourceOutputInfoList)?;
+    let sources: protocol::SourceInfoList = conn.request(Command::GetSourceInfoList)?;
+    let sinks: protocol::SinkInfoList = conn.request(Command::GetSinkInfoList)?;
+    let (default_sink, default_info) = read_default(conn)?;
+    Ok(Snapshot {
+        inputs: inputs.iter().map(|i| (i.index, session_from_info(i))).collect(),
+        recordings: recordings.iter()
+            .filter(|i| !is_meter_stream(&i.props))
+            .map(|i| (i.index, recording_from_info(i)))
+            .collect(),
+        sources: sources.iter()
+            .filter(|i| i.monitor_of_sink_index.is_none())
+            .map(|i| (i.index, source_from_info(i)))
+            .collect(),
+        sinks,
+        default_sink,
+        default_info,
+    })
+}
+
+/// Bring `entries` in line with a full listing: streams missing from it
+/// are removed, the rest go through `upsert_entry`.
+fn sync_entries(entries: &mut HashMap<u32, StreamEntry>, fresh: StreamViews, sender: &mpsc::Sender<AudioUpdate>) {
+    let gone: Vec<u32> = entries.keys()
+        .filter(|idx| !fresh.iter().any(|(i, _)| i == *idx))
+        .copied()
+        .collect();
+    for idx in gone {
+        remove_entry(entries, idx, sender);
+    }
+    for (idx, (session, entry)) in fresh {
+        upsert_entry(entries, idx, session, entry, sender);
+    }
+}
+
+/// Diff a snapshot against the cache and report every difference.
+fn apply_snapshot(st: &mut PulseState, snapshot: Snapshot, sender: &mpsc::Sender<AudioUpdate>) {
+    sync_entries(&mut st.inputs, snapshot.inputs, sender);
+    sync_entries(&mut st.recordings, snapshot.recordings, sender);
+    sync_entries(&mut st.sources, snapshot.sources, sender);
+
+    let gone: Vec<u32> = st.outputs.keys()
+        .filter(|idx| !snapshot.sinks.iter().any(|s| s.index == **idx))
+        .copied()
+        .collect();
+    for idx in gone {
+        remove_output(st, idx, sender);
+    }
+    for info in &snapshot.sinks {
+        update_output(st, info.index, info, sender);
+    }
+    update_default_sink(st, snapshot.default_sink, snapshot.default_info.as_ref(), sender);
+}
+
+/// Follows server events on a subscription connection. Lookups go over a
+/// second connection of the listener's own, so the shared state is only
+/// locked to fold the answers in, never while waiting on the server.
+fn run_listener(
+    state: Arc<Mutex<PulseState>>,
+    sender: mpsc::Sender<AudioUpdate>,
+    cancel: Arc<AtomicBool>,
+    sub_sock: Arc<Mutex<Option<UnixStream>>>,
+) {
+    let mut query: Option<PulseConnection> = None;
+    while !cancel.load(Ordering::Relaxed) {
+        let mut conn = match PulseConnection::connect() {
+            Ok(c) => c,
+            Err(_) => {
+                thread::sleep(Duration::from_millis(RECONNECT_DELAY_MS));
+                continue;
+            }
+        };
+
+        let mask = SubscriptionMask::SINK_INPUT | SubscriptionMask::SINK | SubscriptionMask::SERVER
+            | SubscriptionMask::SOURCE_OUTPUT | SubscriptionMask::SOURCE;
+        if conn.command(Command::Subscribe(mask)).is_err() {
+            thread::sleep(Duration::from_millis(RECONNECT_DELAY_MS));
+            continue;
+        }
+        // Keep a handle so stop_listening can unblock the read below
+        if let (Ok(mut slot), Ok(clone)) = (sub_sock.lock(), conn.sock.get_ref().try_clone()) {
+            *slot = Some(clone);
+        }
+
+        // Catch up on whatever changed while we weren't subscribed (the
+        // server restarting drops every stream); later events queue up
+        // on the subscription meanwhile
+        if let Ok(snapshot) = with_conn(&mut query, read_snapshot) {
+            let Ok(mut st) = state.lock() else { return };
+            apply_snapshot(&mut st, snapshot, &sender);
+        }
+
+        loop {
+            let event = match protocol::read_command_message(&mut conn.sock, conn.version) {
+                Ok((_, Command::SubscribeEvent(ev))) => ev,
+                Ok(_) => continue,
+                Err(_) => break,
+            };
+            if cancel.load(Ordering::Relaxed) { return; }
+
+            match (event.event_facility, event.event_type, event.index) {
+                (SubscriptionEventFacility::SinkInput, SubscriptionEventType::Removed, Some(idx)) => {
+                    let Ok(mut st) = state.lock() else { return };
+                    remove_entry(&mut st.inputs, idx, &sender);
+                }
+                (SubscriptionEventFacility::SinkInput, _, Some(idx)) => {
+                    let Ok(info) = with_conn(&mut query, |c| c.request::<SinkInputInfo>(Command::GetSinkInputInfo(idx))) else {
+                        continue;
+                    };
+                    let (session, entry) = session_from_info(&info);
+                    let Ok(mut st) = state.lock() else { return };
+                    upsert_entry(&mut st.inputs, idx, session, entry, &sender);
+                }
+                (SubscriptionEventFacility::SourceOutput, SubscriptionEventType::Removed, Some(idx)) => {
+                    let Ok(mut st) = state.lock() else { return };
+                    remove_entry(&mut st.recordings, idx, &sender);
+                }
+                (SubscriptionEventFacility::SourceOutput, _, Some(idx)) => {
+                    let Ok(info) = with_conn(&mut query, |c| c.request::<SourceOutputInfo>(Command::GetSourceOutputInfo(idx))) else {
+                        continue;
+                    };
+                    if is_meter_stream(&info.props) { continue; }
+                    let (session, entry) = recording_from_info(&info);
+                    let Ok(mut st) = state.lock() else { return };
+                    upsert_entry(&mut st.recordings, idx, session, entry, &sender);
+                }
+                (SubscriptionEventFacility::Source, SubscriptionEventType::Removed, Some(idx)) => {
+                    let Ok(mut st) = state.lock() else { return };
+                    remove_entry(&mut st.sources, idx, &sender);
+                }
+                (SubscriptionEventFacility::Source, _, Some(idx)) => {
+                    let Ok(info) = with_conn(&mut query, |c| c.request::<SourceInfo>(Command::GetSourceInfo(
+                        protocol::GetSourceInfo { index: Some(idx), name: None },
+                    ))) else {
+                        continue;
+                    };
+                    // Monitors of sinks aren't microphones
+                    if info.monitor_of_sink_index.is_some() { continue; }
+                    let (session, entry) = source_from_info(&info);
+                    let Ok(mut st) = state.lock() else { return };
+                    upsert_entry(&mut st.sources, idx, session, entry, &sender);
+                }
+                (SubscriptionEventFacility::Server, _, _) => {
+                    let Ok((name, info)) = with_conn(&mut query, read_default) else { continue };
+                    let Ok(mut st) = state.lock() else { return };
+                    update_default_sink(&mut st, name, info.as_ref(), &sender);
+                }
+                (SubscriptionEventFacility::Sink, SubscriptionEventType::Removed, Some(idx)) => {
+                    let Ok(mut st) = state.lock() else { return };
+                    remove_output(&mut st, idx, &sender);
+                }
+                (SubscriptionEventFacility::Sink, _, Some(idx)) => {
+                    let Ok(info) = with_conn(&mut query, |c| c.request::<protocol::SinkInfo>(Command::GetSinkInfo(
+                        protocol::GetSinkInfo { index: Some(idx), name: None },
+                    ))) else {
+                        continue;
+                    };
+                    let Ok(mut st) = state.lock() else { return };
+                    update_output(&mut st, idx, &info, &sender);
+                    if st.master.is_some_and(|m| m.index == idx) {
+                        update_master(&mut st, master_from_info(&info), &sender);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Ok(mut slot) = sub_sock.lock() { *slot = None; }
+        thread::sleep(Duration::from_millis(RECONNECT_DELAY_MS));
+    }
+}
+
+// ── Level meter ───────────────────────────────────────────────────────────────
+
+/// What a meter record stream listens to.
+#[derive(Clone, Copy, PartialEq)]
+enum MeterTarget {
+    /// One sink-input, tapped on its sink's monitor
+    Input(u32),
+    /// The whole monitor of the default sink (by sink index)
+    Endpoint(u32),
+}
+
+/// Peak and sum of squares of the samples seen this meter period.
+#[derive(Default)]
+struct MeterAccum {
+    peak: f32,
+    sum_sq: f32,
+    samples: u32,
+}
+
+impl MeterAccum {
+    fn add(&mut self, data: &[u8]) {
+        for chunk in data.chunks_exact(4) {
+            let s = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]).abs();
+            self.peak = self.peak.max(s);
+            self.sum_sq += s * s;
+            self.samples += 1;
+        }
+    }
+
+    fn level(&self) -> AudioLevel {
+        let rms = if self.samples == 0 { 0.0 } else { (self.sum_sq / self.samples as f32).sqrt() };
+        AudioLevel { peak: self.peak.min(1.0), rms: rms.min(1.0) }
+    }
+}
+
+/// A second connection that owns one record stream per metered target. Its
+/// socket carries both command replies and sample data, so it is read packet
+/// by packet instead of through `PulseConnection::request`.
+struct Meter {
+    conn: PulseConnection,
+    /// seq of an unanswered CreateRecordStream → what it will measure
+    pending: HashMap<u32, MeterTarget>,
+    /// record stream channel → what it measures
+    streams: HashMap<u32, MeterTarget>,
+    levels: HashMap<u32, MeterAccum>,
+}
+
+impl Meter {
+    fn connect() -> Result<Self, AudioError> {
+        let conn = PulseConnection::connect()?;
+        // Short reads let the thread keep its tick and notice cancellation
+        conn.sock.get_ref().set_read_timeout(Some(Duration::from_millis(METER_INTERVAL_MS)))?;
+        Ok(Self { conn, pending: HashMap::new(), streams: HashMap::new(), levels: HashMap::new() })
+    }
+
+    fn tracks(&self, target: MeterTarget) -> bool {
+        self.streams.values().chain(self.pending.values()).any(|t| *t == target)
+    }
+
+    fn send(&mut self, cmd: &Command) -> Result<u32, AudioError> {
+        let seq = self.conn.next_seq();
+        protocol::write_command_message(self.conn.sock.get_mut(), seq, cmd, self.conn.version)?;
+        Ok(seq)
+    }
+
+    /// Drop streams whose target is gone and open streams for new targets.
+    fn sync(&mut self, state: &Mutex<PulseState>) -> Result<(), AudioError> {
+        let mut wanted = Vec::new();
+        {
+            let mut st = state.lock().map_err(|_| AudioError::Other("PulseAudio state poisoned".into()))?;
+            let master = st.master.map(|m| m.index);
+            let stale: Vec<u32> = self.streams.iter()
+                .filter(|(_, t)| match t {
+                    MeterTarget::Input(idx) => !st.inputs.contains_key(idx),
+                    MeterTarget::Endpoint(sink) => Some(*sink) != master,
+                })
+                .map(|(ch, _)| *ch)
+                .collect();
+            for channel in stale {
+                self.streams.remove(&channel);
+                self.levels.remove(&channel);
+                self.send(&Command::DeleteRecordStream(channel))?;
+            }
+
+            if let Some(sink) = master.filter(|s| !self.tracks(MeterTarget::Endpoint(*s))) {
+                if let Some(monitor) = monitor_of(&mut st, sink) {
+                    wanted.push((MeterTarget::Endpoint(sink), monitor));
+                }
+            }
+            let inputs: Vec<u32> = st.inputs.keys().copied()
+                .filter(|idx| !self.tracks(MeterTarget::Input(*idx)))
+                .collect();
+            for idx in inputs {
+                let Ok(info) = st.with_conn(|c| c.request::<SinkInputInfo>(Command::GetSinkInputInfo(idx))) else {
+                    continue;
+                };
+                if let Some(monitor) = monitor_of(&mut st, info.sink_index) {
+                    wanted.push((MeterTarget::Input(idx), monitor));
+                }
+            }
+        }
+
+        for (target, monitor) in wanted {
+            let direct = match target {
+                MeterTarget::Input(idx) => Some(idx),
+                MeterTarget::Endpoint(_) => None,
+            };
+            let seq = self.send(&Command::CreateRecordStream(meter_stream_params(monitor, direct)))?;
+            self.pending.insert(seq, target);
+        }
+        Ok(())
+    }
+
+    /// Read one packet if one arrives within the read timeout.
+    fn pump(&mut self) -> Result<(), AudioError> {
+        match self.conn.sock.fill_buf() {
+            Ok([]) => return Err(AudioError::BackendUnavailable("PulseAudio closed the meter connection".into())),
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let desc = protocol::read_descriptor(&mut self.conn.sock)?;
+        let mut payload = vec![0; desc.length as usize];
+        self.conn.sock.read_exact(&mut payload)?;
+
+        if desc.channel != u32::MAX {
+            if self.streams.contains_key(&desc.channel) {
+                self.levels.entry(desc.channel).or_default().add(&payload);
+            }
+            return Ok(());
+        }
+
+        let mut r = Cursor::new(payload.as_slice());
+        let Ok((seq, cmd)) = Command::read_tag_prefixed(&mut r, self.conn.version) else { return Ok(()) };
+        match cmd {
+            Command::Reply => {
+                if let Some(target) = self.pending.remove(&seq) {
+                    let reply: protocol::CreateRecordStreamReply =
+                        protocol::TagStructReader::new(&mut r, self.conn.version).read()?;
+                    self.streams.insert(reply.channel, target);
+                }
+            }
+            // The next sync retries the target
+            Command::Error(_) => { self.pending.remove(&seq); }
+            // A tapped sink-input moved to another sink; the next sync follows it
+            Command::RecordStreamKilled(channel) => {
+                self.streams.remove(&channel);
+                self.levels.remove(&channel);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Report this period's levels, folded per session like volumes are.
+    fn emit(&mut self, state: &Mutex<PulseState>, sender: &mpsc::Sender<AudioUpdate>) {
+        if self.streams.is_empty() { return; }
+        let Ok(st) = state.lock() else { return };
+        let mut sessions: HashMap<SessionKey, AudioLevel> = HashMap::new();
+        let mut endpoint = None;
+        for (channel, target) in &self.streams {
+            // Paused streams send nothing; they read as silence
+            let level = self.levels.remove(channel).map(|a| a.level()).unwrap_or_default();
+            match target {
+                MeterTarget::Input(idx) => {
+                    if let Some(entry) = st.inputs.get(idx) {
+                        let slot = sessions.entry(entry.session_id.clone()).or_default();
+                        *slot = slot.max(level);
+                    }
+                }
+                MeterTarget::Endpoint(_) => endpoint = Some(level),
+            }
+        }
+        if !sessions.is_empty() {
+            let _ = sender.send(AudioUpdate::SessionLevels(sessions.into_iter().collect()));
+        }
+        if let Some(level) = endpoint {
+            let _ = sender.send(AudioUpdate::EndpointLevel(level));
+        }
+    }
+}
+
+fn monitor_of(st: &mut PulseState, sink: u32) -> Option<u32> {
+    let info: protocol::SinkInfo = st.with_conn(|c| c.request(Command::GetSinkInfo(
+        protocol::GetSinkInfo { index: Some(sink), name: None },
+    ))).ok()?;
+    info.monitor_source_index
+}
+
+/// Mono float record stream on a sink monitor, optionally tapped on a single
+/// sink-input, fragmented to deliver about one packet per meter period.
+fn meter_stream_params(monitor: u32, direct_on_input: Option<u32>) -> protocol::RecordStreamParams {
+    let mut props = Props::new();
+    props.set(Prop::ApplicationId, METER_APP_ID);
+    props.set(Prop::MediaName, c"Level meter");
+    let fragment_bytes = METER_RATE * 4 * METER_INTERVAL_MS as u32 / 1000;
+    protocol::RecordStreamParams {
+        sample_spec: protocol::SampleSpec {
+            format: protocol::SampleFormat::Float32Le,
+            channels: 1,
+            sample_rate: METER_RATE,
+        },
+        channel_map: protocol::ChannelMap::mono(),
+        source_index: Some(monitor),
+        buffer_attr: BufferAttr { fragment_size: fragment_bytes, ..Default::default() },
+        flags: StreamFlags {
+            adjust_latency: true,
+            no_move: true,
+            // Metering alone shouldn't keep an idle sink awake
+            no_inhibit_auto_suspend: true,
+            ..Default::default()
+        },
+        direct_on_input_index: direct_on_input,
+        props,
+        ..Default::default()
+    }
+}
+
+fn run_meter(state: Arc<Mutex<PulseState>>, sender: mpsc::Sender<AudioUpdate>, cancel: Arc<AtomicBool>) {
+    while !cancel.load(Ordering::Relaxed) {
+        let Ok(mut meter) = Meter::connect() else {
+            thread::sleep(Duration::from_millis(RECONNECT_DELAY_MS));
+            continue;
+        };
+        let mut last_sync: Option<Instant> = None;
+        let mut last_emit = Instant::now();
+        while !cancel.load(Ordering::Relaxed) {
+            if last_sync.is_none_or(|t| t.elapsed() >= Duration::from_millis(METER_RESYNC_MS)) {
+                if meter.sync(&state).is_err() { break; }
+                last_sync = Some(Instant::now());
+            }
+            if last_emit.elapsed() >= Duration::from_millis(METER_INTERVAL_MS) {
+                meter.emit(&state, &sender);
+                last_emit = Instant::now();
+            }
+            if meter.pump().is_err() { break; }
+        }
+        if !cancel.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(RECONNECT_DELAY_MS));
+        }
+    }
+}
+
+// ── Backend ───────────────────────────────────────────────────────────────────
+
+pub struct LinuxAudioBackend {
+    state: Arc<Mutex<PulseState>>,
+    listener_cancel: Option<Arc<AtomicBool>>,
+    sub_sock: Arc<Mutex<Option<UnixStream>>>,
+    meter_cancel: Option<Arc<AtomicBool>>,
+}
 
 impl LinuxAudioBackend {
     pub fn new() -> Self {
-        Self
+        Self {
+            state: Arc::new(Mutex::new(PulseState::default())),
+            listener_cancel: None,
+            sub_sock: Arc::new(Mutex::new(None)),
+            meter_cancel: None,
+        }
+    }
+
+    fn lock_state(&self) -> Result<std::sync::MutexGuard<'_, PulseState>, AudioError> {
+        self.state.lock().map_err(|_| AudioError::Other("PulseAudio state poisoned".into()))
     }
 }
 
 impl AudioBackend for LinuxAudioBackend {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // TODO: Initialize PulseAudio/PipeWire
-        Err("Linux support not yet implemented".into())
+    fn initialize(&mut self) -> Result<(), AudioError> {
+        let mut st = self.lock_state()?;
+        let info: protocol::ServerInfo = st.with_conn(|c| c.request(Command::GetServerInfo))?;
+        st.default_sink = info.default_sink_name.as_deref().map(cstr_lossy);
+        let _ = st.refresh_master();
+        let _ = st.refresh_outputs();
+        Ok(())
     }
 
-    fn get_sessions(&self) -> Result<Vec<AudioSession>, Box<dyn std::error::Error>> {
-        Ok(Vec::new())
+    fn get_sessions(&self) -> Result<Vec<AudioSession>, AudioError> {
+        let mut st = self.lock_state()?;
+        let infos: protocol::SinkInputInfoList =
+            st.with_conn(|c| c.request(Command::GetSinkInputInfoList))?;
+
+        // Apps often open several streams (one per tab/sound); how they
+        // fold into sessions is up to the key's grouping rule.
+        let (sessions, inputs) = collect_sessions(infos.iter().map(|i| (i.index, session_from_info(i))));
+        st.inputs = inputs;
+        Ok(sessions)
     }
 
-    fn set_volume(&mut self, _session_id: &str, _volume: f32) -> Result<(), Box<dyn std::error::Error>> {
+    fn get_recording_streams(&self) -> Result<Vec<AudioSession>, AudioError> {
+        let mut st = self.lock_state()?;
+        let infos: protocol::SourceOutputInfoList =
+            st.with_conn(|c| c.request(Command::GetSourceOutputInfoList))?;
+        let (sessions, recordings) = collect_sessions(infos.iter()
+            .filter(|i| !is_meter_stream(&i.props))
+            .map(|i| (i.index, recording_from_info(i))));
+        st.recordings = recordings;
+        Ok(sessions)
+    }
+
+    fn get_input_devices(&self) -> Result<Vec<AudioSession>, AudioError> {
+        let mut st = self.lock_state()?;
+        let infos: protocol::SourceInfoList = st.with_conn(|c| c.request(Command::GetSourceInfoList))?;
+        let (sessions, sources) = collect_sessions(infos.iter()
+            .filter(|i| i.monitor_of_sink_index.is_none())
+            .map(|i| (i.index, source_from_info(i))));
+        st.sources = sources;
+        Ok(sessions)
+    }
+
+    fn set_volume(&mut self, session: &SessionKey, volume: f32) -> Result<(), AudioError> {
+        let mut st = self.lock_state()?;
+        let targets: Vec<(u32, f32, ChannelMap)> = st.entries_mut(session.kind).iter()
+            .filter(|(_, e)| &e.session_id == session)
+            .map(|(i, e)| (*i, e.balance, e.channel_map))
+            .collect();
+        if targets.is_empty() { return Err(AudioError::SessionGone(session.clone())); }
+        for (index, balance, channel_map) in targets {
+            // Keep whatever balance the stream already has
+            let volume_cv = stream_volume(volume, balance, &channel_map);
+            let cmd = match session.kind {
+                SessionKind::Playback => Command::SetSinkInputVolume(
+                    protocol::SetStreamVolumeParams { index, volume: volume_cv },
+                ),
+                SessionKind::Recording => Command::SetSourceOutputVolume(
+                    protocol::SetStreamVolumeParams { index, volume: volume_cv },
+                ),
+                SessionKind::Input => Command::SetSourceVolume(protocol::SetDeviceVolumeParams {
+                    device_index: Some(index),
+                    device_name: None,
+                    volume: volume_cv,
+                }),
+            };
+            st.with_conn(|c| c.command(cmd))?;
+            if let Some(e) = st.entries_mut(session.kind).get_mut(&index) { e.volume = volume; }
+        }
+        Ok(())
+    }
+
+    fn set_mute(&mut self, session: &SessionKey, muted: bool) -> Result<(), AudioError> {
+        let mut st = self.lock_state()?;
+        let targets: Vec<u32> = st.entries_mut(session.kind).iter()
+            .filter(|(_, e)| &e.session_id == session)
+            .map(|(i, _)| *i)
+            .collect();
+        if targets.is_empty() { return Err(AudioError::SessionGone(session.clone())); }
+        for index in targets {
+            let cmd = match session.kind {
+                SessionKind::Playback => Command::SetSinkInputMute(
+                    protocol::SetStreamMuteParams { index, mute: muted },
+                ),
+                SessionKind::Recording => Command::SetSourceOutputMute(
+                    protocol::SetStreamMuteParams { index, mute: muted },
+                ),
+                SessionKind::Input => Command::SetSourceMute(protocol::SetDeviceMuteParams {
+                    device_index: Some(index),
+                    device_name: None,
+                    mute: muted,
+                }),
+            };
+            st.with_conn(|c| c.command(cmd))?;
+            if let Some(e) = st.entries_mut(session.kind).get_mut(&index) { e.muted = muted; }
+        }
+        Ok(())
+    }
+
+    fn set_balance(&mut self, session: &SessionKey, balance: f32) -> Result<(), AudioError> {
+        if session.kind != SessionKind::Playback {
+            return Err(AudioError::NotSupported(format!("Balance on {} streams", session.kind.as_str())));
+        }
+        let balance = balance.clamp(-1.0, 1.0);
+        let mut st = self.lock_state()?;
+        let targets: Vec<(u32, f32, ChannelMap)> = st.inputs.iter()
+            .filter(|(_, e)| &e.session_id == session)
+            .map(|(i, e)| (*i, e.volume, e.channel_map))
+            .collect();
+        if targets.is_empty() { return Err(AudioError::SessionGone(session.clone())); }
+        for (index, volume, channel_map) in targets {
+            let params = protocol::SetStreamVolumeParams { index, volume: stream_volume(volume, balance, &channel_map) };
+            st.with_conn(|c| c.command(Command::SetSinkInputVolume(params)))?;
+            if let Some(e) = st.inputs.get_mut(&index) { e.balance = balance; }
+        }
+        Ok(())
+    }
+
+    fn move_session_to_device(&mut self, session: &SessionKey, device: &str) -> Result<(), AudioError> {
+        if session.kind != SessionKind::Playback {
+            return Err(AudioError::NotSupported(format!("Routing {} streams", session.kind.as_str())));
+        }
+        let mut st = self.lock_state()?;
+        let sinks: protocol::SinkInfoList = st.with_conn(|c| c.request(Command::GetSinkInfoList))?;
+        let sink = sinks.iter()
+            .find(|s| sink_display_name(s) == device)
+            .ok_or_else(|| AudioError::Other(format!("no output device named {device}")))?
+            .index;
+        let targets: Vec<u32> = st.inputs.iter()
+            .filter(|(_, e)| &e.session_id == session)
+            .map(|(i, _)| *i)
+            .collect();
+        if targets.is_empty() { return Err(AudioError::SessionGone(session.clone())); }
+        for index in targets {
+            let params = protocol::MoveStreamParams { index: Some(index), device_index: Some(sink), device_name: None };
+            st.with_conn(|c| c.command(Command::MoveSinkInput(params)))?;
+        }
+        Ok(())
+    }
+
+    fn get_endpoint_volume(&self) -> Result<(f32, bool), AudioError> {
+        let master = self.lock_state()?.refresh_master()?;
+        Ok((master.volume, master.muted))
+    }
+
+    fn set_endpoint_volume(&mut self, volume: f32) -> Result<(), AudioError> {
+        let mut st = self.lock_state()?;
+        let channels = match st.master {
+            Some(m) => m.channels,
+            None => st.refresh_master()?.channels,
+        };
+        let params = protocol::SetDeviceVolumeParams {
+            device_index: None,
+            device_name: Some(DEFAULT_SINK.to_owned()),
+            volume: volume_from_f32(volume, channels),
+        };
+        st.with_conn(|c| c.command(Command::SetSinkVolume(params)))?;
+        if let Some(m) = st.master.as_mut() { m.volume = volume; }
         Ok(())
     }
 
-    fn set_mute(&mut self, _session_id: &str, _muted: bool) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_endpoint_mute(&mut self, muted: bool) -> Result<(), AudioError> {
+        let mut st = self.lock_state()?;
+        let params = protocol::SetDeviceMuteParams {
+            device_index: None,
+            device_name: Some(DEFAULT_SINK.to_owned()),
+            mute: muted,
+        };
+        st.with_conn(|c| c.command(Command::SetSinkMute(params)))?;
+        if let Some(m) = st.master.as_mut() { m.muted = muted; }
         Ok(())
     }
 
-    fn start_listening(&mut self, _sender: mpsc::Sender<AudioUpdate>) -> Result<(), Box<dyn std::error::Error>> {
+    fn start_listening(&mut self, sender: mpsc::Sender<AudioUpdate>) -> Result<(), AudioError> {
+        self.stop_listening()?;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let state = Arc::clone(&self.state);
+        let sub_sock = Arc::clone(&self.sub_sock);
+        let cancel_clone = Arc::clone(&cancel);
+        thread::spawn(move || run_listener(state, sender, cancel_clone, sub_sock));
+        self.listener_cancel = Some(cancel);
         Ok(())
     }
 
-    fn stop_listening(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn stop_listening(&mut self) -> Result<(), AudioError> {
+        if let Some(cancel) = self.listener_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+            if let Ok(mut slot) = self.sub_sock.lock() {
+                if let Some(sock) = slot.take() {
+                    let _ = sock.shutdown(std::net::Shutdown::Both);
+                }
+            }
+        }
         Ok(())
     }
 
-    fn get_output_devices(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        Ok(vec![])
+    fn start_metering(&mut self, sender: mpsc::Sender<AudioUpdate>) -> Result<(), AudioError> {
+        self.stop_metering()?;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let state = Arc::clone(&self.state);
+        let cancel_clone = Arc::clone(&cancel);
+        thread::spawn(move || run_meter(state, sender, cancel_clone));
+        self.meter_cancel = Some(cancel);
+        Ok(())
+    }
+
+    fn stop_metering(&mut self) -> Result<(), AudioError> {
+        // The thread notices within one read timeout and drops its streams
+        if let Some(cancel) = self.meter_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn get_output_devices(&self) -> Result<Vec<String>, AudioError> {
+        let mut st = self.lock_state()?;
+        st.refresh_outputs()?;
+        let mut outputs: Vec<(u32, String)> = st.outputs.iter()
+            .filter(|(_, o)| o.state == DeviceState::Active)
+            .map(|(i, o)| (*i, o.name.clone()))
+            .collect();
+        outputs.sort();
+        Ok(outputs.into_iter().map(|(_, name)| name).collect())
     }
 
     fn get_default_output_device(&self) -> Option<String> {
-        None
+        let mut st = self.state.lock().ok()?;
+        let info: protocol::ServerInfo = st.with_conn(|c| c.request(Command::GetServerInfo)).ok()?;
+        let name = cstr_lossy(info.default_sink_name.as_deref()?);
+        st.default_sink = Some(name.clone());
+        st.sink_description(&name)
     }
-}
\ No newline at end of file
+
+    fn set_default_output_device(&mut self, name: &str) -> Result<(), AudioError> {
+        let mut st = self.lock_state()?;
+        let sinks: protocol::SinkInfoList = st.with_conn(|c| c.request(Command::GetSinkInfoList))?;
+        let sink = sinks.into_iter()
+            .find(|s| sink_display_name(s) == name)
+            .ok_or_else(|| AudioError::Other(format!("no output device named {name}")))?;
+        // The listener sees the server change and reports the new default
+        st.with_conn(|c| c.command(Command::SetDefaultSink(sink.name)))
+    }
+}
@@ -1,5 +1,5 @@
 #[cfg(target_os = "macos")]
-use crate::audio::{AudioBackend, AudioSession, AudioUpdate};
+use crate::audio::{AudioBackend, AudioError, AudioSession, AudioUpdate, SessionKey};
 use std::sync::mpsc;
 
 pub struct MacOSAudioBackend;
@@ -11,36 +11,64 @@ impl MacOSAudioBackend {
 }
 
 impl AudioBackend for MacOSAudioBackend {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn initialize(&mut self) -> Result<(), AudioError> {
         // TODO: Initialize CoreAudio
-        Err("macOS support not yet implemented".into())
+        Err(AudioError::NotSupported("Audio control on macOS".into()))
     }
 
-    fn get_sessions(&self) -> Result<Vec<AudioSession>, Box<dyn std::error::Error>> {
+    fn get_sessions(&self) -> Result<Vec<AudioSession>, AudioError> {
         Ok(Vec::new())
     }
 
-    fn set_volume(&mut self, _session_id: &str, _volume: f32) -> Result<(), Box<dyn std::error::Error>> {
+    fn get_recording_streams(&self) -> Result<Vec<AudioSession>, AudioError> {
+        Ok(Vec::new())
+    }
+
+    fn get_input_devices(&self) -> Result<Vec<AudioSession>, AudioError> {
+        Ok(Vec::new())
+    }
+
+    fn set_volume(&mut self, _session: &SessionKey, _volume: f32) -> Result<(), AudioError> {
+        Ok(())
+    }
+
+    fn set_mute(&mut self, _session: &SessionKey, _muted: bool) -> Result<(), AudioError> {
         Ok(())
     }
 
-    fn set_mute(&mut self, _session_id: &str, _muted: bool) -> Result<(), Box<dyn std::error::Error>> {
+    fn move_session_to_device(&mut self, _session: &SessionKey, _device: &str) -> Result<(), AudioError> {
+        Err(AudioError::NotSupported("Audio control on macOS".into()))
+    }
+
+    fn get_endpoint_volume(&self) -> Result<(f32, bool), AudioError> {
+        Err(AudioError::NotSupported("Audio control on macOS".into()))
+    }
+
+    fn set_endpoint_volume(&mut self, _volume: f32) -> Result<(), AudioError> {
         Ok(())
     }
 
-    fn start_listening(&mut self, _sender: mpsc::Sender<AudioUpdate>) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_endpoint_mute(&mut self, _muted: bool) -> Result<(), AudioError> {
         Ok(())
     }
 
-    fn stop_listening(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn start_listening(&mut self, _sender: mpsc::Sender<AudioUpdate>) -> Result<(), AudioError> {
         Ok(())
     }
 
-    fn get_output_devices(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    fn stop_listening(&mut self) -> Result<(), AudioError> {
+        Ok(())
+    }
+
+    fn get_output_devices(&self) -> Result<Vec<String>, AudioError> {
         Ok(vec![])
     }
 
     fn get_default_output_device(&self) -> Option<String> {
         None
     }
+
+    fn set_default_output_device(&mut self, _name: &str) -> Result<(), AudioError> {
+        Err(AudioError::NotSupported("Audio control on macOS".into()))
+    }
 }
\ No newline at end of file
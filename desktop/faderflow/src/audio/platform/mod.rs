@@ -4,21 +4,42 @@ pub mod windows;
 #[cfg(target_os = "linux")]
 pub mod linux;
 
+#[cfg(all(target_os = "linux", feature = "pipewire"))]
+pub mod pipewire;
+
 #[cfg(target_os = "macos")]
 pub mod macos;
 
+pub mod simulated;
+
 use super::backend::AudioBackend;
 
 pub fn create_backend() -> Box<dyn AudioBackend> {
+    if let Some(path) = simulated::scenario_path() {
+        return Box::new(simulated::SimulatedAudioBackend::new(path));
+    }
+
     #[cfg(target_os = "windows")]
     return Box::new(windows::WindowsAudioBackend::new());
 
     #[cfg(target_os = "linux")]
-    return Box::new(linux::LinuxAudioBackend::new());
+    return linux_backend();
 
     #[cfg(target_os = "macos")]
     return Box::new(macos::MacOSAudioBackend::new());
 
     #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
     compile_error!("Unsupported platform");
-}
\ No newline at end of file
+}
+
+/// Native PipeWire when the daemon is up (and the feature is built in),
+/// otherwise the Pulse protocol, which pipewire-pulse also speaks.
+#[cfg(target_os = "linux")]
+fn linux_backend() -> Box<dyn AudioBackend> {
+    #[cfg(feature = "pipewire")]
+    if pipewire::is_running() {
+        return Box::new(pipewire::PipeWireAudioBackend::new());
+    }
+
+    Box::new(linux::LinuxAudioBackend::new())
+}
@@ -0,0 +1,773 @@
+use crate::audio::{AudioBackend, AudioError, AudioSession, AudioUpdate, SessionKey, SessionKind};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::Duration;
+
+use ::pipewire as pw;
+use pw::metadata::{Metadata, MetadataListener};
+use pw::node::{Node, NodeListener};
+use pw::registry::GlobalObject;
+use pw::spa::param::ParamType;
+use pw::spa::pod::deserialize::PodDeserializer;
+use pw::spa::pod::serialize::PodSerializer;
+use pw::spa::pod::{Object, Pod, Property, Value, ValueArray};
+use pw::spa::utils::dict::DictRef;
+use pw::types::ObjectType;
+
+const INIT_TIMEOUT_MS: u64 = 3000;
+const DEFAULT_SINK_KEY: &str = "default.audio.sink";
+/// The user's choice; the session manager derives default.audio.sink from it
+const CONFIGURED_SINK_KEY: &str = "default.configured.audio.sink";
+/// Per-stream metadata the session manager links the stream to
+const TARGET_OBJECT_KEY: &str = "target.object";
+
+/// True when a PipeWire daemon socket exists for this user.
+pub fn is_running() -> bool {
+    let dir = std::env::var_os("PIPEWIRE_RUNTIME_DIR")
+        .or_else(|| std::env::var_os("XDG_RUNTIME_DIR"));
+    let remote = std::env::var("PIPEWIRE_REMOTE").unwrap_or_else(|_| "pipewire-0".into());
+    dir.is_some_and(|d| Path::new(&d).join(remote).exists())
+}
+
+// ── Shared state ──────────────────────────────────────────────────────────────
+
+/// A playback or recording stream, or a capture device (Audio/Source node).
+#[derive(Clone)]
+struct StreamEntry {
+    kind: SessionKind,
+    session_id: SessionKey,
+    name: String,
+    process_id: u32,
+    exe_path: Option<String>,
+    channels: usize,
+    volume: f32,
+    muted: bool,
+    /// Set once the first Props param arrived, i.e. volume/mute are real
+    ready: bool,
+}
+
+impl StreamEntry {
+    fn key_for(&self, id: u32) -> SessionKey {
+        match self.kind {
+            SessionKind::Input => SessionKey::input_device(self.name.clone()),
+            kind => SessionKey::for_stream(self.exe_path.as_deref().unwrap_or(&self.name), self.process_id, id.to_string())
+                .with_kind(kind),
+        }
+    }
+
+    fn to_session(&self) -> AudioSession {
+        let mut session = AudioSession::new(
+            self.session_id.clone(),
+            self.name.clone(),
+            self.volume,
+            self.muted,
+            self.process_id,
+        );
+        session.exe_path = self.exe_path.clone();
+        session
+    }
+}
+
+struct SinkEntry {
+    name: String,
+    description: String,
+    channels: usize,
+    volume: f32,
+    muted: bool,
+}
+
+/// Everything the main-loop thread learns from the registry, read by the
+/// backend methods. Like the Pulse backend, set_volume/set_mute update the
+/// cache before the param round-trips so the change doesn't echo back.
+#[derive(Default)]
+struct PwState {
+    streams: HashMap<u32, StreamEntry>,
+    sinks: HashMap<u32, SinkEntry>,
+    default_sink: Option<String>,
+    sender: Option<mpsc::Sender<AudioUpdate>>,
+    /// Set while the main loop is connected; what's cached is stale otherwise
+    connected: bool,
+}
+
+impl PwState {
+    fn emit(&self, update: AudioUpdate) {
+        if let Some(tx) = &self.sender {
+            let _ = tx.send(update);
+        }
+    }
+
+    fn group_has_other(&self, id: u32, session_id: &SessionKey) -> bool {
+        self.streams.iter().any(|(i, e)| *i != id && e.ready && &e.session_id == session_id)
+    }
+
+    fn sink_description(&self, name: &str) -> Option<String> {
+        self.sinks.values().find(|s| s.name == name).map(|s| s.description.clone())
+    }
+
+    fn default_sink_id(&self) -> Option<u32> {
+        let name = self.default_sink.as_deref()?;
+        self.sinks.iter().find(|(_, s)| s.name == name).map(|(id, _)| *id)
+    }
+}
+
+enum PwCommand {
+    SetVolume(u32, Vec<f32>),
+    SetMute(u32, bool),
+    SetDefaultSink(String),
+    MoveStream(u32, String), // stream node id, sink node name
+    Quit,
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn dict_str(props: Option<&DictRef>, key: &str) -> Option<String> {
+    let s = props?.get(key)?.trim();
+    if s.is_empty() { None } else { Some(s.to_string()) }
+}
+
+/// Node volumes are linear; pulse/pavucontrol show them on a cubic scale,
+/// so we do the same to keep the fader feel identical to the Pulse backend.
+fn volume_to_f32(volumes: &[f32]) -> f32 {
+    let max = volumes.iter().copied().fold(0.0f32, f32::max);
+    max.cbrt().clamp(0.0, 1.0)
+}
+
+fn volume_from_f32(volume: f32, channels: usize) -> Vec<f32> {
+    let v = volume.clamp(0.0, 1.0);
+    vec![v * v * v; channels.max(1)]
+}
+
+fn stream_from_props(id: u32, kind: SessionKind, props: Option<&DictRef>) -> StreamEntry {
+    let process_id = dict_str(props, "application.process.id")
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(0);
+    let binary = dict_str(props, "application.process.binary");
+
+    let name = if kind == SessionKind::Input {
+        dict_str(props, "node.description")
+            .or_else(|| dict_str(props, "node.nick"))
+            .or_else(|| dict_str(props, "node.name"))
+            .unwrap_or_else(|| format!("Input {id}"))
+    } else {
+        dict_str(props, "application.name")
+            .or_else(|| binary.clone())
+            .or_else(|| dict_str(props, "node.name"))
+            .unwrap_or_else(|| format!("Stream {id}"))
+    };
+
+    let exe_path = (process_id != 0)
+        .then(|| std::fs::read_link(format!("/proc/{process_id}/exe")).ok())
+        .flatten()
+        .map(|p| p.to_string_lossy().into_owned())
+        .or(binary);
+
+    let mut entry = StreamEntry {
+        kind,
+        session_id: SessionKey::new(""),
+        name,
+        process_id,
+        exe_path,
+        channels: 0,
+        volume: 0.0,
+        muted: false,
+        ready: false,
+    };
+    entry.session_id = entry.key_for(id);
+    entry
+}
+
+/// Node classes we expose as sessions.
+fn stream_kind(media_class: &str) -> Option<SessionKind> {
+    match media_class {
+        "Stream/Output/Audio" => Some(SessionKind::Playback),
+        "Stream/Input/Audio" => Some(SessionKind::Recording),
+        "Audio/Source" => Some(SessionKind::Input),
+        _ => None,
+    }
+}
+
+fn sink_from_props(props: Option<&DictRef>) -> SinkEntry {
+    let name = dict_str(props, "node.name").unwrap_or_default();
+    let description = dict_str(props, "node.description")
+        .or_else(|| dict_str(props, "node.nick"))
+        .unwrap_or_else(|| name.clone());
+    SinkEntry { name, description, channels: 0, volume: 0.0, muted: false }
+}
+
+/// Pull channelVolumes and mute out of a Props param pod.
+fn parse_props(pod: &Pod) -> (Option<Vec<f32>>, Option<bool>) {
+    let Ok((_, Value::Object(obj))) = PodDeserializer::deserialize_any_from(pod.as_bytes()) else {
+        return (None, None);
+    };
+    let mut volumes = None;
+    let mut muted = None;
+    for prop in obj.properties {
+        match (prop.key, prop.value) {
+            (pw::spa::sys::SPA_PROP_channelVolumes, Value::ValueArray(ValueArray::Float(v))) => volumes = Some(v),
+            (pw::spa::sys::SPA_PROP_mute, Value::Bool(m)) => muted = Some(m),
+            _ => {}
+        }
+    }
+    (volumes, muted)
+}
+
+fn props_pod(properties: Vec<Property>) -> Option<Vec<u8>> {
+    let obj = Value::Object(Object {
+        type_: pw::spa::sys::SPA_TYPE_OBJECT_Props,
+        id: pw::spa::sys::SPA_PARAM_Props,
+        properties,
+    });
+    PodSerializer::serialize(Cursor::new(Vec::new()), &obj)
+        .ok()
+        .map(|(cursor, _)| cursor.into_inner())
+}
+
+/// The default sink metadata value is a tiny JSON object: {"name":"alsa_output..."}
+fn metadata_name(value: &str) -> Option<String> {
+    let rest = &value[value.find("\"name\"")? + 6..];
+    let rest = &rest[rest.find('"')? + 1..];
+    Some(rest[..rest.find('"')?].to_string())
+}
+
+// ── Main loop thread ──────────────────────────────────────────────────────────
+
+/// Proxies only live on the loop thread; dropping one unbinds it.
+enum Bound {
+    Node(Node, NodeListener),
+    Metadata(Metadata, MetadataListener),
+}
+
+fn on_stream_params(state: &Arc<Mutex<PwState>>, id: u32, pod: &Pod) {
+    let (volumes, muted) = parse_props(pod);
+    if volumes.is_none() && muted.is_none() { return; }
+    let Ok(mut st) = state.lock() else { return };
+    let Some(prev) = st.streams.get(&id).cloned() else { return };
+
+    let mut entry = prev.clone();
+    if let Some(v) = &volumes {
+        entry.channels = v.len();
+        entry.volume = volume_to_f32(v);
+    }
+    if let Some(m) = muted { entry.muted = m; }
+    entry.ready = true;
+    st.streams.insert(id, entry.clone());
+
+    if !prev.ready {
+        if !st.group_has_other(id, &entry.session_id) {
+            st.emit(AudioUpdate::SessionAdded(entry.to_session()));
+        }
+        return;
+    }
+    if (prev.volume - entry.volume).abs() > 0.001 {
+        st.emit(AudioUpdate::VolumeChanged(entry.session_id.clone(), entry.volume));
+    }
+    if prev.muted != entry.muted {
+        st.emit(AudioUpdate::MuteChanged(entry.session_id, entry.muted));
+    }
+}
+
+/// Sinks carry the master volume; only the default one feeds the UI.
+fn on_sink_params(state: &Arc<Mutex<PwState>>, id: u32, pod: &Pod) {
+    let (volumes, muted) = parse_props(pod);
+    let Ok(mut st) = state.lock() else { return };
+    let is_default = st.default_sink_id() == Some(id);
+    let Some(sink) = st.sinks.get_mut(&id) else { return };
+
+    let prev = (sink.volume, sink.muted);
+    if let Some(v) = &volumes {
+        sink.channels = v.len();
+        sink.volume = volume_to_f32(v);
+    }
+    if let Some(m) = muted { sink.muted = m; }
+    let (volume, muted) = (sink.volume, sink.muted);
+
+    if !is_default { return; }
+    if (prev.0 - volume).abs() > 0.001 {
+        st.emit(AudioUpdate::EndpointVolumeChanged(volume));
+    }
+    if prev.1 != muted {
+        st.emit(AudioUpdate::EndpointMuteChanged(muted));
+    }
+}
+
+fn on_global(
+    registry: &pw::registry::Registry,
+    state: &Arc<Mutex<PwState>>,
+    bound: &Rc<RefCell<HashMap<u32, Bound>>>,
+    global: &GlobalObject<&DictRef>,
+) {
+    let id = global.id;
+    match global.type_ {
+        ObjectType::Node => {
+            let class = dict_str(global.props, "media.class").unwrap_or_default();
+            match (class.as_str(), stream_kind(&class)) {
+                (_, Some(kind)) => {
+                    let Ok(node) = registry.bind::<Node, _>(global) else { return };
+                    if let Ok(mut st) = state.lock() {
+                        st.streams.insert(id, stream_from_props(id, kind, global.props));
+                    }
+
+                    let info_state = Arc::clone(state);
+                    let param_state = Arc::clone(state);
+                    let listener = node
+                        .add_listener_local()
+                        .info(move |info| {
+                            // Global props are a subset; the full set fills in
+                            // the app name, but never rename a visible session
+                            let Ok(mut st) = info_state.lock() else { return };
+                            let fresh = stream_from_props(id, kind, info.props());
+                            if let Some(e) = st.streams.get_mut(&id) {
+                                if !e.ready {
+                                    e.session_id = fresh.session_id;
+                                    e.name = fresh.name;
+                                    e.process_id = fresh.process_id;
+                                    e.exe_path = fresh.exe_path;
+                                }
+                            }
+                        })
+                        .param(move |_, param_id, _, _, pod| {
+                            if param_id != ParamType::Props { return; }
+                            if let Some(pod) = pod {
+                                on_stream_params(&param_state, id, pod);
+                            }
+                        })
+                        .register();
+                    node.subscribe_params(&[ParamType::Props]);
+                    bound.borrow_mut().insert(id, Bound::Node(node, listener));
+                }
+                ("Audio/Sink", _) => {
+                    let Ok(node) = registry.bind::<Node, _>(global) else { return };
+                    if let Ok(mut st) = state.lock() {
+                        let sink = sink_from_props(global.props);
+                        st.emit(AudioUpdate::OutputDeviceAdded(sink.description.clone()));
+                        st.sinks.insert(id, sink);
+                    }
+
+                    let param_state = Arc::clone(state);
+                    let listener = node
+                        .add_listener_local()
+                        .param(move |_, param_id, _, _, pod| {
+                            if param_id != ParamType::Props { return; }
+                            if let Some(pod) = pod {
+                                on_sink_params(&param_state, id, pod);
+                            }
+                        })
+                        .register();
+                    node.subscribe_params(&[ParamType::Props]);
+                    bound.borrow_mut().insert(id, Bound::Node(node, listener));
+                }
+                _ => {}
+            }
+        }
+        ObjectType::Metadata => {
+            if dict_str(global.props, "metadata.name").as_deref() != Some("default") { return; }
+            let Ok(metadata) = registry.bind::<Metadata, _>(global) else { return };
+
+            let meta_state = Arc::clone(state);
+            let listener = metadata
+                .add_listener_local()
+                .property(move |_, key, _, value| {
+                    if key != Some(DEFAULT_SINK_KEY) { return 0; }
+                    let Ok(mut st) = meta_state.lock() else { return 0 };
+                    let name = value.and_then(metadata_name);
+                    if name.is_some() && name != st.default_sink {
+                        st.default_sink = name.clone();
+                        let description = name.as_deref()
+                            .and_then(|n| st.sink_description(n))
+                            .unwrap_or_else(|| "Unknown Device".into());
+                        st.emit(AudioUpdate::DefaultDeviceChanged(description));
+                        // The master target follows the new device
+                        if let Some(sink) = st.default_sink_id().and_then(|id| st.sinks.get(&id)) {
+                            let (volume, muted) = (sink.volume, sink.muted);
+                            st.emit(AudioUpdate::EndpointVolumeChanged(volume));
+                            st.emit(AudioUpdate::EndpointMuteChanged(muted));
+                        }
+                    }
+                    0
+                })
+                .register();
+            bound.borrow_mut().insert(id, Bound::Metadata(metadata, listener));
+        }
+        _ => {}
+    }
+}
+
+fn on_global_remove(state: &Arc<Mutex<PwState>>, bound: &Rc<RefCell<HashMap<u32, Bound>>>, id: u32) {
+    bound.borrow_mut().remove(&id);
+    let Ok(mut st) = state.lock() else { return };
+    if let Some(sink) = st.sinks.remove(&id) {
+        st.emit(AudioUpdate::OutputDeviceRemoved(sink.description));
+    }
+    if let Some(gone) = st.streams.remove(&id) {
+        if gone.ready && !st.group_has_other(id, &gone.session_id) {
+            st.emit(AudioUpdate::SessionRemoved(gone.session_id));
+        }
+    }
+}
+
+/// Only the "default" metadata object is bound.
+fn set_metadata(bound: &Rc<RefCell<HashMap<u32, Bound>>>, subject: u32, key: &str, type_: Option<&str>, value: &str) {
+    for b in bound.borrow().values() {
+        if let Bound::Metadata(metadata, _) = b {
+            metadata.set_property(subject, key, type_, Some(value));
+        }
+    }
+}
+
+fn on_command(bound: &Rc<RefCell<HashMap<u32, Bound>>>, mainloop: &pw::main_loop::MainLoop, cmd: PwCommand) {
+    let (id, properties) = match cmd {
+        PwCommand::SetVolume(id, volumes) => (id, vec![Property::new(
+            pw::spa::sys::SPA_PROP_channelVolumes,
+            Value::ValueArray(ValueArray::Float(volumes)),
+        )]),
+        PwCommand::SetMute(id, muted) => (id, vec![Property::new(
+            pw::spa::sys::SPA_PROP_mute,
+            Value::Bool(muted),
+        )]),
+        PwCommand::SetDefaultSink(name) => {
+            let value = serde_json::json!({ "name": name }).to_string();
+            set_metadata(bound, 0, CONFIGURED_SINK_KEY, Some("Spa:String:JSON"), &value);
+            return;
+        }
+        PwCommand::MoveStream(id, sink_name) => {
+            set_metadata(bound, id, TARGET_OBJECT_KEY, None, &sink_name);
+            return;
+        }
+        PwCommand::Quit => {
+            mainloop.quit();
+            return;
+        }
+    };
+    let Some(bytes) = props_pod(properties) else { return };
+    let Some(pod) = Pod::from_bytes(&bytes) else { return };
+    if let Some(Bound::Node(node, _)) = bound.borrow().get(&id) {
+        node.set_param(ParamType::Props, 0, pod);
+    }
+}
+
+type Connection = (pw::main_loop::MainLoop, pw::context::Context, pw::core::Core, Rc<pw::registry::Registry>);
+
+fn connect() -> Result<Connection, pw::Error> {
+    let mainloop = pw::main_loop::MainLoop::new(None)?;
+    let context = pw::context::Context::new(&mainloop)?;
+    let core = context.connect(None)?;
+    let registry = Rc::new(core.get_registry()?);
+    Ok((mainloop, context, core, registry))
+}
+
+fn run_loop(
+    state: Arc<Mutex<PwState>>,
+    commands: pw::channel::Receiver<PwCommand>,
+    ready: mpsc::Sender<Result<(), String>>,
+) {
+    pw::init();
+
+    let (mainloop, _context, core, registry) = match connect() {
+        Ok(parts) => parts,
+        Err(e) => {
+            let _ = ready.send(Err(e.to_string()));
+            return;
+        }
+    };
+
+    let bound: Rc<RefCell<HashMap<u32, Bound>>> = Rc::new(RefCell::new(HashMap::new()));
+
+    let registry_weak = Rc::downgrade(&registry);
+    let global_state = Arc::clone(&state);
+    let global_bound = Rc::clone(&bound);
+    let remove_state = Arc::clone(&state);
+    let remove_bound = Rc::clone(&bound);
+    let _registry_listener = registry
+        .add_listener_local()
+        .global(move |global| {
+            if let Some(registry) = registry_weak.upgrade() {
+                on_global(&registry, &global_state, &global_bound, global);
+            }
+        })
+        .global_remove(move |id| on_global_remove(&remove_state, &remove_bound, id))
+        .register();
+
+    // Two round-trips: the first flushes the registry globals, the second
+    // the info/params of the nodes we bound while handling them. Only then
+    // does initialize() return, so the first get_sessions() isn't empty.
+    let pending = Rc::new(Cell::new(core.sync(0).ok()));
+    let synced_once = Rc::new(Cell::new(false));
+    let ready = RefCell::new(Some(ready));
+    let done_core = core.clone();
+    let done_loop = mainloop.clone();
+    let ready_state = Arc::clone(&state);
+    let lost: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+    let error_lost = Rc::clone(&lost);
+    let _core_listener = core
+        .add_listener_local()
+        .done(move |id, seq| {
+            if id != pw::core::PW_ID_CORE || pending.get() != Some(seq) { return; }
+            if !synced_once.replace(true) {
+                pending.set(done_core.sync(0).ok());
+            } else if let Some(tx) = ready.borrow_mut().take() {
+                if let Ok(mut st) = ready_state.lock() { st.connected = true; }
+                let _ = tx.send(Ok(()));
+            }
+        })
+        .error(move |id, _, _, message| {
+            // An error on the core object means the daemon went away
+            if id == pw::core::PW_ID_CORE {
+                eprintln!("PipeWire core error: {message}");
+                error_lost.replace(Some(message.to_string()));
+                done_loop.quit();
+            }
+        })
+        .register();
+
+    let cmd_bound = Rc::clone(&bound);
+    let cmd_loop = mainloop.clone();
+    let _commands = commands.attach(mainloop.loop_(), move |cmd| on_command(&cmd_bound, &cmd_loop, cmd));
+
+    mainloop.run();
+
+    // Proxies must go before the core they were created on
+    bound.borrow_mut().clear();
+
+    // Object ids don't survive a reconnect; start the next loop empty
+    let Ok(mut st) = state.lock() else { return };
+    st.connected = false;
+    st.streams.clear();
+    st.sinks.clear();
+    st.default_sink = None;
+    // Until initialize() runs again, sessions and writes report the backend
+    // as unavailable and the app retries
+    if let Some(message) = lost.take() {
+        st.emit(AudioUpdate::BackendLost(AudioError::BackendUnavailable(
+            format!("PipeWire connection lost: {message}"),
+        )));
+    }
+}
+
+fn no_default_sink() -> AudioError {
+    AudioError::Other("no default sink".into())
+}
+
+// ── Backend ───────────────────────────────────────────────────────────────────
+
+pub struct PipeWireAudioBackend {
+    state: Arc<Mutex<PwState>>,
+    commands: Option<pw::channel::Sender<PwCommand>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl PipeWireAudioBackend {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(PwState::default())),
+            commands: None,
+            thread: None,
+        }
+    }
+
+    fn lock_state(&self) -> Result<std::sync::MutexGuard<'_, PwState>, AudioError> {
+        self.state.lock().map_err(|_| AudioError::Other("PipeWire state poisoned".into()))
+    }
+
+    fn send(&self, cmd: PwCommand) -> Result<(), AudioError> {
+        let tx = self.commands.as_ref()
+            .ok_or_else(|| AudioError::BackendUnavailable("PipeWire backend not initialized".into()))?;
+        tx.send(cmd).map_err(|_| AudioError::BackendUnavailable("PipeWire main loop is gone".into()))
+    }
+
+    fn sessions_of(&self, kind: SessionKind) -> Result<Vec<AudioSession>, AudioError> {
+        let mut st = self.lock_state()?;
+        if !st.connected {
+            return Err(AudioError::BackendUnavailable("not connected to PipeWire".into()));
+        }
+        // Grouping rules may have changed since the streams were seen
+        for (id, entry) in st.streams.iter_mut() {
+            entry.session_id = entry.key_for(*id);
+        }
+        let mut ids: Vec<&u32> = st.streams.iter()
+            .filter(|(_, e)| e.kind == kind)
+            .map(|(id, _)| id)
+            .collect();
+        ids.sort();
+
+        let mut sessions: Vec<AudioSession> = Vec::new();
+        for id in ids {
+            let entry = &st.streams[id];
+            if entry.ready && !sessions.iter().any(|s| s.id == entry.session_id) {
+                sessions.push(entry.to_session());
+            }
+        }
+        Ok(sessions)
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(tx) = self.commands.take() {
+            let _ = tx.send(PwCommand::Quit);
+        }
+        if let Some(handle) = self.thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for PipeWireAudioBackend {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl AudioBackend for PipeWireAudioBackend {
+    fn initialize(&mut self) -> Result<(), AudioError> {
+        self.shutdown();
+        let (cmd_tx, cmd_rx) = pw::channel::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let state = Arc::clone(&self.state);
+        self.thread = Some(thread::spawn(move || run_loop(state, cmd_rx, ready_tx)));
+        self.commands = Some(cmd_tx);
+
+        match ready_rx.recv_timeout(Duration::from_millis(INIT_TIMEOUT_MS)) {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(AudioError::BackendUnavailable(format!("PipeWire connect failed: {e}"))),
+            Err(_) => Err(AudioError::BackendUnavailable("PipeWire did not answer".into())),
+        }
+    }
+
+    fn get_sessions(&self) -> Result<Vec<AudioSession>, AudioError> {
+        self.sessions_of(SessionKind::Playback)
+    }
+
+    fn get_recording_streams(&self) -> Result<Vec<AudioSession>, AudioError> {
+        self.sessions_of(SessionKind::Recording)
+    }
+
+    fn get_input_devices(&self) -> Result<Vec<AudioSession>, AudioError> {
+        self.sessions_of(SessionKind::Input)
+    }
+
+    fn set_volume(&mut self, session: &SessionKey, volume: f32) -> Result<(), AudioError> {
+        let targets: Vec<(u32, usize)> = {
+            let mut st = self.lock_state()?;
+            st.streams.iter_mut()
+                .filter(|(_, e)| e.ready && &e.session_id == session)
+                .map(|(i, e)| {
+                    e.volume = volume;
+                    (*i, e.channels)
+                })
+                .collect()
+        };
+        if targets.is_empty() { return Err(AudioError::SessionGone(session.clone())); }
+        for (id, channels) in targets {
+            self.send(PwCommand::SetVolume(id, volume_from_f32(volume, channels)))?;
+        }
+        Ok(())
+    }
+
+    fn set_mute(&mut self, session: &SessionKey, muted: bool) -> Result<(), AudioError> {
+        let targets: Vec<u32> = {
+            let mut st = self.lock_state()?;
+            st.streams.iter_mut()
+                .filter(|(_, e)| e.ready && &e.session_id == session)
+                .map(|(i, e)| {
+                    e.muted = muted;
+                    *i
+                })
+                .collect()
+        };
+        if targets.is_empty() { return Err(AudioError::SessionGone(session.clone())); }
+        for id in targets {
+            self.send(PwCommand::SetMute(id, muted))?;
+        }
+        Ok(())
+    }
+
+    fn move_session_to_device(&mut self, session: &SessionKey, device: &str) -> Result<(), AudioError> {
+        if session.kind != SessionKind::Playback {
+            return Err(AudioError::NotSupported(format!("Routing {} streams", session.kind.as_str())));
+        }
+        let (sink_name, targets) = {
+            let st = self.lock_state()?;
+            let sink_name = st.sinks.values()
+                .find(|s| s.description == device)
+                .map(|s| s.name.clone())
+                .ok_or_else(|| AudioError::Other(format!("no output device named {device}")))?;
+            let targets: Vec<u32> = st.streams.iter()
+                .filter(|(_, e)| e.ready && &e.session_id == session)
+                .map(|(i, _)| *i)
+                .collect();
+            (sink_name, targets)
+        };
+        if targets.is_empty() { return Err(AudioError::SessionGone(session.clone())); }
+        for id in targets {
+            self.send(PwCommand::MoveStream(id, sink_name.clone()))?;
+        }
+        Ok(())
+    }
+
+    fn get_endpoint_volume(&self) -> Result<(f32, bool), AudioError> {
+        let st = self.lock_state()?;
+        let sink = st.default_sink_id()
+            .and_then(|id| st.sinks.get(&id))
+            .ok_or_else(no_default_sink)?;
+        Ok((sink.volume, sink.muted))
+    }
+
+    fn set_endpoint_volume(&mut self, volume: f32) -> Result<(), AudioError> {
+        let (id, channels) = {
+            let mut st = self.lock_state()?;
+            let id = st.default_sink_id().ok_or_else(no_default_sink)?;
+            let sink = st.sinks.get_mut(&id).ok_or_else(no_default_sink)?;
+            sink.volume = volume;
+            (id, sink.channels)
+        };
+        self.send(PwCommand::SetVolume(id, volume_from_f32(volume, channels)))
+    }
+
+    fn set_endpoint_mute(&mut self, muted: bool) -> Result<(), AudioError> {
+        let id = {
+            let mut st = self.lock_state()?;
+            let id = st.default_sink_id().ok_or_else(no_default_sink)?;
+            let sink = st.sinks.get_mut(&id).ok_or_else(no_default_sink)?;
+            sink.muted = muted;
+            id
+        };
+        self.send(PwCommand::SetMute(id, muted))
+    }
+
+    fn start_listening(&mut self, sender: mpsc::Sender<AudioUpdate>) -> Result<(), AudioError> {
+        self.lock_state()?.sender = Some(sender);
+        Ok(())
+    }
+
+    fn stop_listening(&mut self) -> Result<(), AudioError> {
+        self.lock_state()?.sender = None;
+        Ok(())
+    }
+
+    fn get_output_devices(&self) -> Result<Vec<String>, AudioError> {
+        let st = self.lock_state()?;
+        let mut sinks: Vec<(&u32, &SinkEntry)> = st.sinks.iter().collect();
+        sinks.sort_by_key(|(id, _)| **id);
+        Ok(sinks.into_iter().map(|(_, s)| s.description.clone()).collect())
+    }
+
+    fn get_default_output_device(&self) -> Option<String> {
+        let st = self.state.lock().ok()?;
+        st.sink_description(st.default_sink.as_deref()?)
+    }
+
+    fn set_default_output_device(&mut self, name: &str) -> Result<(), AudioError> {
+        let sink_name = {
+            let st = self.lock_state()?;
+            st.sinks.values()
+                .find(|s| s.description == name)
+                .map(|s| s.name.clone())
+                .ok_or_else(|| AudioError::Other(format!("no output device named {name}")))?
+        };
+        // Confirmed when the session manager updates default.audio.sink
+        self.send(PwCommand::SetDefaultSink(sink_name))
+    }
+}
@@ -0,0 +1,634 @@
+use crate::audio::{AudioBackend, AudioError, AudioLevel, AudioSession, AudioUpdate, DeviceState, SessionKey, SessionKind};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const SCENARIO_ENV: &str = "FADERFLOW_SIMULATE";
+const SCENARIO_FLAG: &str = "--simulate";
+const TICK_MS: u64 = 20;
+const METER_INTERVAL_MS: u64 = 33;
+
+/// Scenario file from `--simulate <file>` (or `--simulate=<file>`), falling
+/// back to the FADERFLOW_SIMULATE env var.
+pub fn scenario_path() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == SCENARIO_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--simulate=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    std::env::var_os(SCENARIO_ENV)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+// ── Scenario ──────────────────────────────────────────────────────────────────
+
+/// One scripted stream. Events address it by `name`; the session key is
+/// derived like a real backend would, so grouping rules apply here too.
+#[derive(Debug, Clone)]
+struct SimStream {
+    name: String,
+    kind: SessionKind,
+    session: AudioSession,
+}
+
+impl SimStream {
+    fn rekey(&mut self) {
+        let s = &self.session;
+        self.session.id = if self.kind == SessionKind::Input {
+            SessionKey::input_device(s.display_name.clone())
+        } else {
+            let exe = s.exe_path.as_deref().unwrap_or(&s.display_name);
+            SessionKey::for_stream(exe, s.process_id, self.name.clone()).with_kind(self.kind)
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    Add(SimStream),
+    Remove(String),
+    Volume(String, f32),
+    Mute(String, bool),
+    DefaultDevice(String),
+    MasterVolume(f32),
+    MasterMute(bool),
+    DeviceAdd(String),
+    DeviceRemove(String),
+    DeviceState(String, DeviceState),
+}
+
+#[derive(Debug, Clone)]
+struct TimedAction {
+    at: Duration,
+    action: Action,
+}
+
+/// Parsed scenario file. TOML and JSON share one shape:
+///
+/// ```toml
+/// devices = ["Speakers", "Headphones"]
+/// default_device = "Speakers"
+/// master_volume = 0.7       # optional, with master_muted
+/// repeat = true
+///
+/// [[sessions]]
+/// name = "Spotify"
+/// exe = "/usr/bin/spotify"   # optional, like pid; feeds the session key
+/// kind = "playback"          # or "recording" / "input" (a microphone)
+/// volume = 0.8
+///
+/// [[events]]
+/// at_ms = 2000
+/// action = "volume"      # add | remove | volume | mute | default_device
+/// session = "Spotify"    #   | master_volume | master_mute (no session)
+///                        #   | device_add | device_remove | device_state
+///                        #     (these take `device`, device_state also
+///                        #     `state` = "active" | "unplugged")
+/// volume = 0.3
+/// ```
+#[derive(Debug, Clone, Default)]
+struct Scenario {
+    sessions: Vec<SimStream>,
+    devices: Vec<String>,
+    default_device: Option<String>,
+    master_volume: f32,
+    master_muted: bool,
+    events: Vec<TimedAction>,
+    repeat: bool,
+}
+
+fn get_str(t: &toml::map::Map<String, toml::Value>, key: &str) -> Option<String> {
+    t.get(key).and_then(|v| v.as_str()).map(str::to_string)
+}
+
+fn get_f32(t: &toml::map::Map<String, toml::Value>, key: &str) -> Option<f32> {
+    match t.get(key)? {
+        toml::Value::Float(f) => Some(*f as f32),
+        toml::Value::Integer(i) => Some(*i as f32),
+        _ => None,
+    }
+}
+
+fn get_bool(t: &toml::map::Map<String, toml::Value>, key: &str) -> Option<bool> {
+    t.get(key).and_then(|v| v.as_bool())
+}
+
+fn parse_session(t: &toml::map::Map<String, toml::Value>) -> Result<SimStream, String> {
+    let name = get_str(t, "name").or_else(|| get_str(t, "session"))
+        .ok_or("session is missing `name`")?;
+    let pid = t.get("pid").and_then(|v| v.as_integer()).unwrap_or(0) as u32;
+    let mut session = AudioSession::new(
+        SessionKey::new(""),
+        get_str(t, "display_name").unwrap_or_else(|| name.clone()),
+        get_f32(t, "volume").unwrap_or(1.0).clamp(0.0, 1.0),
+        get_bool(t, "muted").unwrap_or(false),
+        pid,
+    );
+    session.exe_path = get_str(t, "exe");
+    let kind = match get_str(t, "kind") {
+        Some(k) => SessionKind::parse(&k).ok_or(format!("unknown session kind `{k}`"))?,
+        None => SessionKind::Playback,
+    };
+    let mut stream = SimStream { name, kind, session };
+    stream.rekey();
+    Ok(stream)
+}
+
+fn parse_event(t: &toml::map::Map<String, toml::Value>) -> Result<TimedAction, String> {
+    let at_ms = t.get("at_ms").and_then(|v| v.as_integer()).ok_or("event is missing `at_ms`")?;
+    let kind = get_str(t, "action").ok_or("event is missing `action`")?;
+    let session = || get_str(t, "session").ok_or(format!("`{kind}` event needs `session`"));
+    let device = || get_str(t, "device").ok_or(format!("`{kind}` event needs `device`"));
+
+    let action = match kind.as_str() {
+        "add" => Action::Add(parse_session(t)?),
+        "remove" => Action::Remove(session()?),
+        "volume" => Action::Volume(
+            session()?,
+            get_f32(t, "volume").ok_or("`volume` event needs `volume`")?.clamp(0.0, 1.0),
+        ),
+        "mute" => Action::Mute(session()?, get_bool(t, "muted").unwrap_or(true)),
+        "default_device" => Action::DefaultDevice(
+            get_str(t, "device").ok_or("`default_device` event needs `device`")?,
+        ),
+        "master_volume" => Action::MasterVolume(
+            get_f32(t, "volume").ok_or("`master_volume` event needs `volume`")?.clamp(0.0, 1.0),
+        ),
+        "master_mute" => Action::MasterMute(get_bool(t, "muted").unwrap_or(true)),
+        "device_add" => Action::DeviceAdd(device()?),
+        "device_remove" => Action::DeviceRemove(device()?),
+        "device_state" => Action::DeviceState(device()?, match get_str(t, "state").as_deref() {
+            Some("unplugged") => DeviceState::Unplugged,
+            Some("active") | None => DeviceState::Active,
+            Some(other) => return Err(format!("unknown device state `{other}`")),
+        }),
+        other => return Err(format!("unknown action `{other}`")),
+    };
+    Ok(TimedAction { at: Duration::from_millis(at_ms.max(0) as u64), action })
+}
+
+impl Scenario {
+    fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path)?;
+        let is_json = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        Self::parse(&text, is_json)
+    }
+
+    fn parse(text: &str, is_json: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let value: toml::Value = if is_json {
+            serde_json::from_str(text)?
+        } else {
+            toml::from_str(text)?
+        };
+        let toml::Value::Table(root) = value else {
+            return Err("scenario root must be a table/object".into());
+        };
+        Ok(Self::from_table(&root)?)
+    }
+
+    fn from_table(root: &toml::map::Map<String, toml::Value>) -> Result<Self, String> {
+        let tables = |key: &str| -> Vec<toml::map::Map<String, toml::Value>> {
+            root.get(key)
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|v| v.as_table().cloned()).collect())
+                .unwrap_or_default()
+        };
+
+        let sessions = tables("sessions").iter().map(parse_session).collect::<Result<Vec<_>, _>>()?;
+        let mut events = tables("events").iter().map(parse_event).collect::<Result<Vec<_>, _>>()?;
+        events.sort_by_key(|e| e.at);
+
+        let devices: Vec<String> = root.get("devices")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+        let default_device = get_str(root, "default_device").or_else(|| devices.first().cloned());
+
+        Ok(Self {
+            sessions,
+            devices,
+            default_device,
+            master_volume: get_f32(root, "master_volume").unwrap_or(1.0).clamp(0.0, 1.0),
+            master_muted: get_bool(root, "master_muted").unwrap_or(false),
+            events,
+            repeat: get_bool(root, "repeat").unwrap_or(false),
+        })
+    }
+}
+
+// ── Shared state ──────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct SimState {
+    sessions: Vec<SimStream>,
+    devices: Vec<String>,
+    unplugged: Vec<String>,
+    default_device: Option<String>,
+    master_volume: f32,
+    master_muted: bool,
+}
+
+impl SimState {
+    /// Apply a scripted action and return the update a real mixer would emit.
+    fn apply(&mut self, action: Action) -> Option<AudioUpdate> {
+        match action {
+            Action::Add(stream) => {
+                if let Some(existing) = self.sessions.iter_mut().find(|s| s.name == stream.name) {
+                    *existing = stream;
+                    return None;
+                }
+                let shared = self.has_key(&stream.session.id);
+                let session = stream.session.clone();
+                self.sessions.push(stream);
+                (!shared).then_some(AudioUpdate::SessionAdded(session))
+            }
+            Action::Remove(name) => {
+                let idx = self.sessions.iter().position(|s| s.name == name)?;
+                let key = self.sessions.remove(idx).session.id;
+                (!self.has_key(&key)).then_some(AudioUpdate::SessionRemoved(key))
+            }
+            Action::Volume(name, volume) => {
+                let stream = self.sessions.iter_mut().find(|s| s.name == name)?;
+                stream.session.volume = volume;
+                Some(AudioUpdate::VolumeChanged(stream.session.id.clone(), volume))
+            }
+            Action::Mute(name, muted) => {
+                let stream = self.sessions.iter_mut().find(|s| s.name == name)?;
+                stream.session.is_muted = muted;
+                Some(AudioUpdate::MuteChanged(stream.session.id.clone(), muted))
+            }
+            Action::DefaultDevice(name) => {
+                if !self.devices.contains(&name) {
+                    self.devices.push(name.clone());
+                }
+                self.default_device = Some(name.clone());
+                Some(AudioUpdate::DefaultDeviceChanged(name))
+            }
+            Action::MasterVolume(volume) => {
+                self.master_volume = volume;
+                Some(AudioUpdate::EndpointVolumeChanged(volume))
+            }
+            Action::MasterMute(muted) => {
+                self.master_muted = muted;
+                Some(AudioUpdate::EndpointMuteChanged(muted))
+            }
+            Action::DeviceAdd(name) => {
+                if self.devices.contains(&name) { return None; }
+                self.devices.push(name.clone());
+                Some(AudioUpdate::OutputDeviceAdded(name))
+            }
+            Action::DeviceRemove(name) => {
+                let idx = self.devices.iter().position(|d| *d == name)?;
+                self.devices.remove(idx);
+                self.unplugged.retain(|d| *d != name);
+                Some(AudioUpdate::OutputDeviceRemoved(name))
+            }
+            Action::DeviceState(name, state) => {
+                if !self.devices.contains(&name) { return None; }
+                self.unplugged.retain(|d| *d != name);
+                if state == DeviceState::Unplugged {
+                    self.unplugged.push(name.clone());
+                }
+                Some(AudioUpdate::OutputDeviceStateChanged(name, state))
+            }
+        }
+    }
+
+    fn has_key(&self, key: &SessionKey) -> bool {
+        self.sessions.iter().any(|s| &s.session.id == key)
+    }
+
+    fn streams_mut<'a>(&'a mut self, key: &'a SessionKey) -> impl Iterator<Item = &'a mut AudioSession> {
+        self.sessions.iter_mut().map(|s| &mut s.session).filter(move |s| &s.id == key)
+    }
+}
+
+fn run_timeline(
+    state: Arc<Mutex<SimState>>,
+    events: Vec<TimedAction>,
+    repeat: bool,
+    sender: mpsc::Sender<AudioUpdate>,
+    cancel: Arc<AtomicBool>,
+) {
+    loop {
+        let start = Instant::now();
+        for event in &events {
+            while start.elapsed() < event.at {
+                if cancel.load(Ordering::Relaxed) { return; }
+                thread::sleep(Duration::from_millis(TICK_MS).min(event.at.saturating_sub(start.elapsed())));
+            }
+            if cancel.load(Ordering::Relaxed) { return; }
+
+            let Ok(mut st) = state.lock() else { return };
+            if let Some(update) = st.apply(event.action.clone()) {
+                if sender.send(update).is_err() { return; }
+            }
+        }
+        if !repeat || events.is_empty() { return; }
+    }
+}
+
+/// Made-up but lively levels: each unmuted stream wobbles at its own pace,
+/// scaled by its volume; the master follows the loudest playback stream.
+fn run_meter(state: Arc<Mutex<SimState>>, sender: mpsc::Sender<AudioUpdate>, cancel: Arc<AtomicBool>) {
+    let start = Instant::now();
+    while !cancel.load(Ordering::Relaxed) {
+        thread::sleep(Duration::from_millis(METER_INTERVAL_MS));
+        let t = start.elapsed().as_secs_f32();
+        let (levels, endpoint) = {
+            let Ok(st) = state.lock() else { return };
+            let mut levels: Vec<(SessionKey, AudioLevel)> = Vec::new();
+            let mut loudest = AudioLevel::default();
+            for (i, stream) in st.sessions.iter().enumerate() {
+                let s = &stream.session;
+                let rms = if s.is_muted { 0.0 } else {
+                    s.volume * (0.45 + 0.3 * (t * (1.7 + 0.6 * i as f32) + i as f32).sin().abs())
+                };
+                let level = AudioLevel { peak: (rms * 1.4).min(1.0), rms };
+                if stream.kind == SessionKind::Playback { loudest = loudest.max(level); }
+                match levels.iter_mut().find(|(k, _)| *k == s.id) {
+                    Some((_, l)) => *l = l.max(level),
+                    None => levels.push((s.id.clone(), level)),
+                }
+            }
+            let gain = if st.master_muted { 0.0 } else { st.master_volume };
+            (levels, AudioLevel { peak: loudest.peak * gain, rms: loudest.rms * gain })
+        };
+        if sender.send(AudioUpdate::SessionLevels(levels)).is_err() { return; }
+        if sender.send(AudioUpdate::EndpointLevel(endpoint)).is_err() { return; }
+    }
+}
+
+// ── Backend ───────────────────────────────────────────────────────────────────
+
+/// Scripted stand-in for the OS mixer. Scenario actions show up exactly like
+/// external changes from a real backend; our own set_volume/set_mute calls
+/// only touch the state, just as the real ones don't echo.
+pub struct SimulatedAudioBackend {
+    path: PathBuf,
+    scenario: Scenario,
+    state: Arc<Mutex<SimState>>,
+    listener_cancel: Option<Arc<AtomicBool>>,
+    meter_cancel: Option<Arc<AtomicBool>>,
+    sender: Option<mpsc::Sender<AudioUpdate>>,
+}
+
+impl SimulatedAudioBackend {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            scenario: Scenario::default(),
+            state: Arc::new(Mutex::new(SimState::default())),
+            listener_cancel: None,
+            meter_cancel: None,
+            sender: None,
+        }
+    }
+
+    fn lock_state(&self) -> Result<std::sync::MutexGuard<'_, SimState>, AudioError> {
+        self.state.lock().map_err(|_| AudioError::Other("simulated state poisoned".into()))
+    }
+
+    fn sessions_of(&self, kind: SessionKind) -> Result<Vec<AudioSession>, AudioError> {
+        let mut st = self.lock_state()?;
+        let mut sessions: Vec<AudioSession> = Vec::new();
+        for stream in st.sessions.iter_mut().filter(|s| s.kind == kind) {
+            stream.rekey();
+            if !sessions.iter().any(|s| s.id == stream.session.id) {
+                sessions.push(stream.session.clone());
+            }
+        }
+        Ok(sessions)
+    }
+}
+
+impl AudioBackend for SimulatedAudioBackend {
+    fn initialize(&mut self) -> Result<(), AudioError> {
+        self.scenario = Scenario::load(&self.path)
+            .map_err(|e| AudioError::BackendUnavailable(format!("scenario {}: {e}", self.path.display())))?;
+        let mut st = self.lock_state()?;
+        st.sessions = self.scenario.sessions.clone();
+        st.devices = self.scenario.devices.clone();
+        st.unplugged.clear();
+        st.default_device = self.scenario.default_device.clone();
+        st.master_volume = self.scenario.master_volume;
+        st.master_muted = self.scenario.master_muted;
+        Ok(())
+    }
+
+    fn get_sessions(&self) -> Result<Vec<AudioSession>, AudioError> {
+        self.sessions_of(SessionKind::Playback)
+    }
+
+    fn get_recording_streams(&self) -> Result<Vec<AudioSession>, AudioError> {
+        self.sessions_of(SessionKind::Recording)
+    }
+
+    fn get_input_devices(&self) -> Result<Vec<AudioSession>, AudioError> {
+        self.sessions_of(SessionKind::Input)
+    }
+
+    fn set_volume(&mut self, session: &SessionKey, volume: f32) -> Result<(), AudioError> {
+        let mut st = self.lock_state()?;
+        if !st.has_key(session) { return Err(AudioError::SessionGone(session.clone())); }
+        for s in st.streams_mut(session) {
+            s.volume = volume.clamp(0.0, 1.0);
+        }
+        Ok(())
+    }
+
+    fn set_mute(&mut self, session: &SessionKey, muted: bool) -> Result<(), AudioError> {
+        let mut st = self.lock_state()?;
+        if !st.has_key(session) { return Err(AudioError::SessionGone(session.clone())); }
+        for s in st.streams_mut(session) {
+            s.is_muted = muted;
+        }
+        Ok(())
+    }
+
+    fn set_balance(&mut self, session: &SessionKey, balance: f32) -> Result<(), AudioError> {
+        if session.kind != SessionKind::Playback {
+            return Err(AudioError::NotSupported(format!("Balance on {} streams", session.kind.as_str())));
+        }
+        let mut st = self.lock_state()?;
+        if !st.has_key(session) { return Err(AudioError::SessionGone(session.clone())); }
+        for s in st.streams_mut(session) {
+            s.balance = balance.clamp(-1.0, 1.0);
+        }
+        Ok(())
+    }
+
+    fn move_session_to_device(&mut self, session: &SessionKey, device: &str) -> Result<(), AudioError> {
+        // The scripted mixer has no real routing; just check the move is valid
+        let st = self.lock_state()?;
+        if !st.devices.iter().any(|d| d == device) {
+            return Err(AudioError::Other(format!("no output device named {device}")));
+        }
+        if session.kind != SessionKind::Playback {
+            return Err(AudioError::NotSupported(format!("Routing {} streams", session.kind.as_str())));
+        }
+        if !st.has_key(session) {
+            return Err(AudioError::SessionGone(session.clone()));
+        }
+        Ok(())
+    }
+
+    fn get_endpoint_volume(&self) -> Result<(f32, bool), AudioError> {
+        let st = self.lock_state()?;
+        Ok((st.master_volume, st.master_muted))
+    }
+
+    fn set_endpoint_volume(&mut self, volume: f32) -> Result<(), AudioError> {
+        self.lock_state()?.master_volume = volume.clamp(0.0, 1.0);
+        Ok(())
+    }
+
+    fn set_endpoint_mute(&mut self, muted: bool) -> Result<(), AudioError> {
+        self.lock_state()?.master_muted = muted;
+        Ok(())
+    }
+
+    fn start_listening(&mut self, sender: mpsc::Sender<AudioUpdate>) -> Result<(), AudioError> {
+        self.stop_listening()?;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let state = Arc::clone(&self.state);
+        let events = self.scenario.events.clone();
+        let repeat = self.scenario.repeat;
+        let cancel_clone = Arc::clone(&cancel);
+        self.sender = Some(sender.clone());
+        thread::spawn(move || run_timeline(state, events, repeat, sender, cancel_clone));
+        self.listener_cancel = Some(cancel);
+        Ok(())
+    }
+
+    fn stop_listening(&mut self) -> Result<(), AudioError> {
+        if let Some(cancel) = self.listener_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        self.sender = None;
+        Ok(())
+    }
+
+    fn start_metering(&mut self, sender: mpsc::Sender<AudioUpdate>) -> Result<(), AudioError> {
+        self.stop_metering()?;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let state = Arc::clone(&self.state);
+        let cancel_clone = Arc::clone(&cancel);
+        thread::spawn(move || run_meter(state, sender, cancel_clone));
+        self.meter_cancel = Some(cancel);
+        Ok(())
+    }
+
+    fn stop_metering(&mut self) -> Result<(), AudioError> {
+        if let Some(cancel) = self.meter_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn get_output_devices(&self) -> Result<Vec<String>, AudioError> {
+        let st = self.lock_state()?;
+        Ok(st.devices.iter().filter(|d| !st.unplugged.contains(d)).cloned().collect())
+    }
+
+    fn get_default_output_device(&self) -> Option<String> {
+        self.state.lock().ok()?.default_device.clone()
+    }
+
+    fn set_default_output_device(&mut self, name: &str) -> Result<(), AudioError> {
+        let update = {
+            let mut st = self.lock_state()?;
+            if !st.devices.iter().any(|d| d == name) {
+                return Err(AudioError::Other(format!("no output device named {name}")));
+            }
+            st.apply(Action::DefaultDevice(name.to_string()))
+        };
+        // Confirm like the OS would, through the update channel
+        if let (Some(tx), Some(update)) = (&self.sender, update) {
+            let _ = tx.send(update);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_toml_scenario() {
+        let scenario = Scenario::parse(r#"
+            devices = ["Speakers", "Headphones"]
+            master_volume = 0.7
+            repeat = true
+
+            [[sessions]]
+            name = "Spotify"
+            exe = "/usr/bin/spotify"
+            volume = 0.8
+
+            [[sessions]]
+            name = "Mic"
+            kind = "input"
+
+            [[events]]
+            at_ms = 2000
+            action = "volume"
+            session = "Spotify"
+            volume = 1.5
+
+            [[events]]
+            at_ms = 500
+            action = "device_state"
+            device = "Headphones"
+            state = "unplugged"
+        "#, false).unwrap();
+
+        assert_eq!(scenario.devices, ["Speakers", "Headphones"]);
+        assert_eq!(scenario.default_device.as_deref(), Some("Speakers"));
+        assert_eq!(scenario.master_volume, 0.7);
+        assert!(scenario.repeat);
+        assert_eq!(scenario.sessions.len(), 2);
+        assert_eq!(scenario.sessions[0].session.volume, 0.8);
+        assert_eq!(scenario.sessions[1].kind, SessionKind::Input);
+
+        // Sorted by time, volumes clamped
+        assert_eq!(scenario.events[0].at, Duration::from_millis(500));
+        assert!(matches!(&scenario.events[0].action,
+            Action::DeviceState(d, DeviceState::Unplugged) if d == "Headphones"));
+        assert!(matches!(&scenario.events[1].action,
+            Action::Volume(s, v) if s == "Spotify" && *v == 1.0));
+    }
+
+    #[test]
+    fn parses_the_same_shape_from_json() {
+        let scenario = Scenario::parse(r#"{
+            "sessions": [{ "name": "Game", "pid": 42 }],
+            "events": [{ "at_ms": 0, "action": "remove", "session": "Game" }]
+        }"#, true).unwrap();
+
+        assert_eq!(scenario.sessions[0].session.process_id, 42);
+        assert_eq!(scenario.master_volume, 1.0);
+        assert!(!scenario.repeat);
+        assert!(matches!(&scenario.events[0].action, Action::Remove(s) if s == "Game"));
+    }
+
+    #[test]
+    fn rejects_malformed_scenarios() {
+        let err = |text: &str, is_json: bool| Scenario::parse(text, is_json).unwrap_err().to_string();
+
+        assert!(err("[[events]]\nat_ms = 1\naction = \"explode\"", false).contains("unknown action"));
+        assert!(err("[[events]]\naction = \"mute\"\nsession = \"a\"", false).contains("at_ms"));
+        assert!(err("[[events]]\nat_ms = 1\naction = \"volume\"", false).contains("needs `session`"));
+        assert!(err("[[sessions]]\nname = \"a\"\nkind = \"loud\"", false).contains("unknown session kind"));
+        assert!(err("[1, 2]", true).contains("root must be a table"));
+        Scenario::parse("devices = [", false).unwrap_err();
+    }
+}
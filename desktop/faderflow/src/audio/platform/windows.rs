@@ -1,11 +1,13 @@
 #[cfg(target_os = "windows")]
-use crate::audio::{AudioBackend, AudioSession, AudioUpdate};
-use std::collections::HashMap;
+use crate::audio::{balance_from_sides, balance_sides, AudioBackend, AudioError, AudioSession, AudioUpdate, SessionKey, SessionKind};
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex, OnceLock, mpsc};
+use std::thread;
 use windows::core::GUID;
 use windows::core::implement;
 use windows::core::PCWSTR;
 use windows::Win32::Media::Audio::*;
+use windows::Win32::Media::Audio::Endpoints::*;
 use windows::Win32::System::Com::*;
 use windows::Win32::System::Threading::*;
 use windows::Win32::Foundation::*;
@@ -18,13 +20,22 @@ use windows::core::Interface;
 static AUDIO_CONTROLS: OnceLock<Mutex<HashMap<String, AudioControlData>>> = OnceLock::new();
 static COM_INITIALIZED: OnceLock<Mutex<bool>> = OnceLock::new();
 static APP_STATE: OnceLock<Mutex<Option<AppStateHandle>>> = OnceLock::new();
+static ENDPOINT: OnceLock<Mutex<Option<EndpointHandle>>> = OnceLock::new();
+static INPUT_ENDPOINTS: OnceLock<Mutex<HashMap<SessionKey, EndpointHandle>>> = OnceLock::new();
+static SESSION_WATCHERS: OnceLock<Mutex<HashMap<SessionKind, SessionWatcher>>> = OnceLock::new();
 static APP_CONTEXT_GUID: GUID = GUID::from_u128(0x12345678_1234_1234_1234_123456789abc);
 
+/// One WASAPI session, keyed in AUDIO_CONTROLS by its instance identifier.
+/// Several of these can share a SessionKey when the app's streams are grouped.
 #[derive(Clone)]
 struct AudioControlData {
+    key: SessionKey,
     volume_control: usize,
     session_control: usize,
     callback: usize,
+    /// Session ended; released by the next enumeration, since COM objects
+    /// mustn't be released from inside their own callback
+    expired: bool,
 }
 
 #[derive(Clone)]
@@ -32,6 +43,28 @@ struct AppStateHandle {
     sender: mpsc::Sender<AudioUpdate>,
 }
 
+/// Endpoint volume of one device with our change callback on it: the default
+/// render device (master), or a capture device. Re-bound when the device id
+/// behind it no longer matches.
+struct EndpointHandle {
+    device_id: String,
+    volume: IAudioEndpointVolume,
+    callback: IAudioEndpointVolumeCallback,
+}
+// SAFETY: like SyncClient, only used from the main thread; the mutex exists
+// to satisfy the static.
+unsafe impl Send for EndpointHandle {}
+
+/// Session-created notification on the session manager of one default
+/// device (render for playback, capture for recording).
+struct SessionWatcher {
+    device_id: String,
+    manager: IAudioSessionManager2,
+    notification: IAudioSessionNotification,
+}
+// SAFETY: as for EndpointHandle
+unsafe impl Send for SessionWatcher {}
+
 fn get_controls() -> &'static Mutex<HashMap<String, AudioControlData>> {
     AUDIO_CONTROLS.get_or_init(|| Mutex::new(HashMap::new()))
 }
@@ -44,11 +77,31 @@ fn get_app_state() -> &'static Mutex<Option<AppStateHandle>> {
     APP_STATE.get_or_init(|| Mutex::new(None))
 }
 
+fn get_endpoint() -> &'static Mutex<Option<EndpointHandle>> {
+    ENDPOINT.get_or_init(|| Mutex::new(None))
+}
+
+fn get_input_endpoints() -> &'static Mutex<HashMap<SessionKey, EndpointHandle>> {
+    INPUT_ENDPOINTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn get_session_watchers() -> &'static Mutex<HashMap<SessionKind, SessionWatcher>> {
+    SESSION_WATCHERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn send_update(update: AudioUpdate) {
+    if let Ok(state) = get_app_state().lock() {
+        if let Some(ref handle) = *state {
+            let _ = handle.sender.send(update);
+        }
+    }
+}
+
 // ── Audio session callbacks ───────────────────────────────────────────────────
 
 #[implement(IAudioSessionEvents)]
 struct AudioSessionCallback {
-    session_name: String,
+    instance_id: String,
 }
 
 impl IAudioSessionEvents_Impl for AudioSessionCallback_Impl {
@@ -61,19 +114,175 @@ impl IAudioSessionEvents_Impl for AudioSessionCallback_Impl {
                 return Ok(());
             }
         }
-        if let Ok(state) = get_app_state().lock() {
-            if let Some(ref handle) = *state {
-                let _ = handle.sender.send(AudioUpdate::VolumeChanged(self.session_name.clone(), newvolume));
-                let _ = handle.sender.send(AudioUpdate::MuteChanged(self.session_name.clone(), newmute.as_bool()));
+        // Look the key up per event: grouping rules can re-key a live session
+        let Some(key) = get_controls().lock().ok()
+            .and_then(|c| c.get(&self.instance_id).map(|d| d.key.clone()))
+        else {
+            return Ok(());
+        };
+        send_update(AudioUpdate::VolumeChanged(key.clone(), newvolume));
+        send_update(AudioUpdate::MuteChanged(key, newmute.as_bool()));
+        Ok(())
+    }
+
+    fn OnChannelVolumeChanged(&self, channelcount: u32, newchannelvolumearray: *const f32, _changedchannel: u32, eventcontext: *const GUID) -> windows::core::Result<()> {
+        if channelcount < 2 || newchannelvolumearray.is_null() {
+            return Ok(());
+        }
+        unsafe {
+            if !eventcontext.is_null() && *eventcontext == APP_CONTEXT_GUID {
+                return Ok(());
             }
         }
+        let Some(key) = get_controls().lock().ok()
+            .and_then(|c| c.get(&self.instance_id).map(|d| d.key.clone()))
+        else {
+            return Ok(());
+        };
+        // Channels 0 and 1 are front left/right in every WASAPI mix format
+        let volumes = unsafe { std::slice::from_raw_parts(newchannelvolumearray, 2) };
+        send_update(AudioUpdate::BalanceChanged(key, balance_from_sides(volumes[0], volumes[1])));
         Ok(())
     }
-
-    fn OnChannelVolumeChanged(&self, _channelcount: u32, _newchannelvolumearray: *const f32, _changedchannel: u32, _eventcontext: *const GUID) -> windows::core::Result<()> { Ok(()) }
     fn OnGroupingParamChanged(&self, _newgroupingparam: *const GUID, _eventcontext: *const GUID) -> windows::core::Result<()> { Ok(()) }
-    fn OnStateChanged(&self, _newstate: AudioSessionState) -> windows::core::Result<()> { Ok(()) }
-    fn OnSessionDisconnected(&self, _disconnectreason: AudioSessionDisconnectReason) -> windows::core::Result<()> { Ok(()) }
+
+    fn OnStateChanged(&self, newstate: AudioSessionState) -> windows::core::Result<()> {
+        if newstate == AudioSessionStateExpired {
+            self.expire();
+        }
+        Ok(())
+    }
+
+    fn OnSessionDisconnected(&self, _disconnectreason: AudioSessionDisconnectReason) -> windows::core::Result<()> {
+        self.expire();
+        Ok(())
+    }
+}
+
+impl AudioSessionCallback {
+    /// Mark the session ended and report it, unless grouped streams of the
+    /// same key are still alive.
+    fn expire(&self) {
+        let key = {
+            let Ok(mut controls) = get_controls().lock() else { return };
+            let Some(data) = controls.get_mut(&self.instance_id) else { return };
+            if data.expired { return; }
+            data.expired = true;
+            let key = data.key.clone();
+            if controls.values().any(|d| !d.expired && d.key == key) { return; }
+            key
+        };
+        send_update(AudioUpdate::SessionRemoved(key));
+    }
+}
+
+#[implement(IAudioSessionNotification)]
+struct SessionCreatedCallback {
+    kind: SessionKind,
+}
+
+impl IAudioSessionNotification_Impl for SessionCreatedCallback_Impl {
+    fn OnSessionCreated(&self, newsession: windows::core::Ref<IAudioSessionControl>) -> windows::core::Result<()> {
+        // Reading the process and extracting its icon is slow; the audio
+        // service's notification thread only hands the session over
+        let raw = newsession.ok()?.clone().into_raw() as usize;
+        let kind = self.kind;
+        thread::spawn(move || unsafe {
+            let com = CoInitializeEx(None, COINIT_MULTITHREADED).is_ok();
+            session_created(IAudioSessionControl::from_raw(raw as *mut _), kind);
+            if com { CoUninitialize(); }
+        });
+        Ok(())
+    }
+}
+
+/// Track a session the manager announced and report it, unless it's another
+/// stream of an already-listed (grouped) session.
+unsafe fn session_created(session_control: IAudioSessionControl, kind: SessionKind) {
+    let Some(stream) = read_session(session_control, kind) else { return };
+    let mut session = {
+        let Ok(mut controls) = get_controls().lock() else { return };
+        let (instance_id, session) = track_session(stream, &mut controls);
+        let shared = controls.iter()
+            .any(|(id, d)| *id != instance_id && !d.expired && d.key == session.id);
+        if shared { return; }
+        session
+    };
+    attach_icon(&mut session);
+    send_update(AudioUpdate::SessionAdded(session));
+}
+
+// ── Endpoint volume callback ──────────────────────────────────────────────────
+
+#[implement(IAudioEndpointVolumeCallback)]
+struct EndpointVolumeCallback {
+    /// Capture device this reports for; None for the master endpoint
+    key: Option<SessionKey>,
+}
+
+impl IAudioEndpointVolumeCallback_Impl for EndpointVolumeCallback_Impl {
+    fn OnNotify(&self, pnotify: *mut AUDIO_VOLUME_NOTIFICATION_DATA) -> windows::core::Result<()> {
+        let Some(data) = (unsafe { pnotify.as_ref() }) else { return Ok(()) };
+        if data.guidEventContext == APP_CONTEXT_GUID {
+            return Ok(());
+        }
+        let muted = data.bMuted.as_bool();
+        match &self.key {
+            None => {
+                send_update(AudioUpdate::EndpointVolumeChanged(data.fMasterVolume));
+                send_update(AudioUpdate::EndpointMuteChanged(muted));
+            }
+            Some(key) => {
+                send_update(AudioUpdate::VolumeChanged(key.clone(), data.fMasterVolume));
+                send_update(AudioUpdate::MuteChanged(key.clone(), muted));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<windows::core::Error> for AudioError {
+    fn from(e: windows::core::Error) -> Self {
+        let code = e.code();
+        if code == E_ACCESSDENIED {
+            AudioError::PermissionDenied(e.to_string())
+        } else if code == AUDCLNT_E_SERVICE_NOT_RUNNING || code == CO_E_NOTINITIALIZED {
+            AudioError::BackendUnavailable(e.to_string())
+        } else if code == E_NOTIMPL {
+            AudioError::NotSupported(e.to_string())
+        } else {
+            AudioError::Other(e.to_string())
+        }
+    }
+}
+
+unsafe fn default_device(flow: EDataFlow) -> windows::core::Result<(IMMDevice, String)> {
+    let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+    let device = enumerator.GetDefaultAudioEndpoint(flow, eConsole)?;
+    let id = device.GetId()?;
+    let id_str = id.to_string().unwrap_or_default();
+    CoTaskMemFree(Some(id.0 as *const _));
+    Ok((device, id_str))
+}
+
+/// Run `f` on the default device's endpoint volume, (re-)binding it and its
+/// change callback first if the default device moved.
+unsafe fn with_endpoint<T>(
+    f: impl FnOnce(&IAudioEndpointVolume) -> windows::core::Result<T>,
+) -> Result<T, AudioError> {
+    let (device, device_id) = default_device(eRender)?;
+    let mut slot = get_endpoint().lock().map_err(|_| AudioError::Other("endpoint state poisoned".into()))?;
+    if slot.as_ref().is_none_or(|h| h.device_id != device_id) {
+        if let Some(old) = slot.take() {
+            let _ = old.volume.UnregisterControlChangeNotify(&old.callback);
+        }
+        let volume: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None)?;
+        let callback: IAudioEndpointVolumeCallback = EndpointVolumeCallback { key: None }.into();
+        volume.RegisterControlChangeNotify(&callback)?;
+        *slot = Some(EndpointHandle { device_id, volume, callback });
+    }
+    let handle = slot.as_ref().ok_or_else(|| AudioError::Other("no default endpoint".into()))?;
+    Ok(f(&handle.volume)?)
 }
 
 // ── Device change notification ────────────────────────────────────────────────
@@ -95,10 +304,20 @@ impl IMMNotificationClient_Impl for DeviceNotificationClient_Impl {
             get_device_friendly_name(pwstrdefaultdeviceid)
                 .unwrap_or_else(|| "Unknown Device".into())
         };
-        if let Ok(state) = get_app_state().lock() {
-            if let Some(ref handle) = *state {
-                let _ = handle.sender.send(AudioUpdate::DefaultDeviceChanged(friendly_name));
-            }
+        send_update(AudioUpdate::DefaultDeviceChanged(friendly_name));
+
+        // The master target follows the new device. The callback itself is
+        // re-bound from the main thread on the next endpoint call.
+        let master = unsafe {
+            let device: windows::core::Result<IMMDevice> = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                .and_then(|e: IMMDeviceEnumerator| e.GetDevice(*pwstrdefaultdeviceid));
+            device
+                .and_then(|d| d.Activate::<IAudioEndpointVolume>(CLSCTX_ALL, None))
+                .and_then(|v| Ok((v.GetMasterVolumeLevelScalar()?, v.GetMute()?.as_bool())))
+        };
+        if let Ok((volume, muted)) = master {
+            send_update(AudioUpdate::EndpointVolumeChanged(volume));
+            send_update(AudioUpdate::EndpointMuteChanged(muted));
         }
         Ok(())
     }
@@ -149,6 +368,190 @@ unsafe fn get_process_info(pid: u32) -> Option<(String, String)> {
     None
 }
 
+// ── Session enumeration ───────────────────────────────────────────────────────
+
+/// One WASAPI session as read from its controls, before it's tracked
+struct SessionRead {
+    instance_id: String,
+    session_control: IAudioSessionControl,
+    volume_control: ISimpleAudioVolume,
+    session: AudioSession,
+}
+
+/// Read one WASAPI session: its process, key and current levels. Expired
+/// sessions and the system sounds session (pid 0) are skipped. Takes no
+/// locks; the process lookup can be slow.
+unsafe fn read_session(session_control: IAudioSessionControl, kind: SessionKind) -> Option<SessionRead> {
+    let state = session_control.GetState().ok()?;
+    if state != AudioSessionStateActive && state != AudioSessionStateInactive { return None; }
+    let session_control2 = session_control.cast::<IAudioSessionControl2>().ok()?;
+    let volume_control = session_control.cast::<ISimpleAudioVolume>().ok()?;
+    let process_id = session_control2.GetProcessId().unwrap_or(0);
+    if process_id == 0 { return None; }
+
+    let (display_name, exe_path) = if let Some((name, path)) = get_process_info(process_id) {
+        (name, Some(path))
+    } else if let Ok(name) = session_control.GetDisplayName() {
+        let name_str = name.to_string().unwrap_or_default();
+        if !name_str.is_empty() && !name_str.starts_with("@%") {
+            (name_str, None)
+        } else {
+            (format!("Process {}", process_id), None)
+        }
+    } else {
+        (format!("Process {}", process_id), None)
+    };
+
+    let instance_id = session_control2.GetSessionInstanceIdentifier()
+        .ok()
+        .map(|p| {
+            let id = p.to_string().unwrap_or_default();
+            CoTaskMemFree(Some(p.0 as *const _));
+            id
+        })
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| format!("pid:{process_id}"));
+    let key = SessionKey::for_stream(
+        exe_path.as_deref().unwrap_or(&display_name),
+        process_id,
+        instance_id.clone(),
+    ).with_kind(kind);
+
+    let volume = volume_control.GetMasterVolume().ok()?;
+    let is_muted = volume_control.GetMute()
+        .map(|m| m.as_bool())
+        .unwrap_or(false);
+    let balance = session_control.cast::<IChannelAudioVolume>().ok()
+        .map(|c| channel_balance(&c))
+        .unwrap_or(0.0);
+
+    let mut session = AudioSession::new(key, display_name, volume, is_muted, process_id);
+    session.exe_path = exe_path;
+    session.balance = balance;
+    Some(SessionRead { instance_id, session_control, volume_control, session })
+}
+
+/// Make sure `controls` tracks a session that was read, registering our
+/// volume callback the first time it's seen. The session comes back without
+/// its icon; see `attach_icon`.
+unsafe fn track_session(
+    stream: SessionRead,
+    controls: &mut HashMap<String, AudioControlData>,
+) -> (String, AudioSession) {
+    let SessionRead { instance_id, session_control, volume_control, session } = stream;
+    let key = session.id.clone();
+    if let Some(existing) = controls.get_mut(&instance_id) {
+        existing.key = key;
+    } else {
+        let callback: IAudioSessionEvents = AudioSessionCallback {
+            instance_id: instance_id.clone(),
+        }.into();
+        if session_control.RegisterAudioSessionNotification(&callback).is_ok() {
+            let control_data = AudioControlData {
+                key,
+                volume_control: volume_control.as_raw() as usize,
+                session_control: session_control.as_raw() as usize,
+                callback: callback.as_raw() as usize,
+                expired: false,
+            };
+            controls.insert(instance_id.clone(), control_data);
+            std::mem::forget(volume_control);
+            std::mem::forget(session_control);
+            std::mem::forget(callback);
+        }
+    }
+
+    (instance_id, session)
+}
+
+/// Fill in a session's icon from its exe, extracting it on first sight.
+/// Call with no locks held.
+fn attach_icon(session: &mut AudioSession) {
+    session.icon_handle = session.exe_path.as_deref()
+        .and_then(crate::utils::icon::cached_icon)
+        .map(|icon| icon.handle);
+}
+
+/// Balance of a session from its first two channel volumes.
+unsafe fn channel_balance(channels: &IChannelAudioVolume) -> f32 {
+    if channels.GetChannelCount().unwrap_or(0) < 2 { return 0.0; }
+    match (channels.GetChannelVolume(0), channels.GetChannelVolume(1)) {
+        (Ok(left), Ok(right)) => balance_from_sides(left, right),
+        _ => 0.0,
+    }
+}
+
+/// Unregister our callback from a tracked session and drop its COM objects.
+unsafe fn release_control(data: &AudioControlData) {
+    let session_control = IAudioSessionControl::from_raw(data.session_control as *mut _);
+    let callback = IAudioSessionEvents::from_raw(data.callback as *mut _);
+    let _ = session_control.UnregisterAudioSessionNotification(&callback);
+    drop(callback);
+    drop(session_control);
+    let volume_control = ISimpleAudioVolume::from_raw(data.volume_control as *mut _);
+    drop(volume_control);
+}
+
+/// The session manager of the default device for `flow`, with our
+/// session-created notification on it. Re-bound when the default device
+/// moves, like the master endpoint.
+unsafe fn watch_sessions(flow: EDataFlow, kind: SessionKind) -> Result<IAudioSessionManager2, AudioError> {
+    let (device, device_id) = default_device(flow)?;
+    let mut watchers = get_session_watchers().lock()
+        .map_err(|_| AudioError::Other("session watcher state poisoned".into()))?;
+    if let Some(watcher) = watchers.get(&kind).filter(|w| w.device_id == device_id) {
+        return Ok(watcher.manager.clone());
+    }
+    if let Some(old) = watchers.remove(&kind) {
+        let _ = old.manager.UnregisterSessionNotification(&old.notification);
+    }
+    let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+    let notification: IAudioSessionNotification = SessionCreatedCallback { kind }.into();
+    manager.RegisterSessionNotification(&notification)?;
+    watchers.insert(kind, SessionWatcher { device_id, manager: manager.clone(), notification });
+    Ok(manager)
+}
+
+/// Enumerate the WASAPI sessions on the default device for `flow`, keeping
+/// AUDIO_CONTROLS (and its callbacks) in step with what's live. Windows only
+/// starts delivering session-created events once the manager they were
+/// registered on has been enumerated, so this also arms those.
+unsafe fn enumerate_sessions(flow: EDataFlow, kind: SessionKind) -> Result<Vec<AudioSession>, AudioError> {
+    let session_manager = watch_sessions(flow, kind)?;
+    let session_enumerator = session_manager.GetSessionEnumerator()?;
+    let count = session_enumerator.GetCount()?;
+
+    let streams: Vec<SessionRead> = (0..count)
+        .filter_map(|i| session_enumerator.GetSession(i).ok())
+        .filter_map(|session_control| read_session(session_control, kind))
+        .collect();
+
+    let mut sessions: Vec<AudioSession> = Vec::new();
+    let mut live = HashSet::new();
+    let mut controls = get_controls().lock()
+        .map_err(|_| AudioError::Other("session state poisoned".into()))?;
+
+    for stream in streams {
+        let (instance_id, session) = track_session(stream, &mut controls);
+        live.insert(instance_id);
+        // Grouped streams share one session entry
+        if !sessions.iter().any(|s| s.id == session.id) {
+            sessions.push(session);
+        }
+    }
+
+    // Render and capture sessions share the map; only prune our own kind
+    controls.retain(|instance_id, data| {
+        if data.key.kind != kind || live.contains(instance_id) { return true; }
+        release_control(data);
+        false
+    });
+    drop(controls);
+
+    sessions.iter_mut().for_each(attach_icon);
+    Ok(sessions)
+}
+
 // ── Backend ───────────────────────────────────────────────────────────────────
 
 struct SyncClient(IMMNotificationClient);
@@ -168,7 +571,7 @@ impl WindowsAudioBackend {
 }
 
 impl AudioBackend for WindowsAudioBackend {
-    fn initialize(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn initialize(&mut self) -> Result<(), AudioError> {
         unsafe {
             if let Ok(mut initialized) = is_com_initialized().lock() {
                 if !*initialized {
@@ -180,155 +583,173 @@ impl AudioBackend for WindowsAudioBackend {
             let client: IMMNotificationClient = DeviceNotificationClient.into();
             enumerator.RegisterEndpointNotificationCallback(&client)?;
             self._notification_client = Some(SyncClient(client));
+            let _ = with_endpoint(|_| Ok(()));
         }
         Ok(())
     }
 
-    fn get_sessions(&self) -> Result<Vec<AudioSession>, Box<dyn std::error::Error>> {
+    fn get_sessions(&self) -> Result<Vec<AudioSession>, AudioError> {
+        unsafe { enumerate_sessions(eRender, SessionKind::Playback) }
+    }
+
+    fn get_recording_streams(&self) -> Result<Vec<AudioSession>, AudioError> {
+        unsafe { enumerate_sessions(eCapture, SessionKind::Recording) }
+    }
+
+    fn get_input_devices(&self) -> Result<Vec<AudioSession>, AudioError> {
         unsafe {
             let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
-            let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
-            let session_manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
-            let session_enumerator = session_manager.GetSessionEnumerator()?;
-            let count = session_enumerator.GetCount()?;
+            let collection = enumerator.EnumAudioEndpoints(eCapture, DEVICE_STATE_ACTIVE)?;
+            let count = collection.GetCount()?;
 
+            let mut endpoints = get_input_endpoints().lock()
+                .map_err(|_| AudioError::Other("input endpoint state poisoned".into()))?;
+            let mut fresh: HashMap<SessionKey, EndpointHandle> = HashMap::new();
             let mut sessions = Vec::new();
-            let mut new_controls = HashMap::new();
-
             for i in 0..count {
-                if let Ok(session_control) = session_enumerator.GetSession(i) {
-                    if let Ok(state) = session_control.GetState() {
-                        if state == AudioSessionStateActive || state == AudioSessionStateInactive {
-                            if let Ok(session_control2) = session_control.cast::<IAudioSessionControl2>() {
-                                if let Ok(volume_control) = session_control.cast::<ISimpleAudioVolume>() {
-                                    let process_id = session_control2.GetProcessId().unwrap_or(0);
-                                    if process_id == 0 { continue; }
-
-                                    let (display_name, exe_path) = if let Some((name, path)) = get_process_info(process_id) {
-                                        (name, Some(path))
-                                    } else if let Ok(name) = session_control.GetDisplayName() {
-                                        let name_str = name.to_string().unwrap_or_default();
-                                        if !name_str.is_empty() && !name_str.starts_with("@%") {
-                                            (name_str, None)
-                                        } else {
-                                            (format!("Process {}", process_id), None)
-                                        }
-                                    } else {
-                                        (format!("Process {}", process_id), None)
-                                    };
-
-                                    let icon_handle = if let Some(ref exe) = exe_path {
-                                        crate::utils::icon::extract_icon_to_handle(exe).map(Arc::new)
-                                    } else {
-                                        None
-                                    };
-
-                                    if let Ok(volume) = volume_control.GetMasterVolume() {
-                                        let is_muted = volume_control.GetMute()
-                                            .map(|m| m.as_bool())
-                                            .unwrap_or(false);
-
-                                        if let Ok(controls_map) = get_controls().lock() {
-                                            if let Some(existing) = controls_map.get(&display_name) {
-                                                new_controls.insert(display_name.clone(), existing.clone());
-                                                drop(volume_control);
-                                                drop(session_control);
-                                            } else {
-                                                let callback: IAudioSessionEvents = AudioSessionCallback {
-                                                    session_name: display_name.clone(),
-                                                }.into();
-                                                if session_control.RegisterAudioSessionNotification(&callback).is_ok() {
-                                                    let control_data = AudioControlData {
-                                                        volume_control: volume_control.as_raw() as usize,
-                                                        session_control: session_control.as_raw() as usize,
-                                                        callback: callback.as_raw() as usize,
-                                                    };
-                                                    new_controls.insert(display_name.clone(), control_data);
-                                                    std::mem::forget(volume_control);
-                                                    std::mem::forget(session_control);
-                                                    std::mem::forget(callback);
-                                                }
-                                            }
-                                        }
-
-                                        let mut session = AudioSession::new(
-                                            display_name.clone(),
-                                            display_name,
-                                            volume,
-                                            is_muted,
-                                            process_id,
-                                        );
-                                        session.icon_handle = icon_handle;
-                                        session.exe_path = exe_path.clone();
-                                        sessions.push(session);
-                                    }
-                                }
-                            }
+                let Ok(device) = collection.Item(i) else { continue };
+                let Ok(id) = device.GetId() else { continue };
+                let device_id = id.to_string().unwrap_or_default();
+                let name = get_device_friendly_name(&PCWSTR(id.0))
+                    .unwrap_or_else(|| "Unknown Input".into());
+                CoTaskMemFree(Some(id.0 as *const _));
+
+                let key = SessionKey::input_device(name.clone());
+                if fresh.contains_key(&key) { continue; }
+                let handle = match endpoints.remove(&key) {
+                    Some(h) if h.device_id == device_id => h,
+                    stale => {
+                        if let Some(old) = stale {
+                            let _ = old.volume.UnregisterControlChangeNotify(&old.callback);
                         }
+                        let Ok(volume) = device.Activate::<IAudioEndpointVolume>(CLSCTX_ALL, None) else { continue };
+                        let callback: IAudioEndpointVolumeCallback = EndpointVolumeCallback { key: Some(key.clone()) }.into();
+                        if volume.RegisterControlChangeNotify(&callback).is_err() { continue; }
+                        EndpointHandle { device_id, volume, callback }
                     }
-                }
+                };
+                let level = handle.volume.GetMasterVolumeLevelScalar().unwrap_or(0.0);
+                let muted = handle.volume.GetMute().map(|m| m.as_bool()).unwrap_or(false);
+                sessions.push(AudioSession::new(key.clone(), name, level, muted, 0));
+                fresh.insert(key, handle);
             }
-
-            if let Ok(mut controls_map) = get_controls().lock() {
-                for (name, data) in controls_map.drain() {
-                    if !new_controls.contains_key(&name) {
-                        let session_control = IAudioSessionControl::from_raw(data.session_control as *mut _);
-                        let callback = IAudioSessionEvents::from_raw(data.callback as *mut _);
-                        let _ = session_control.UnregisterAudioSessionNotification(&callback);
-                        drop(callback);
-                        drop(session_control);
-                        let volume_control = ISimpleAudioVolume::from_raw(data.volume_control as *mut _);
-                        drop(volume_control);
-                    }
-                }
-                *controls_map = new_controls;
+            for (_, old) in endpoints.drain() {
+                let _ = old.volume.UnregisterControlChangeNotify(&old.callback);
             }
-
+            *endpoints = fresh;
             Ok(sessions)
         }
     }
 
-    fn set_volume(&mut self, session_id: &str, volume: f32) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_volume(&mut self, session: &SessionKey, volume: f32) -> Result<(), AudioError> {
+        if session.kind == SessionKind::Input {
+            if let Ok(endpoints) = get_input_endpoints().lock() {
+                let handle = endpoints.get(session).ok_or_else(|| AudioError::SessionGone(session.clone()))?;
+                unsafe { handle.volume.SetMasterVolumeLevelScalar(volume, &APP_CONTEXT_GUID as *const _)?; }
+            }
+            return Ok(());
+        }
         if let Ok(controls) = get_controls().lock() {
-            if let Some(data) = controls.get(session_id) {
+            let mut found = false;
+            for data in controls.values().filter(|d| !d.expired && &d.key == session) {
+                found = true;
                 unsafe {
                     let control = ISimpleAudioVolume::from_raw(data.volume_control as *mut _);
                     control.SetMasterVolume(volume, &APP_CONTEXT_GUID as *const _)?;
                     std::mem::forget(control);
                 }
             }
+            if !found { return Err(AudioError::SessionGone(session.clone())); }
         }
         Ok(())
     }
 
-    fn set_mute(&mut self, session_id: &str, muted: bool) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_mute(&mut self, session: &SessionKey, muted: bool) -> Result<(), AudioError> {
+        if session.kind == SessionKind::Input {
+            if let Ok(endpoints) = get_input_endpoints().lock() {
+                let handle = endpoints.get(session).ok_or_else(|| AudioError::SessionGone(session.clone()))?;
+                unsafe { handle.volume.SetMute(muted, &APP_CONTEXT_GUID as *const _)?; }
+            }
+            return Ok(());
+        }
         if let Ok(controls) = get_controls().lock() {
-            if let Some(data) = controls.get(session_id) {
+            let mut found = false;
+            for data in controls.values().filter(|d| !d.expired && &d.key == session) {
+                found = true;
                 unsafe {
                     let control = ISimpleAudioVolume::from_raw(data.volume_control as *mut _);
                     control.SetMute(muted, &APP_CONTEXT_GUID as *const _)?;
                     std::mem::forget(control);
                 }
             }
+            if !found { return Err(AudioError::SessionGone(session.clone())); }
         }
         Ok(())
     }
 
-    fn start_listening(&mut self, sender: mpsc::Sender<AudioUpdate>) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_balance(&mut self, session: &SessionKey, balance: f32) -> Result<(), AudioError> {
+        if session.kind != SessionKind::Playback {
+            return Err(AudioError::NotSupported(format!("Balance on {} streams", session.kind.as_str())));
+        }
+        let (left, right) = balance_sides(balance.clamp(-1.0, 1.0));
+        if let Ok(controls) = get_controls().lock() {
+            let mut found = false;
+            for data in controls.values().filter(|d| !d.expired && &d.key == session) {
+                found = true;
+                unsafe {
+                    let control = IAudioSessionControl::from_raw(data.session_control as *mut _);
+                    let channels = control.cast::<IChannelAudioVolume>();
+                    std::mem::forget(control);
+                    let channels = channels?;
+                    // Mono sessions have nothing to balance
+                    if channels.GetChannelCount()? < 2 { continue; }
+                    channels.SetChannelVolume(0, left, &APP_CONTEXT_GUID as *const _)?;
+                    channels.SetChannelVolume(1, right, &APP_CONTEXT_GUID as *const _)?;
+                }
+            }
+            if !found { return Err(AudioError::SessionGone(session.clone())); }
+        }
+        Ok(())
+    }
+
+    fn move_session_to_device(&mut self, _session: &SessionKey, _device: &str) -> Result<(), AudioError> {
+        // Per-app routing is only reachable through undocumented interfaces
+        Err(AudioError::NotSupported("Per-app output routing on Windows".into()))
+    }
+
+    fn get_endpoint_volume(&self) -> Result<(f32, bool), AudioError> {
+        unsafe {
+            with_endpoint(|v| Ok((v.GetMasterVolumeLevelScalar()?, v.GetMute()?.as_bool())))
+        }
+    }
+
+    fn set_endpoint_volume(&mut self, volume: f32) -> Result<(), AudioError> {
+        unsafe {
+            with_endpoint(|v| v.SetMasterVolumeLevelScalar(volume.clamp(0.0, 1.0), &APP_CONTEXT_GUID as *const _))
+        }
+    }
+
+    fn set_endpoint_mute(&mut self, muted: bool) -> Result<(), AudioError> {
+        unsafe {
+            with_endpoint(|v| v.SetMute(muted, &APP_CONTEXT_GUID as *const _))
+        }
+    }
+
+    fn start_listening(&mut self, sender: mpsc::Sender<AudioUpdate>) -> Result<(), AudioError> {
         if let Ok(mut state) = get_app_state().lock() {
             *state = Some(AppStateHandle { sender });
         }
         Ok(())
     }
 
-    fn stop_listening(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    fn stop_listening(&mut self) -> Result<(), AudioError> {
         if let Ok(mut state) = get_app_state().lock() {
             *state = None;
         }
         Ok(())
     }
 
-    fn get_output_devices(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    fn get_output_devices(&self) -> Result<Vec<String>, AudioError> {
         unsafe {
             let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
             let collection = enumerator.EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)?;
@@ -351,4 +772,9 @@ impl AudioBackend for WindowsAudioBackend {
             get_device_friendly_name(&PCWSTR(device.GetId().ok()?.0))
         }
     }
+
+    fn set_default_output_device(&mut self, _name: &str) -> Result<(), AudioError> {
+        // Windows has no public API for this; only the undocumented IPolicyConfig
+        Err(AudioError::NotSupported("Changing the default output device on Windows".into()))
+    }
 }
\ No newline at end of file
@@ -1,12 +1,56 @@
 use std::sync::Arc;
 use std::time::Instant;
 
+use super::session_key::SessionKey;
+
+/// Quietest level a meter shows; meters are linear in dB above it
+const METER_FLOOR_DB: f32 = -60.0;
+
+/// Signal level over one meter period, linear full scale 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AudioLevel {
+    pub peak: f32,
+    pub rms: f32,
+}
+
+impl AudioLevel {
+    /// The louder of two readings, for sessions made of several streams.
+    pub fn max(self, other: AudioLevel) -> AudioLevel {
+        AudioLevel { peak: self.peak.max(other.peak), rms: self.rms.max(other.rms) }
+    }
+
+    /// Where an amplitude sits on a meter, 0.0 (floor or silence) to 1.0.
+    pub fn meter_position(amplitude: f32) -> f32 {
+        if amplitude <= 0.0 { return 0.0; }
+        ((20.0 * amplitude.log10() - METER_FLOOR_DB) / -METER_FLOOR_DB).clamp(0.0, 1.0)
+    }
+}
+
+/// Left/right balance from the gains of the two sides, -1.0 (left only) to
+/// 1.0 (right only). Like pavucontrol, the quieter side is measured against
+/// the louder one.
+pub fn balance_from_sides(left: f32, right: f32) -> f32 {
+    if left == right { return 0.0; }
+    if left > right { right / left - 1.0 } else { 1.0 - left / right }
+}
+
+/// Gains (left, right) for a balance; the louder side stays at 1.0.
+pub fn balance_sides(balance: f32) -> (f32, f32) {
+    let b = balance.clamp(-1.0, 1.0);
+    if b < 0.0 { (1.0, 1.0 + b) } else { (1.0 - b, 1.0) }
+}
+
 #[derive(Debug, Clone)]
 pub struct AudioSession {
-    pub id: String,
+    pub id: SessionKey,
     pub display_name: String,
     pub volume: f32,
     pub is_muted: bool,
+    /// -1.0 (left) to 1.0 (right); `volume` is the louder side
+    pub balance: f32,
+    /// Latest meter reading; stays at zero on backends without metering
+    pub level: AudioLevel,
+    #[allow(dead_code)]  // not consumed by the UI yet
     pub process_id: u32,
     pub icon_handle: Option<Arc<iced::widget::image::Handle>>,
     pub exe_path: Option<String>,
@@ -16,7 +60,7 @@ pub struct AudioSession {
 
 impl AudioSession {
     pub fn new(
-        id: String,
+        id: SessionKey,
         display_name: String,
         volume: f32,
         is_muted: bool,
@@ -27,6 +71,8 @@ impl AudioSession {
             display_name,
             volume,
             is_muted,
+            balance: 0.0,
+            level: AudioLevel::default(),
             process_id,
             icon_handle: None,
             exe_path: None,
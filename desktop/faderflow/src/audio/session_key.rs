@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+
+use super::session::AudioSession;
+
+/// Stable identity of an audio session. Backends fill in everything they know
+/// about a stream and collapse it according to the app's grouping rule, so
+/// the key is also what a channel gets assigned to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SessionKey {
+    /// Full executable path, or the app name when the binary isn't visible
+    pub exe: String,
+    /// Process id, kept only when instances of the app are split
+    pub instance: Option<u32>,
+    /// Backend stream/session id, kept only when every stream is separate
+    pub stream: Option<String>,
+    /// Playback stream, recording stream or capture device
+    pub kind: SessionKind,
+}
+
+/// Which side of the mixer a session sits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum SessionKind {
+    /// An app's output stream (sink-input, render session)
+    #[default]
+    Playback,
+    /// An app's recording stream (source-output, capture session)
+    Recording,
+    /// A capture device such as a microphone; `exe` holds its name
+    Input,
+}
+
+impl SessionKind {
+    pub const ALL: [SessionKind; 3] = [SessionKind::Playback, SessionKind::Recording, SessionKind::Input];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SessionKind::Playback => "playback",
+            SessionKind::Recording => "recording",
+            SessionKind::Input => "input",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == s)
+    }
+}
+
+/// Reserved exe for the synthetic master target; can't collide with a path.
+const MASTER_EXE: &str = "::master";
+pub const MASTER_LABEL: &str = "System master";
+
+impl SessionKey {
+    pub fn new(exe: impl Into<String>) -> Self {
+        Self { exe: exe.into(), instance: None, stream: None, kind: SessionKind::Playback }
+    }
+
+    pub fn with_kind(mut self, kind: SessionKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// A capture device, keyed by its display name so configs stay readable.
+    pub fn input_device(name: impl Into<String>) -> Self {
+        Self::new(name).with_kind(SessionKind::Input)
+    }
+
+    /// The default output device's master volume, assignable like a session.
+    pub fn master() -> Self {
+        Self::new(MASTER_EXE)
+    }
+
+    pub fn is_master(&self) -> bool {
+        self.exe == MASTER_EXE
+    }
+
+    /// Key for one backend stream, collapsed per the grouping rule for `exe`.
+    pub fn for_stream(exe: &str, pid: u32, stream: impl Into<String>) -> Self {
+        let mut key = Self::new(exe);
+        match grouping_for(exe) {
+            Grouping::App => {}
+            Grouping::Process => key.instance = (pid != 0).then_some(pid),
+            Grouping::Stream => {
+                key.instance = (pid != 0).then_some(pid);
+                key.stream = Some(stream.into());
+            }
+        }
+        key
+    }
+
+    /// File stem of the exe, e.g. "chrome" for C:\…\chrome.exe
+    pub fn app_name(&self) -> &str {
+        if self.is_master() { return MASTER_LABEL; }
+        if self.kind == SessionKind::Input { return &self.exe; }
+        let file = self.exe.rsplit(['/', '\\']).next().unwrap_or(&self.exe);
+        file.strip_suffix(".exe").unwrap_or(file)
+    }
+
+    /// Old configs keyed channels by display name only; such keys carry no
+    /// path and no instance, and get re-resolved against live sessions.
+    pub fn is_legacy(&self) -> bool {
+        !self.is_master() && self.kind == SessionKind::Playback
+            && self.instance.is_none() && self.stream.is_none() && !self.exe.contains(['/', '\\'])
+    }
+
+    /// Whether `session` is a live match for this (possibly stale) key:
+    /// same executable, or for legacy keys the same app name.
+    pub fn matches_app(&self, session: &AudioSession) -> bool {
+        if session.id.kind != self.kind { return false; }
+        if session.id.exe == self.exe { return true; }
+        self.is_legacy()
+            && (session.display_name.eq_ignore_ascii_case(&self.exe)
+                || session.id.app_name().eq_ignore_ascii_case(&self.exe))
+    }
+
+    /// Live session a stale key should now point at: the lowest matching
+    /// key that `taken` doesn't already claim.
+    pub fn rebind_target<'a>(
+        &self,
+        sessions: impl IntoIterator<Item = &'a AudioSession>,
+        taken: impl Fn(&SessionKey) -> bool,
+    ) -> Option<SessionKey> {
+        sessions.into_iter()
+            .filter(|s| self.matches_app(s))
+            .map(|s| &s.id)
+            .filter(|id| !taken(id))
+            .min()
+            .cloned()
+    }
+}
+
+impl fmt::Display for SessionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kind != SessionKind::Playback { write!(f, "{}:", self.kind.as_str())?; }
+        write!(f, "{}", self.exe)?;
+        if let Some(pid) = self.instance { write!(f, "#{pid}")?; }
+        if let Some(stream) = &self.stream { write!(f, "@{stream}")?; }
+        Ok(())
+    }
+}
+
+/// Display names for a set of sessions. Instances of the same app are told
+/// apart by a counter in key order: "Chrome", "Chrome #2", "Chrome #3".
+/// Playback and recording streams of one app are counted separately.
+pub fn session_labels<'a>(sessions: impl IntoIterator<Item = &'a AudioSession>) -> HashMap<SessionKey, String> {
+    let mut by_name: HashMap<(SessionKind, &str), Vec<&SessionKey>> = HashMap::new();
+    for s in sessions {
+        by_name.entry((s.id.kind, s.display_name.as_str())).or_default().push(&s.id);
+    }
+    let mut labels = HashMap::new();
+    for ((_, name), mut keys) in by_name {
+        keys.sort();
+        for (i, key) in keys.into_iter().enumerate() {
+            let label = if i == 0 { name.to_string() } else { format!("{name} #{}", i + 1) };
+            labels.insert(key.clone(), label);
+        }
+    }
+    labels
+}
+
+// ── Grouping rules ────────────────────────────────────────────────────────────
+
+/// How the streams of one executable map onto sessions (and so channels).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Grouping {
+    /// Every process and stream of the app shares one session
+    #[default]
+    App,
+    /// One session per process ("Chrome", "Chrome #2")
+    Process,
+    /// One session per backend stream
+    Stream,
+}
+
+impl Grouping {
+    pub const ALL: [Grouping; 3] = [Grouping::App, Grouping::Process, Grouping::Stream];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Grouping::App => "app",
+            Grouping::Process => "process",
+            Grouping::Stream => "stream",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|g| g.as_str() == s)
+    }
+}
+
+impl fmt::Display for Grouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Grouping::App => "One per app",
+            Grouping::Process => "One per process",
+            Grouping::Stream => "One per stream",
+        })
+    }
+}
+
+static GROUPING_RULES: OnceLock<RwLock<HashMap<String, Grouping>>> = OnceLock::new();
+
+fn rules() -> &'static RwLock<HashMap<String, Grouping>> {
+    GROUPING_RULES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Replace the per-exe grouping rules. Backends pick them up the next time
+/// they build keys, i.e. on the next session refresh.
+pub fn set_grouping_rules(new_rules: HashMap<String, Grouping>) {
+    if let Ok(mut r) = rules().write() {
+        *r = new_rules;
+    }
+}
+
+pub fn grouping_rules() -> HashMap<String, Grouping> {
+    rules().read().map(|r| r.clone()).unwrap_or_default()
+}
+
+pub fn grouping_for(exe: &str) -> Grouping {
+    rules().read().ok()
+        .and_then(|r| r.get(exe).copied())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(exe: &str, pid: u32) -> SessionKey {
+        SessionKey { instance: Some(pid), ..SessionKey::new(exe) }
+    }
+
+    fn session(exe: &str, name: &str, pid: u32) -> AudioSession {
+        AudioSession::new(instance(exe, pid), name.into(), 1.0, false, pid)
+    }
+
+    #[test]
+    fn grouping_rules_shape_stream_keys() {
+        set_grouping_rules(HashMap::from([
+            ("/opt/test/split".to_string(), Grouping::Process),
+            ("/opt/test/streams".to_string(), Grouping::Stream),
+        ]));
+
+        let app = SessionKey::for_stream("/opt/test/whole", 7, "s1");
+        assert_eq!((app.instance, app.stream), (None, None));
+        let process = SessionKey::for_stream("/opt/test/split", 7, "s1");
+        assert_eq!((process.instance, process.stream), (Some(7), None));
+        let stream = SessionKey::for_stream("/opt/test/streams", 7, "s1");
+        assert_eq!((stream.instance, stream.stream.as_deref()), (Some(7), Some("s1")));
+        // No pid, nothing to split by
+        assert_eq!(SessionKey::for_stream("/opt/test/split", 0, "s1").instance, None);
+    }
+
+    #[test]
+    fn only_bare_names_are_legacy() {
+        assert!(SessionKey::new("Spotify").is_legacy());
+        assert!(!SessionKey::new("/usr/bin/spotify").is_legacy());
+        assert!(!SessionKey::new(r"C:\Apps\spotify.exe").is_legacy());
+        assert!(!instance("Spotify", 3).is_legacy());
+        assert!(!SessionKey::input_device("Mic").is_legacy());
+        assert!(!SessionKey::master().is_legacy());
+    }
+
+    #[test]
+    fn stale_keys_match_their_app() {
+        let live = session("/usr/bin/spotify", "Spotify", 40);
+        assert!(SessionKey::new("/usr/bin/spotify").matches_app(&live));
+        assert!(SessionKey::new("Spotify").matches_app(&live));
+        assert!(SessionKey::new("spotify").matches_app(&live));
+        assert!(!SessionKey::new("Discord").matches_app(&live));
+        assert!(!SessionKey::new("/usr/bin/spotify").with_kind(SessionKind::Recording).matches_app(&live));
+        // A path key only matches its own path, never a name
+        assert!(!SessionKey::new("/opt/Spotify").matches_app(&live));
+    }
+
+    #[test]
+    fn legacy_keys_rebind_to_live_sessions() {
+        let sessions = [
+            session("/usr/bin/firefox", "Firefox", 20),
+            session("/usr/bin/spotify", "Spotify", 41),
+            session("/usr/bin/spotify", "Spotify", 40),
+        ];
+        let none_taken = |_: &SessionKey| false;
+
+        // The old name-keyed assignment picks up the live session, lowest key first
+        let rebound = SessionKey::new("Spotify").rebind_target(&sessions, none_taken);
+        assert_eq!(rebound, Some(instance("/usr/bin/spotify", 40)));
+
+        // Already on another channel: the next one
+        let taken = |k: &SessionKey| k.instance == Some(40);
+        let rebound = SessionKey::new("Spotify").rebind_target(&sessions, taken);
+        assert_eq!(rebound.and_then(|k| k.instance), Some(41));
+
+        // Nothing running that matches: the key stays as it is
+        assert_eq!(SessionKey::new("Discord").rebind_target(&sessions, none_taken), None);
+        assert_eq!(SessionKey::new("/usr/bin/mpv").rebind_target(&sessions, none_taken), None);
+    }
+}
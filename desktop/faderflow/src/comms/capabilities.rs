@@ -0,0 +1,85 @@
+// capabilities.rs - What a device says it can do, from CMD_CAPABILITIES.
+// Firmware older than 1.2 doesn't answer; `Capabilities::LEGACY` is what
+// that firmware is assumed to support.
+
+use crate::comms::protocol::*;
+
+/// Largest channel count the host keeps state for
+pub const MAX_CHANNELS: usize = 5;
+
+/// How the device wants icon pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconFormat {
+    /// No icon support; icons aren't sent
+    None,
+    /// 64×64 big-endian RGB565 via CMD_DISPLAY_UPDATE_ICON
+    Rgb565,
+}
+
+impl IconFormat {
+    #[cfg(test)]
+    pub fn wire(self) -> u8 {
+        match self {
+            IconFormat::None => ICON_FORMAT_NONE,
+            IconFormat::Rgb565 => ICON_FORMAT_RGB565,
+        }
+    }
+
+    /// Unknown formats are treated as none; the host can't produce them
+    pub fn from_wire(b: u8) -> Self {
+        match b {
+            ICON_FORMAT_RGB565 => IconFormat::Rgb565,
+            _ => IconFormat::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub channels: u8,
+    /// Per-channel screen size in pixels, 0×0 without displays
+    pub display: (u16, u16),
+    pub icon_format: IconFormat,
+    pub framing_version: u8,
+    /// Bit n set = host → device command n is understood
+    pub commands: u32,
+}
+
+impl Capabilities {
+    /// Firmware 1.0/1.1: five 240×240 screens, handshake, names, volume,
+    /// icons and calibration.
+    pub const LEGACY: Capabilities = Capabilities {
+        channels: 5,
+        display: (240, 240),
+        icon_format: IconFormat::Rgb565,
+        framing_version: 1,
+        commands: 1 << CMD_HANDSHAKE_REQUEST
+            | 1 << CMD_HANDSHAKE_ACK
+            | 1 << CMD_ECHO_UUID
+            | 1 << CMD_DISPLAY_UPDATE_APP_NAME
+            | 1 << CMD_DISPLAY_UPDATE_APP_VOLUME
+            | 1 << CMD_DISPLAY_UPDATE_ICON
+            | 1 << CMD_CALIBRATION_START
+            | 1 << CMD_CALIBRATION_CANCEL,
+    };
+
+    pub fn supports(&self, cmd: u8) -> bool {
+        cmd < 32 && self.commands & (1 << cmd) != 0
+    }
+
+    pub fn has_display(&self) -> bool {
+        self.display.0 > 0 && self.display.1 > 0
+    }
+
+    /// Whether names and icons are worth sending at all
+    pub fn shows_icons(&self) -> bool {
+        self.has_display()
+            && self.icon_format != IconFormat::None
+            && self.supports(CMD_DISPLAY_UPDATE_ICON)
+    }
+
+    /// Channels the host drives, capped at what it keeps state for
+    pub fn channel_count(&self) -> usize {
+        (self.channels as usize).min(MAX_CHANNELS)
+    }
+}
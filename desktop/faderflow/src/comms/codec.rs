@@ -0,0 +1,521 @@
+// codec.rs - Packet encoding and decoding, byte for byte what protocol.h's
+// packed structs put on the wire. Both directions encode and decode so each
+// layout can be checked by a round trip; the host only uses half of it.
+
+use crate::comms::capabilities::{Capabilities, IconFormat};
+use crate::comms::device_info::EncoderMode;
+use crate::comms::protocol::*;
+
+/// Icons are square, this many pixels a side
+pub const ICON_SIZE: usize = 64;
+/// Bytes of icon pixel data following a CMD_DISPLAY_UPDATE_ICON header
+pub const ICON_BYTES: usize = ICON_SIZE * ICON_SIZE * 2;
+/// Bytes of pixel data in a CMD_DISPLAY_UPDATE_ICON_ROW
+pub const ICON_ROW_BYTES: usize = ICON_SIZE * 2;
+/// Most run bytes a CMD_DISPLAY_UPDATE_ICON_RLE can carry: an icon with no
+/// repeats at all, plus a control byte per 128 pixels
+pub const ICON_RLE_MAX: usize = ICON_BYTES + ICON_BYTES / 128;
+/// CMD_DISPLAY_UPDATE_ICON_RLE header: cmd, channel, run byte count
+#[cfg(test)]
+const ICON_RLE_HEADER: usize = 4;
+/// Name field of CMD_DISPLAY_UPDATE_APP_NAME; the last byte is always NUL
+const NAME_FIELD: usize = 64;
+/// Magic field of the handshake response, "FADERFLOW" and a NUL
+const MAGIC_FIELD: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The packet isn't all there yet
+    Incomplete,
+    /// The lead byte isn't a command of this direction
+    Unknown(u8),
+    /// A known command whose contents don't make sense
+    Malformed(u8),
+}
+
+/// Knob gestures reported with CMD_BUTTON_EVENT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonEvent {
+    Press,
+    LongPress,
+    DoublePress,
+}
+
+impl ButtonEvent {
+    #[cfg(test)]
+    pub fn wire(self) -> u8 {
+        match self {
+            ButtonEvent::Press => BUTTON_EVENT_PRESS,
+            ButtonEvent::LongPress => BUTTON_EVENT_LONG_PRESS,
+            ButtonEvent::DoublePress => BUTTON_EVENT_DOUBLE_PRESS,
+        }
+    }
+
+    pub fn from_wire(b: u8) -> Option<Self> {
+        match b {
+            BUTTON_EVENT_PRESS => Some(ButtonEvent::Press),
+            BUTTON_EVENT_LONG_PRESS => Some(ButtonEvent::LongPress),
+            BUTTON_EVENT_DOUBLE_PRESS => Some(ButtonEvent::DoublePress),
+            _ => None,
+        }
+    }
+}
+
+// ── Host → device ────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostPacket {
+    HandshakeRequest,
+    HandshakeAck,
+    /// Names longer than 63 bytes are cut at a character boundary
+    AppName { channel: u8, name: String },
+    /// Display percentage, 0-100
+    Volume { channel: u8, volume: u8 },
+    /// `ICON_BYTES` of big-endian RGB565
+    Icon { channel: u8, rgb565: Vec<u8> },
+    /// `ICON_ROW_BYTES` of the same, row 0 at the top
+    IconRow { channel: u8, row: u8, rgb565: Vec<u8> },
+    /// Runs from `rgb565_to_rle_icon`, at most `ICON_RLE_MAX` bytes
+    IconRle { channel: u8, runs: Vec<u8> },
+    Meters([u8; METER_CHANNELS]),
+    /// -100 (left) to 100 (right)
+    Balance { channel: u8, balance: i8 },
+    Mute { channel: u8, muted: bool },
+    EncoderMode { channel: u8, mode: EncoderMode },
+    CalibrationStart,
+    CalibrationCancel,
+    /// Ask for v2 framing; always sent raw
+    FramingRequest,
+    CapabilitiesRequest,
+}
+
+impl HostPacket {
+    /// Full packet length for a command byte, including the command itself.
+    /// None for CMD_DISPLAY_UPDATE_ICON_RLE, whose header carries its length.
+    #[cfg(test)]
+    pub fn packet_len(cmd: u8) -> Option<usize> {
+        Some(match cmd {
+            CMD_HANDSHAKE_REQUEST | CMD_HANDSHAKE_ACK
+            | CMD_CALIBRATION_START | CMD_CALIBRATION_CANCEL | CMD_FRAMING_REQUEST
+            | CMD_CAPABILITIES_REQUEST => 1,
+            CMD_DISPLAY_UPDATE_APP_NAME => 2 + NAME_FIELD,
+            CMD_DISPLAY_UPDATE_ICON => 2 + ICON_BYTES,
+            CMD_DISPLAY_UPDATE_ICON_ROW => 3 + ICON_ROW_BYTES,
+            CMD_DISPLAY_UPDATE_METER => 1 + METER_CHANNELS,
+            CMD_DISPLAY_UPDATE_APP_VOLUME | CMD_DISPLAY_UPDATE_BALANCE
+            | CMD_DISPLAY_UPDATE_MUTE | CMD_ENCODER_MODE => 3,
+            _ => return None,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            HostPacket::HandshakeRequest => vec![CMD_HANDSHAKE_REQUEST],
+            HostPacket::HandshakeAck => vec![CMD_HANDSHAKE_ACK],
+            HostPacket::AppName { channel, name } => {
+                let mut out = vec![CMD_DISPLAY_UPDATE_APP_NAME, *channel];
+                let mut len = name.len().min(NAME_FIELD - 1);
+                while !name.is_char_boundary(len) { len -= 1; }
+                out.extend_from_slice(&name.as_bytes()[..len]);
+                out.resize(2 + NAME_FIELD, 0);
+                out
+            }
+            HostPacket::Volume { channel, volume } => {
+                vec![CMD_DISPLAY_UPDATE_APP_VOLUME, *channel, (*volume).min(100)]
+            }
+            HostPacket::Icon { channel, rgb565 } => {
+                let mut out = Vec::with_capacity(2 + rgb565.len());
+                out.extend_from_slice(&[CMD_DISPLAY_UPDATE_ICON, *channel]);
+                out.extend_from_slice(rgb565);
+                out
+            }
+            HostPacket::IconRow { channel, row, rgb565 } => {
+                let mut out = vec![CMD_DISPLAY_UPDATE_ICON_ROW, *channel, *row];
+                out.extend_from_slice(rgb565);
+                out
+            }
+            HostPacket::IconRle { channel, runs } => {
+                let mut out = vec![CMD_DISPLAY_UPDATE_ICON_RLE, *channel];
+                out.extend_from_slice(&(runs.len() as u16).to_le_bytes());
+                out.extend_from_slice(runs);
+                out
+            }
+            HostPacket::Meters(levels) => {
+                let mut out = vec![CMD_DISPLAY_UPDATE_METER];
+                out.extend_from_slice(levels);
+                out
+            }
+            HostPacket::Balance { channel, balance } => {
+                vec![CMD_DISPLAY_UPDATE_BALANCE, *channel, (*balance).clamp(-100, 100) as u8]
+            }
+            HostPacket::Mute { channel, muted } => vec![CMD_DISPLAY_UPDATE_MUTE, *channel, *muted as u8],
+            HostPacket::EncoderMode { channel, mode } => vec![CMD_ENCODER_MODE, *channel, mode.wire()],
+            HostPacket::CalibrationStart => vec![CMD_CALIBRATION_START],
+            HostPacket::CalibrationCancel => vec![CMD_CALIBRATION_CANCEL],
+            HostPacket::FramingRequest => vec![CMD_FRAMING_REQUEST],
+            HostPacket::CapabilitiesRequest => vec![CMD_CAPABILITIES_REQUEST],
+        }
+    }
+
+    /// What the packet sets on the device, for packets where only the
+    /// latest value matters. A newer packet for the same slot makes an
+    /// unacknowledged older one pointless to retransmit.
+    pub fn slot(&self) -> Option<(u8, u8)> {
+        let (cmd, channel) = match self {
+            HostPacket::AppName { channel, .. } => (CMD_DISPLAY_UPDATE_APP_NAME, *channel),
+            HostPacket::Volume { channel, .. } => (CMD_DISPLAY_UPDATE_APP_VOLUME, *channel),
+            // Either form of a whole icon replaces the other
+            HostPacket::Icon { channel, .. } | HostPacket::IconRle { channel, .. } => {
+                (CMD_DISPLAY_UPDATE_ICON, *channel)
+            }
+            HostPacket::Meters(_) => (CMD_DISPLAY_UPDATE_METER, 0),
+            HostPacket::Balance { channel, .. } => (CMD_DISPLAY_UPDATE_BALANCE, *channel),
+            HostPacket::Mute { channel, .. } => (CMD_DISPLAY_UPDATE_MUTE, *channel),
+            HostPacket::EncoderMode { channel, .. } => (CMD_ENCODER_MODE, *channel),
+            _ => return None,
+        };
+        Some((cmd, channel))
+    }
+
+    /// Decode the packet at the start of `buf`, returning it and its length.
+    #[cfg(test)]
+    pub fn decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let (&cmd, _) = buf.split_first().ok_or(DecodeError::Incomplete)?;
+        let len = match cmd {
+            CMD_DISPLAY_UPDATE_ICON_RLE => {
+                let header = buf.get(..ICON_RLE_HEADER).ok_or(DecodeError::Incomplete)?;
+                let runs = u16::from_le_bytes([header[2], header[3]]) as usize;
+                if runs > ICON_RLE_MAX { return Err(DecodeError::Malformed(cmd)); }
+                ICON_RLE_HEADER + runs
+            }
+            _ => Self::packet_len(cmd).ok_or(DecodeError::Unknown(cmd))?,
+        };
+        if buf.len() < len { return Err(DecodeError::Incomplete); }
+        let p = &buf[..len];
+
+        let packet = match cmd {
+            CMD_HANDSHAKE_REQUEST => HostPacket::HandshakeRequest,
+            CMD_HANDSHAKE_ACK => HostPacket::HandshakeAck,
+            CMD_DISPLAY_UPDATE_APP_NAME => {
+                let field = &p[2..];
+                let end = field.iter().position(|&b| b == 0).ok_or(DecodeError::Malformed(cmd))?;
+                HostPacket::AppName {
+                    channel: p[1],
+                    name: String::from_utf8_lossy(&field[..end]).into_owned(),
+                }
+            }
+            CMD_DISPLAY_UPDATE_APP_VOLUME => HostPacket::Volume { channel: p[1], volume: p[2] },
+            CMD_DISPLAY_UPDATE_ICON => HostPacket::Icon { channel: p[1], rgb565: p[2..].to_vec() },
+            CMD_DISPLAY_UPDATE_ICON_ROW => HostPacket::IconRow { channel: p[1], row: p[2], rgb565: p[3..].to_vec() },
+            CMD_DISPLAY_UPDATE_ICON_RLE => HostPacket::IconRle { channel: p[1], runs: p[ICON_RLE_HEADER..].to_vec() },
+            CMD_DISPLAY_UPDATE_METER => {
+                let mut levels = [0u8; METER_CHANNELS];
+                levels.copy_from_slice(&p[1..]);
+                HostPacket::Meters(levels)
+            }
+            CMD_DISPLAY_UPDATE_BALANCE => HostPacket::Balance { channel: p[1], balance: p[2] as i8 },
+            CMD_DISPLAY_UPDATE_MUTE => HostPacket::Mute { channel: p[1], muted: p[2] != 0 },
+            CMD_ENCODER_MODE => HostPacket::EncoderMode {
+                channel: p[1],
+                mode: EncoderMode::from_wire(p[2]).ok_or(DecodeError::Malformed(cmd))?,
+            },
+            CMD_CALIBRATION_START => HostPacket::CalibrationStart,
+            CMD_CALIBRATION_CANCEL => HostPacket::CalibrationCancel,
+            CMD_FRAMING_REQUEST => HostPacket::FramingRequest,
+            CMD_CAPABILITIES_REQUEST => HostPacket::CapabilitiesRequest,
+            other => return Err(DecodeError::Unknown(other)),
+        };
+        Ok((packet, len))
+    }
+}
+
+// ── Device → host ────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DevicePacket {
+    /// Beacon / reply to CMD_HANDSHAKE_REQUEST; only decodes with valid magic
+    Handshake { device_type: u8, uuid: [u8; UUID_SIZE], version: (u8, u8) },
+    /// Fader position, 0-255
+    Fader { channel: u8, position: u8 },
+    /// Encoder in balance mode, -100 to 100
+    Balance { channel: u8, balance: i8 },
+    Button { channel: u8, event: ButtonEvent },
+    /// Phases: 0 = waiting bottom, 1 = waiting top, 2 = done & saved, 3 = cancelled
+    CalibrationStatus { channel: u8, phase: u8 },
+    /// Raw capture values for the debug panel
+    CalibrationDebug { channel: u8, kind: u8, v1: u16, v2: u16 },
+    /// The device switched to framing; always received raw
+    FramingAccepted { version: u8 },
+    /// A host frame arrived intact (ACK) or with a bad CRC (NAK)
+    FrameAck { seq: u8 },
+    FrameNak { seq: u8 },
+    /// Reply to CMD_CAPABILITIES_REQUEST
+    Capabilities(Capabilities),
+}
+
+impl DevicePacket {
+    /// Full packet length for a command byte, including the command itself.
+    pub fn packet_len(cmd: u8) -> Option<usize> {
+        Some(match cmd {
+            CMD_HANDSHAKE_RESPONSE => 2 + MAGIC_FIELD + UUID_SIZE + 2,
+            CMD_FADER_UPDATE | CMD_BALANCE_UPDATE | CMD_BUTTON_EVENT | CMD_CALIBRATION_STATUS => 3,
+            CMD_CALIBRATION_DEBUG => 7,
+            CMD_FRAMING_ACCEPT | CMD_FRAME_ACK | CMD_FRAME_NAK => 2,
+            CMD_CAPABILITIES => 12,
+            _ => return None,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            DevicePacket::Handshake { device_type, uuid, version } => {
+                let mut out = vec![CMD_HANDSHAKE_RESPONSE];
+                out.extend_from_slice(MAGIC_STRING);
+                out.resize(1 + MAGIC_FIELD, 0);
+                out.push(*device_type);
+                out.extend_from_slice(uuid);
+                out.extend_from_slice(&[version.0, version.1]);
+                out
+            }
+            DevicePacket::Fader { channel, position } => vec![CMD_FADER_UPDATE, *channel, *position],
+            DevicePacket::Balance { channel, balance } => vec![CMD_BALANCE_UPDATE, *channel, *balance as u8],
+            DevicePacket::Button { channel, event } => vec![CMD_BUTTON_EVENT, *channel, event.wire()],
+            DevicePacket::CalibrationStatus { channel, phase } => vec![CMD_CALIBRATION_STATUS, *channel, *phase],
+            DevicePacket::CalibrationDebug { channel, kind, v1, v2 } => {
+                let mut out = vec![CMD_CALIBRATION_DEBUG, *channel, *kind];
+                out.extend_from_slice(&v1.to_le_bytes());
+                out.extend_from_slice(&v2.to_le_bytes());
+                out
+            }
+            DevicePacket::FramingAccepted { version } => vec![CMD_FRAMING_ACCEPT, *version],
+            DevicePacket::FrameAck { seq } => vec![CMD_FRAME_ACK, *seq],
+            DevicePacket::FrameNak { seq } => vec![CMD_FRAME_NAK, *seq],
+            DevicePacket::Capabilities(caps) => {
+                let mut out = vec![CMD_CAPABILITIES, caps.channels];
+                out.extend_from_slice(&caps.display.0.to_le_bytes());
+                out.extend_from_slice(&caps.display.1.to_le_bytes());
+                out.extend_from_slice(&[caps.icon_format.wire(), caps.framing_version]);
+                out.extend_from_slice(&caps.commands.to_le_bytes());
+                out
+            }
+        }
+    }
+
+    /// Decode the packet at the start of `buf`, returning it and its length.
+    pub fn decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let (&cmd, _) = buf.split_first().ok_or(DecodeError::Incomplete)?;
+        let len = Self::packet_len(cmd).ok_or(DecodeError::Unknown(cmd))?;
+        if buf.len() < len { return Err(DecodeError::Incomplete); }
+        let p = &buf[..len];
+
+        let packet = match cmd {
+            CMD_HANDSHAKE_RESPONSE => {
+                let magic = &p[1..1 + MAGIC_FIELD];
+                if &magic[..MAGIC_STRING.len()] != MAGIC_STRING {
+                    return Err(DecodeError::Malformed(cmd));
+                }
+                let rest = &p[1 + MAGIC_FIELD..];
+                let mut uuid = [0u8; UUID_SIZE];
+                uuid.copy_from_slice(&rest[1..1 + UUID_SIZE]);
+                DevicePacket::Handshake {
+                    device_type: rest[0],
+                    uuid,
+                    version: (rest[1 + UUID_SIZE], rest[2 + UUID_SIZE]),
+                }
+            }
+            CMD_FADER_UPDATE => DevicePacket::Fader { channel: p[1], position: p[2] },
+            CMD_BALANCE_UPDATE => DevicePacket::Balance { channel: p[1], balance: p[2] as i8 },
+            CMD_BUTTON_EVENT => DevicePacket::Button {
+                channel: p[1],
+                event: ButtonEvent::from_wire(p[2]).ok_or(DecodeError::Malformed(cmd))?,
+            },
+            CMD_CALIBRATION_STATUS => DevicePacket::CalibrationStatus { channel: p[1], phase: p[2] },
+            CMD_CALIBRATION_DEBUG => DevicePacket::CalibrationDebug {
+                channel: p[1],
+                kind: p[2],
+                v1: u16::from_le_bytes([p[3], p[4]]),
+                v2: u16::from_le_bytes([p[5], p[6]]),
+            },
+            CMD_FRAMING_ACCEPT => DevicePacket::FramingAccepted { version: p[1] },
+            CMD_FRAME_ACK => DevicePacket::FrameAck { seq: p[1] },
+            CMD_FRAME_NAK => DevicePacket::FrameNak { seq: p[1] },
+            CMD_CAPABILITIES => DevicePacket::Capabilities(Capabilities {
+                channels: p[1],
+                display: (u16::from_le_bytes([p[2], p[3]]), u16::from_le_bytes([p[4], p[5]])),
+                icon_format: IconFormat::from_wire(p[6]),
+                framing_version: p[7],
+                commands: u32::from_le_bytes([p[8], p[9], p[10], p[11]]),
+            }),
+            other => return Err(DecodeError::Unknown(other)),
+        };
+        Ok((packet, len))
+    }
+}
+
+/// A CMD_FADER_UPDATE position (0-255) as a 0-100 percentage.
+pub fn position_percent(position: u8) -> u8 {
+    (position as f32 / 255.0 * 100.0) as u8
+}
+
+// ── Incremental decoding ─────────────────────────────────────────────────────
+
+/// Collects bytes from the port and hands out whole device packets.
+/// A truncated packet waits for the rest; a byte that doesn't start a
+/// well-formed packet is dropped so the stream resyncs on the next command.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    buf: Vec<u8>,
+}
+
+impl Decoder {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    pub fn next_packet(&mut self) -> Option<DevicePacket> {
+        loop {
+            match DevicePacket::decode(&self.buf) {
+                Ok((packet, len)) => {
+                    self.buf.drain(..len);
+                    return Some(packet);
+                }
+                Err(DecodeError::Incomplete) => return None,
+                Err(DecodeError::Unknown(_) | DecodeError::Malformed(_)) => {
+                    self.buf.remove(0);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host_round_trip(packet: HostPacket) {
+        let bytes = packet.encode();
+        assert_eq!(Some(bytes.len()), HostPacket::packet_len(bytes[0]), "{packet:?}");
+        assert_eq!(HostPacket::decode(&bytes), Ok((packet, bytes.len())));
+    }
+
+    fn device_round_trip(packet: DevicePacket) {
+        let bytes = packet.encode();
+        assert_eq!(Some(bytes.len()), DevicePacket::packet_len(bytes[0]), "{packet:?}");
+        assert_eq!(DevicePacket::decode(&bytes), Ok((packet, bytes.len())));
+    }
+
+    #[test]
+    fn host_packets_round_trip() {
+        host_round_trip(HostPacket::HandshakeRequest);
+        host_round_trip(HostPacket::HandshakeAck);
+        host_round_trip(HostPacket::AppName { channel: 3, name: "Spotify".into() });
+        host_round_trip(HostPacket::AppName { channel: 0, name: String::new() });
+        host_round_trip(HostPacket::Volume { channel: 1, volume: 100 });
+        host_round_trip(HostPacket::Icon { channel: 4, rgb565: (0..ICON_BYTES).map(|i| i as u8).collect() });
+        host_round_trip(HostPacket::IconRow { channel: 2, row: 63, rgb565: vec![0xAB; ICON_ROW_BYTES] });
+        host_round_trip(HostPacket::Meters([0, 64, 128, 200, 255]));
+        host_round_trip(HostPacket::Balance { channel: 2, balance: -100 });
+        host_round_trip(HostPacket::Balance { channel: 2, balance: 37 });
+        host_round_trip(HostPacket::Mute { channel: 0, muted: true });
+        host_round_trip(HostPacket::Mute { channel: 0, muted: false });
+        for mode in EncoderMode::ALL {
+            host_round_trip(HostPacket::EncoderMode { channel: 1, mode });
+        }
+        host_round_trip(HostPacket::CalibrationStart);
+        host_round_trip(HostPacket::CalibrationCancel);
+        host_round_trip(HostPacket::FramingRequest);
+        host_round_trip(HostPacket::CapabilitiesRequest);
+    }
+
+    #[test]
+    fn device_packets_round_trip() {
+        device_round_trip(DevicePacket::Handshake {
+            device_type: 1,
+            uuid: std::array::from_fn(|i| i as u8 * 17),
+            version: (1, 4),
+        });
+        device_round_trip(DevicePacket::Fader { channel: 4, position: 255 });
+        device_round_trip(DevicePacket::Balance { channel: 0, balance: -55 });
+        for event in [ButtonEvent::Press, ButtonEvent::LongPress, ButtonEvent::DoublePress] {
+            device_round_trip(DevicePacket::Button { channel: 2, event });
+        }
+        device_round_trip(DevicePacket::CalibrationStatus { channel: 3, phase: 1 });
+        device_round_trip(DevicePacket::CalibrationDebug { channel: 1, kind: 1, v1: 12, v2: 1011 });
+        device_round_trip(DevicePacket::FramingAccepted { version: FRAMING_VERSION });
+        device_round_trip(DevicePacket::FrameAck { seq: 255 });
+        device_round_trip(DevicePacket::FrameNak { seq: 7 });
+        device_round_trip(DevicePacket::Capabilities(Capabilities::LEGACY));
+        device_round_trip(DevicePacket::Capabilities(Capabilities {
+            channels: 3,
+            display: (0, 0),
+            icon_format: IconFormat::None,
+            framing_version: FRAMING_VERSION,
+            commands: 0x8000_0001,
+        }));
+    }
+
+    #[test]
+    fn lengths_match_firmware_structs() {
+        // sizeof() of the packed structs in protocol.h
+        assert_eq!(DevicePacket::packet_len(CMD_HANDSHAKE_RESPONSE), Some(30));
+        assert_eq!(HostPacket::packet_len(CMD_DISPLAY_UPDATE_APP_NAME), Some(66));
+        assert_eq!(HostPacket::packet_len(CMD_DISPLAY_UPDATE_METER), Some(6));
+        assert_eq!(HostPacket::packet_len(CMD_DISPLAY_UPDATE_ICON), Some(8194));
+        assert_eq!(HostPacket::packet_len(CMD_DISPLAY_UPDATE_ICON_ROW), Some(131));
+        assert_eq!(DevicePacket::packet_len(CMD_CAPABILITIES), Some(12));
+    }
+
+    #[test]
+    fn rle_icons_carry_their_length() {
+        for runs in [vec![], vec![0x80, 0x12, 0x34], vec![7; ICON_RLE_MAX]] {
+            let packet = HostPacket::IconRle { channel: 4, runs };
+            let bytes = packet.encode();
+            assert_eq!(HostPacket::decode(&bytes), Ok((packet, bytes.len())));
+            assert_eq!(HostPacket::decode(&bytes[..bytes.len() - 1]), Err(DecodeError::Incomplete));
+        }
+        let mut oversized = HostPacket::IconRle { channel: 0, runs: vec![] }.encode();
+        oversized[2..4].copy_from_slice(&(ICON_RLE_MAX as u16 + 1).to_le_bytes());
+        assert_eq!(HostPacket::decode(&oversized), Err(DecodeError::Malformed(CMD_DISPLAY_UPDATE_ICON_RLE)));
+    }
+
+    #[test]
+    fn long_names_are_cut_on_a_char_boundary() {
+        let name = "é".repeat(40);  // 80 bytes
+        let bytes = HostPacket::AppName { channel: 0, name }.encode();
+        assert_eq!(bytes.len(), 66);
+        let Ok((HostPacket::AppName { name, .. }, _)) = HostPacket::decode(&bytes) else { panic!() };
+        assert_eq!(name, "é".repeat(31));
+    }
+
+    #[test]
+    fn decoder_waits_for_split_packets() {
+        let bytes = DevicePacket::Fader { channel: 1, position: 9 }.encode();
+        let mut decoder = Decoder::default();
+        decoder.push(&bytes[..2]);
+        assert_eq!(decoder.next_packet(), None);
+        decoder.push(&bytes[2..]);
+        assert_eq!(decoder.next_packet(), Some(DevicePacket::Fader { channel: 1, position: 9 }));
+        assert_eq!(decoder.next_packet(), None);
+    }
+
+    #[test]
+    fn decoder_resyncs_past_garbage() {
+        let mut decoder = Decoder::default();
+        decoder.push(&[0xFF, 0x00, 0x42]);
+        decoder.push(&DevicePacket::Balance { channel: 0, balance: 10 }.encode());
+        // A button event with an unknown gesture is dropped byte by byte
+        decoder.push(&[CMD_BUTTON_EVENT, 0, 9]);
+        decoder.push(&DevicePacket::CalibrationStatus { channel: 2, phase: 0 }.encode());
+        assert_eq!(decoder.next_packet(), Some(DevicePacket::Balance { channel: 0, balance: 10 }));
+        assert_eq!(decoder.next_packet(), Some(DevicePacket::CalibrationStatus { channel: 2, phase: 0 }));
+        assert_eq!(decoder.next_packet(), None);
+    }
+
+    #[test]
+    fn handshake_needs_magic() {
+        let mut bytes = DevicePacket::Handshake { device_type: 1, uuid: [0; UUID_SIZE], version: (1, 0) }.encode();
+        bytes[3] = b'X';
+        assert_eq!(DevicePacket::decode(&bytes), Err(DecodeError::Malformed(CMD_HANDSHAKE_RESPONSE)));
+    }
+}
@@ -1,26 +1,91 @@
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::comms::scanner::SharedPort;
+use crate::comms::capabilities::Capabilities;
+use crate::comms::link::Framing;
+use crate::comms::protocol::CMD_DISPLAY_UPDATE_METER;
+use crate::comms::worker::DeviceIo;
+use crate::utils::channel_group::ChannelGroup;
+use crate::utils::taper::Taper;
+
+/// What a channel's rotary encoder adjusts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EncoderMode {
+    /// Fine volume steps alongside the fader
+    #[default]
+    Volume,
+    /// Left/right balance of the assigned session
+    Balance,
+}
+
+impl EncoderMode {
+    pub const ALL: [EncoderMode; 2] = [EncoderMode::Volume, EncoderMode::Balance];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EncoderMode::Volume => "volume",
+            EncoderMode::Balance => "balance",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == s)
+    }
+
+    /// Mode byte of CMD_ENCODER_MODE
+    pub fn wire(self) -> u8 {
+        match self {
+            EncoderMode::Volume => 0,
+            EncoderMode::Balance => 1,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn from_wire(b: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.wire() == b)
+    }
+}
+
+impl std::fmt::Display for EncoderMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EncoderMode::Volume => "Knob: volume",
+            EncoderMode::Balance => "Knob: balance",
+        })
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
+#[allow(dead_code)]  // Lost is reserved for per-device loss; today a lost port ends the session
 pub enum DeviceStatus {
     Connected,
     Lost,
 }
 
+/// First firmware that draws level meters. Older builds don't know
+/// CMD_DISPLAY_UPDATE_METER and lose packet sync on it.
+pub const METER_FIRMWARE: (u8, u8) = (1, 5);
+
 pub struct DeviceInfo {
     pub port_name: String,
-    pub port: SharedPort,
+    pub io: DeviceIo,
     pub uuid: [u8; 16],
     pub version: (u8, u8),
+    pub framing: Framing,
+    pub capabilities: Option<Capabilities>,  // None = firmware too old to say
     pub rename: Option<String>,
     pub status: DeviceStatus,
     pub watchdog_cancel: Arc<AtomicBool>,
-    pub channel_assignments: [String; 5],
-    pub rx_buf: Vec<u8>,
+    pub channel_assignments: [ChannelGroup; 5],
+    pub channel_tapers: [Taper; 5],
+    pub encoder_modes: [EncoderMode; 5],
     pub channel_volumes: [u8; 5],
+    pub channel_balances: [i8; 5],                // last balance sent/received
+    pub channel_mutes: [bool; 5],                 // last mute state sent
+    pub icon_progress: [Option<f32>; 5],          // icon uploads in flight, 0.0-1.0
     pub last_fader_rx: [Option<std::time::Instant>; 5],
+    pub meter_levels: [u8; 5],                    // last meter frame sent
+    pub last_meter_tx: Option<std::time::Instant>,
     pub cal_state: Option<(u8, u8)>,  // (channel, phase) while calibrating
     pub cal_debug: Vec<String>,       // recent calibration capture log lines
 }
@@ -45,6 +110,46 @@ impl DeviceInfo {
         format!("v{}.{}", self.version.0, self.version.1)
     }
 
+    pub fn link_string(&self) -> &'static str {
+        match self.framing {
+            Framing::V1 => "Raw (v1)",
+            Framing::V2 => "Framed, CRC + ACK (v2)",
+        }
+    }
+
+    /// What the device reported, or what pre-1.2 firmware is known to do
+    pub fn caps(&self) -> Capabilities {
+        self.capabilities.unwrap_or(Capabilities::LEGACY)
+    }
+
+    pub fn firmware_too_old(&self) -> bool {
+        self.capabilities.is_none()
+    }
+
+    pub fn supports(&self, cmd: u8) -> bool {
+        self.caps().supports(cmd)
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.caps().channel_count()
+    }
+
+    /// Meters go out only to firmware that both lists the command and is
+    /// new enough to have shipped it
+    pub fn shows_meters(&self) -> bool {
+        self.version >= METER_FIRMWARE && self.supports(CMD_DISPLAY_UPDATE_METER)
+    }
+
+    pub fn capabilities_string(&self) -> String {
+        let Some(caps) = self.capabilities else { return "Unknown (firmware too old)".into() };
+        let display = if caps.has_display() {
+            format!("{}×{} displays", caps.display.0, caps.display.1)
+        } else {
+            "no displays".into()
+        };
+        format!("{} channels, {display}", caps.channels)
+    }
+
     pub fn cancel_watchdog(&self) {
         self.watchdog_cancel.store(true, Ordering::Relaxed);
     }
@@ -0,0 +1,110 @@
+// framing.rs - v2 link framing, matching the firmware's utils/framing.
+//
+//   START | len (u16 LE) | seq | payload (one codec packet) | CRC-16 (LE)
+//
+// The CRC is CRC-16/CCITT-FALSE over len, seq and payload.
+
+use crate::comms::codec::ICON_BYTES;
+use crate::comms::protocol::FRAME_START;
+
+/// Largest payload either side sends: an icon packet
+pub const MAX_PAYLOAD: usize = 2 + ICON_BYTES;
+/// START, len and seq
+const HEADER: usize = 4;
+const TRAILER: usize = 2;
+
+pub fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xFFFF, |mut crc: u16, &b| {
+        crc ^= (b as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+pub fn encode_frame(seq: u8, payload: &[u8]) -> Vec<u8> {
+    let len = payload.len() as u16;
+    let mut out = Vec::with_capacity(HEADER + payload.len() + TRAILER);
+    out.push(FRAME_START);
+    out.extend_from_slice(&len.to_le_bytes());
+    out.push(seq);
+    out.extend_from_slice(payload);
+    let crc = crc16(&out[1..]);
+    out.extend_from_slice(&crc.to_le_bytes());
+    out
+}
+
+/// Pulls whole frames out of the byte stream. Anything that isn't a frame
+/// with a good CRC is skipped a byte at a time until the next START.
+#[derive(Debug, Default)]
+pub struct Deframer {
+    buf: Vec<u8>,
+}
+
+impl Deframer {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Next good frame as (seq, payload).
+    pub fn next_frame(&mut self) -> Option<(u8, Vec<u8>)> {
+        loop {
+            let start = self.buf.iter().position(|&b| b == FRAME_START)?;
+            self.buf.drain(..start);
+            if self.buf.len() < HEADER { return None; }
+
+            let len = u16::from_le_bytes([self.buf[1], self.buf[2]]) as usize;
+            if len == 0 || len > MAX_PAYLOAD {
+                self.buf.remove(0);
+                continue;
+            }
+            let total = HEADER + len + TRAILER;
+            if self.buf.len() < total { return None; }
+
+            let body = &self.buf[1..HEADER + len];
+            let crc = u16::from_le_bytes([self.buf[total - 2], self.buf[total - 1]]);
+            if crc16(body) != crc {
+                self.buf.remove(0);
+                continue;
+            }
+            let seq = self.buf[3];
+            let payload = self.buf[HEADER..HEADER + len].to_vec();
+            self.buf.drain(..total);
+            return Some((seq, payload));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc_matches_ccitt_false_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn frames_survive_noise_and_corruption() {
+        let mut corrupt = encode_frame(1, &[0x10, 0, 42]);
+        corrupt[5] ^= 0xFF;
+
+        let mut deframer = Deframer::default();
+        deframer.push(&[0x00, FRAME_START, 0xFF]);
+        deframer.push(&corrupt);
+        deframer.push(&encode_frame(2, &[0x10, 1, 99]));
+        assert_eq!(deframer.next_frame(), Some((2, vec![0x10, 1, 99])));
+        assert_eq!(deframer.next_frame(), None);
+    }
+
+    #[test]
+    fn split_frames_wait_for_the_rest() {
+        let frame = encode_frame(9, &[0x17, 4]);
+        let mut deframer = Deframer::default();
+        deframer.push(&frame[..5]);
+        assert_eq!(deframer.next_frame(), None);
+        deframer.push(&frame[5..]);
+        assert_eq!(deframer.next_frame(), Some((9, vec![0x17, 4])));
+    }
+}
@@ -0,0 +1,169 @@
+// link.rs - One device's serial link. Sends codec packets raw (v1) or
+// framed (v2) and, under v2, retransmits host frames until the device
+// acknowledges them.
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+use serialport::SerialPort;
+
+use crate::comms::codec::{Decoder, DevicePacket, HostPacket};
+use crate::comms::framing::{encode_frame, Deframer};
+
+/// Sends before giving up on a frame
+const MAX_TRIES: u8 = 3;
+/// ACK wait once the frame's last byte should have left the port
+const ACK_TIMEOUT_MS: u64 = 150;
+/// 115200 baud, 10 bits a byte
+const BYTES_PER_MS: u64 = 11;
+/// Unacknowledged frames in flight; the worker holds further packets back
+/// until acknowledgements (or give-ups) make room
+const MAX_PENDING: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// Raw packets, implicit lengths, no acknowledgements
+    V1,
+    /// CRC-checked frames, ACK/NAK and retransmit
+    V2,
+}
+
+struct Pending {
+    seq: u8,
+    slot: Option<(u8, u8)>,
+    frame: Vec<u8>,
+    /// When to give up waiting for the ACK and send it again
+    deadline: Instant,
+    tries: u8,
+    /// The device reported it damaged; resend without waiting
+    nacked: bool,
+}
+
+pub struct Link {
+    port: Box<dyn SerialPort + Send>,
+    framing: Framing,
+    next_seq: u8,
+    pending: VecDeque<Pending>,
+    /// When everything written so far should be through the UART; a frame
+    /// queued behind others can't be acknowledged before then
+    wire_free_at: Instant,
+    decoder: Decoder,
+    deframer: Deframer,
+}
+
+/// Write `bytes` behind whatever is still going out, returning when the
+/// last of them should have left.
+fn write_queued(port: &mut Box<dyn SerialPort + Send>, wire_free_at: &mut Instant, bytes: &[u8]) -> Instant {
+    let _ = port.write_all(bytes);
+    let _ = port.flush();
+    let start = (*wire_free_at).max(Instant::now());
+    *wire_free_at = start + Duration::from_millis(bytes.len() as u64 / BYTES_PER_MS + 1);
+    *wire_free_at
+}
+
+impl Link {
+    pub fn new(port: Box<dyn SerialPort + Send>, framing: Framing) -> Self {
+        Self {
+            port,
+            framing,
+            next_seq: 0,
+            pending: VecDeque::new(),
+            wire_free_at: Instant::now(),
+            decoder: Decoder::default(),
+            deframer: Deframer::default(),
+        }
+    }
+
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Whether another frame would overrun the window of unacknowledged
+    /// ones. Nothing is dropped to make room: callers wait and poll.
+    pub fn backlogged(&self) -> bool {
+        self.pending.len() >= MAX_PENDING
+    }
+
+    pub fn send(&mut self, packet: &HostPacket) {
+        let payload = packet.encode();
+        if self.framing == Framing::V1 {
+            write_queued(&mut self.port, &mut self.wire_free_at, &payload);
+            return;
+        }
+
+        // A newer value supersedes whatever is still waiting for the same slot
+        let slot = packet.slot();
+        if slot.is_some() {
+            self.pending.retain(|p| p.slot != slot);
+        }
+
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        let frame = encode_frame(seq, &payload);
+        let deadline = write_queued(&mut self.port, &mut self.wire_free_at, &frame) + Duration::from_millis(ACK_TIMEOUT_MS);
+        self.pending.push_back(Pending { seq, slot, frame, deadline, tries: 1, nacked: false });
+    }
+
+    /// Read what the device sent, settle acknowledgements and retransmit
+    /// overdue frames. Returns the device's packets, ACK/NAK excluded.
+    pub fn poll(&mut self) -> Vec<DevicePacket> {
+        let mut packets = vec![];
+        let avail = self.port.bytes_to_read().unwrap_or(0) as usize;
+        if avail > 0 {
+            let mut buf = vec![0u8; avail];
+            if let Ok(n) = self.port.read(&mut buf) {
+                buf.truncate(n);
+                match self.framing {
+                    Framing::V1 => {
+                        self.decoder.push(&buf);
+                        while let Some(p) = self.decoder.next_packet() { packets.push(p); }
+                    }
+                    Framing::V2 => self.deframer.push(&buf),
+                }
+            }
+        }
+
+        while let Some((_, payload)) = self.deframer.next_frame() {
+            let Ok((packet, len)) = DevicePacket::decode(&payload) else { continue };
+            if len != payload.len() { continue; }
+            match packet {
+                DevicePacket::FrameAck { seq } => self.pending.retain(|p| p.seq != seq),
+                DevicePacket::FrameNak { seq } => {
+                    // The seq of a damaged frame may itself be damaged; the timeout covers that
+                    if let Some(p) = self.pending.iter_mut().find(|p| p.seq == seq) {
+                        p.nacked = true;
+                    }
+                }
+                other => packets.push(other),
+            }
+        }
+
+        self.retransmit();
+        packets
+    }
+
+    /// Whether the port still answers; used by the watchdog.
+    pub fn is_alive(&mut self) -> bool {
+        self.port.flush().is_ok()
+    }
+
+    fn retransmit(&mut self) {
+        let port = &mut self.port;
+        let wire_free_at = &mut self.wire_free_at;
+        let now = Instant::now();
+        self.pending.retain_mut(|p| {
+            if !p.nacked && now < p.deadline {
+                return true;
+            }
+            if p.tries >= MAX_TRIES {
+                eprintln!("Device never acknowledged frame {} (command 0x{:02X})", p.seq, p.frame[4]);
+                return false;
+            }
+            p.deadline = write_queued(port, wire_free_at, &p.frame) + Duration::from_millis(ACK_TIMEOUT_MS);
+            p.tries += 1;
+            p.nacked = false;
+            true
+        });
+    }
+}
@@ -1,3 +1,8 @@
 pub mod protocol;
+pub mod codec;
+pub mod capabilities;
+pub mod framing;
+pub mod link;
+pub mod worker;
 pub mod scanner;
 pub mod device_info;
@@ -1,4 +1,5 @@
 // protocol.rs - Shared protocol definitions matching Arduino protocol.h
+// Packet layouts live in codec.rs.
 
 pub const CMD_HANDSHAKE_REQUEST: u8 = 0x01;
 pub const CMD_HANDSHAKE_ACK: u8 = 0x02;
@@ -8,8 +9,42 @@ pub const CMD_ECHO_UUID: u8 = 0x04;
 pub const  CMD_DISPLAY_UPDATE_APP_NAME: u8 = 0x05;
 pub const  CMD_DISPLAY_UPDATE_APP_VOLUME: u8 = 0x06;
 pub const  CMD_DISPLAY_UPDATE_ICON: u8 = 0x07;
+// One row of an icon, so a transfer doesn't hold up everything else
+pub const  CMD_DISPLAY_UPDATE_ICON_ROW: u8 = 0x1B;
+// Run-length encoded icon: cmd, channel, run bytes (u16 LE), runs
+pub const  CMD_DISPLAY_UPDATE_ICON_RLE: u8 = 0x1C;
+pub const  CMD_DISPLAY_UPDATE_METER: u8 = 0x0A;
+pub const  CMD_DISPLAY_UPDATE_BALANCE: u8 = 0x0B;
+pub const  CMD_DISPLAY_UPDATE_MUTE: u8 = 0x0D;
+
+// What a channel's encoder adjusts: 0 = volume, 1 = balance
+pub const CMD_ENCODER_MODE: u8 = 0x0C;
 
 pub const CMD_FADER_UPDATE: u8 = 0x10;
+pub const CMD_BALANCE_UPDATE: u8 = 0x13;
+
+// Knob gestures, device -> host
+pub const CMD_BUTTON_EVENT: u8 = 0x14;
+pub const BUTTON_EVENT_PRESS: u8 = 1;
+pub const BUTTON_EVENT_LONG_PRESS: u8 = 2;
+pub const BUTTON_EVENT_DOUBLE_PRESS: u8 = 3;
+
+// v2 framing, negotiated after the handshake (see framing.rs). Request
+// and accept are raw; ACK/NAK carry the sequence number of a host frame.
+pub const FRAME_START: u8 = 0xA5;
+pub const FRAMING_VERSION: u8 = 2;
+pub const CMD_FRAMING_REQUEST: u8 = 0x15;
+pub const CMD_FRAMING_ACCEPT: u8 = 0x16;
+pub const CMD_FRAME_ACK: u8 = 0x17;
+pub const CMD_FRAME_NAK: u8 = 0x18;
+
+// Capabilities, asked for after framing. Firmware before 1.2 stays silent.
+pub const CMD_CAPABILITIES_REQUEST: u8 = 0x19;
+pub const CMD_CAPABILITIES: u8 = 0x1A;
+pub const DEVICE_TYPE_VOLUME_CONTROLLER: u8 = 0x01;
+#[allow(dead_code)] // anything but RGB565 reads as none
+pub const ICON_FORMAT_NONE: u8 = 0;
+pub const ICON_FORMAT_RGB565: u8 = 1;
 
 // Calibration. Status phases: 0 = waiting bottom, 1 = waiting top,
 // 2 = done & saved, 3 = cancelled
@@ -20,58 +55,4 @@ pub const CMD_CALIBRATION_DEBUG: u8 = 0x12;
 
 pub const MAGIC_STRING: &[u8] = b"FADERFLOW";
 pub const UUID_SIZE: usize = 16;
-
-#[repr(C, packed)]
-#[derive(Debug, Copy, Clone)]
-pub struct HandshakeResponse {
-    pub cmd: u8,
-    pub magic: [u8; 10],
-    pub device_type: u8,
-    pub uuid: [u8; UUID_SIZE],
-    pub version_major: u8,
-    pub version_minor: u8,
-}
-
-#[repr(C, packed)]
-#[derive(Debug, Copy, Clone)]
-pub struct FaderMessage {
-    pub cmd: u8,
-    pub channel: u8,
-    pub position: u8,
-}
-
-#[repr(C, packed)]
-#[derive(Debug, Copy, Clone)]
-pub struct DisplayUpdateAppCommand {
-    pub cmd: u8,        // CMD_SET_APP
-    pub channel: u8,
-    pub name: [u8; 64],
-}
-
-#[repr(C, packed)]
-#[derive(Debug, Copy, Clone)]
-pub struct DisplayUpdateVolumeCommand {
-    pub cmd: u8,        // CMD_SET_VOLUME
-    pub channel: u8,
-    pub volume: u8,     // 0-100
-}
-
-#[repr(C, packed)]
-#[derive(Debug, Copy, Clone)]
-pub struct DisplayUpdateIconCommand {
-    pub cmd: u8,        // CMD_SET_ICON
-    pub channel: u8,
-    // Followed by: uint8_t iconData[8192]
-}
-
-impl HandshakeResponse {
-    pub fn is_valid(&self) -> bool {
-        &self.magic[..9] == MAGIC_STRING
-    }
-}
-
-impl FaderMessage {
-    pub fn position_percent(&self) -> u8 {
-        (self.position as f32 / 255.0 * 100.0) as u8
-    }
-}
\ No newline at end of file
+pub const METER_CHANNELS: usize = 5;
@@ -5,18 +5,23 @@ use std::time::{Duration, Instant};
 use std::thread;
 use std::sync::mpsc;
 
-use crate::comms::protocol::{HandshakeResponse, CMD_HANDSHAKE_ACK, CMD_HANDSHAKE_REQUEST, CMD_HANDSHAKE_RESPONSE};
+use crate::comms::capabilities::Capabilities;
+use crate::comms::codec::{Decoder, DevicePacket, HostPacket};
+use crate::comms::link::{Framing, Link};
+use crate::comms::protocol::{DEVICE_TYPE_VOLUME_CONTROLLER, FRAMING_VERSION};
 
 pub const RESCAN_DELAY_SECS: u64 = 3;
 
 const ARDUINO_BOOT_WAIT_MS: u64 = 500;   // just enough to let DTR reset start
 const HANDSHAKE_TIMEOUT_SECS: u64 = 8;  // beacon fires every 500ms, give plenty of room
+const FRAMING_REPLY_MS: u64 = 300;      // v1 firmware never answers; don't hold up the scan
+const CAPABILITIES_REPLY_MS: u64 = 300; // neither does firmware before 1.2
 const WATCHDOG_INTERVAL_MS: u64 = 500;
 const WATCHDOG_FAIL_THRESHOLD: u32 = 3;
 
 // ── Shared port handle ───────────────────────────────────────────────────────
 
-pub type SharedPort = Arc<Mutex<Box<dyn SerialPort + Send>>>;
+pub type SharedPort = Arc<Mutex<Link>>;
 
 // ── Events flowing from scanner → app ───────────────────────────────────────
 
@@ -30,6 +35,8 @@ pub enum ScanEvent {
         port: SharedPort,
         uuid: [u8; 16],
         version: (u8, u8),
+        /// None when the firmware is too old to report them
+        capabilities: Option<Capabilities>,
     },
     ScanComplete { found: usize },
     DeviceLost { port_name: String },
@@ -38,14 +45,6 @@ pub enum ScanEvent {
 
 // ── Public API ───────────────────────────────────────────────────────────────
 
-/// Spawns the scan on a background thread.
-/// Returns a Receiver the app should poll via ScanTick.
-pub fn start_scan() -> mpsc::Receiver<ScanEvent> {
-    let (tx, rx) = mpsc::channel();
-    thread::spawn(move || run_scan(tx));
-    rx
-}
-
 /// Spawns a watchdog for a connected device.
 /// Sends `DeviceLost` through `tx` if the port goes silent.
 pub fn start_watchdog(
@@ -66,7 +65,7 @@ pub fn start_watchdog(
             }
 
             let alive = port.lock()
-                .map(|mut p| p.flush().is_ok())
+                .map(|mut p| p.is_alive())
                 .unwrap_or(false);
 
             if alive {
@@ -114,7 +113,7 @@ fn run_scan(tx: mpsc::Sender<ScanEvent>) {
         });
 
         match probe_port(&name) {
-            Ok((port, uuid, version)) => {
+            Ok((port, uuid, version, capabilities)) => {
                 found += 1;
                 let shared = Arc::new(Mutex::new(port));
                 let _ = tx.send(ScanEvent::DeviceFound {
@@ -122,6 +121,7 @@ fn run_scan(tx: mpsc::Sender<ScanEvent>) {
                     port: shared,
                     uuid,
                     version,
+                    capabilities,
                 });
             }
             Err(reason) => {
@@ -133,7 +133,11 @@ fn run_scan(tx: mpsc::Sender<ScanEvent>) {
     let _ = tx.send(ScanEvent::ScanComplete { found });
 }
 
-fn probe_port(port_name: &str) -> Result<(Box<dyn SerialPort + Send>, [u8; 16], (u8, u8)), String> {
+/// Open port, UUID, firmware version and capabilities of a device that
+/// answered the handshake
+type ProbedDevice = (Link, [u8; 16], (u8, u8), Option<Capabilities>);
+
+fn probe_port(port_name: &str) -> Result<ProbedDevice, String> {
     let mut port = serialport::new(port_name, 115200)
         .timeout(Duration::from_millis(20))
         .flow_control(serialport::FlowControl::None)
@@ -146,65 +150,98 @@ fn probe_port(port_name: &str) -> Result<(Box<dyn SerialPort + Send>, [u8; 16],
     // Actively request a handshake — handles two cases:
     //   1. Fresh boot: Arduino ignores this and beacons anyway
     //   2. Already running: Arduino won't beacon, so we must ask
-    port.write_all(&[CMD_HANDSHAKE_REQUEST])
+    port.write_all(&HostPacket::HandshakeRequest.encode())
         .map_err(|e| format!("Failed to send handshake request: {e}"))?;
     port.flush().ok();
 
     let deadline = Instant::now() + Duration::from_secs(HANDSHAKE_TIMEOUT_SECS);
+    let mut decoder = Decoder::default();
 
     loop {
         if Instant::now() > deadline {
             return Err("Handshake timeout".into());
         }
 
-        // Read one byte at a time — sync to the beacon start byte
-        let mut byte = [0u8; 1];
-        match port.read(&mut byte) {
-            Ok(_) => {}
+        let mut chunk = [0u8; 64];
+        match port.read(&mut chunk) {
+            Ok(n) => decoder.push(&chunk[..n]),
             Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
                 continue;
             }
             Err(e) => return Err(format!("Read error: {e}")),
         }
 
-        if byte[0] != CMD_HANDSHAKE_RESPONSE {
-            // Not the start of a handshake, keep scanning
-            continue;
-        }
+        // The decoder skips noise and bad magic; a partial beacon waits
+        // for the rest (or the next one, 500ms later)
+        while let Some(packet) = decoder.next_packet() {
+            let DevicePacket::Handshake { device_type, uuid, version } = packet else { continue };
 
-        // Found the command byte — read the rest of the struct
-        let remaining = std::mem::size_of::<HandshakeResponse>() - 1;
-        let mut rest = vec![0u8; remaining];
-        match port.read_exact(&mut rest) {
-            Ok(_) => {}
-            Err(_) => {
-                // Partial read — the beacon will fire again in 500ms, keep waiting
-                continue;
+            // Valid — ACK to stop beaconing
+            port.write_all(&HostPacket::HandshakeAck.encode())
+                .map_err(|e| format!("ACK failed: {e}"))?;
+            port.flush().ok();
+
+            if device_type != DEVICE_TYPE_VOLUME_CONTROLLER {
+                return Err(format!("Unsupported device type 0x{device_type:02X}"));
             }
-        }
 
-        let mut full = vec![0u8; std::mem::size_of::<HandshakeResponse>()];
-        full[0] = CMD_HANDSHAKE_RESPONSE;
-        full[1..].copy_from_slice(&rest);
+            let framing = negotiate_framing(&mut *port);
+            let mut link = Link::new(port, framing);
+            let capabilities = query_capabilities(&mut link);
+            return Ok((link, uuid, version, capabilities));
+        }
+    }
+}
 
-        let response: HandshakeResponse = unsafe {
-            std::ptr::read(full.as_ptr() as *const _)
-        };
+/// Ask for v2 framing. Firmware that predates it drops the request as an
+/// unknown byte, so silence means v1.
+fn negotiate_framing(port: &mut dyn SerialPort) -> Framing {
+    if port.write_all(&HostPacket::FramingRequest.encode()).is_err() {
+        return Framing::V1;
+    }
+    port.flush().ok();
 
-        if !response.is_valid() {
-            // Bad magic — might have caught a partial beacon, wait for next one
-            continue;
+    let deadline = Instant::now() + Duration::from_millis(FRAMING_REPLY_MS);
+    let mut decoder = Decoder::default();
+    while Instant::now() < deadline {
+        let mut chunk = [0u8; 64];
+        match port.read(&mut chunk) {
+            Ok(n) => decoder.push(&chunk[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+            Err(_) => break,
+        }
+        // Fader traffic racing the reply is lost; the first sync resends state
+        while let Some(packet) = decoder.next_packet() {
+            // A version the host doesn't speak is treated like no reply
+            if let DevicePacket::FramingAccepted { version: FRAMING_VERSION } = packet {
+                return Framing::V2;
+            }
         }
+    }
+    Framing::V1
+}
 
-        // Valid — ACK to stop beaconing
-        port.write_all(&[CMD_HANDSHAKE_ACK])
-            .map_err(|e| format!("ACK failed: {e}"))?;
-        port.flush().ok();
+/// Ask what the device can do. Goes over the negotiated link, so a framed
+/// request is retransmitted like any other; silence means pre-1.2 firmware.
+fn query_capabilities(link: &mut Link) -> Option<Capabilities> {
+    link.send(&HostPacket::CapabilitiesRequest);
 
-        return Ok((port, response.uuid, (response.version_major, response.version_minor)));
+    let deadline = Instant::now() + Duration::from_millis(CAPABILITIES_REPLY_MS);
+    while Instant::now() < deadline {
+        let caps = link.poll().into_iter().find_map(|packet| match packet {
+            DevicePacket::Capabilities(caps) => Some(caps),
+            _ => None,
+        });
+        if caps.is_some() {
+            return caps;
+        }
+        thread::sleep(Duration::from_millis(10));
     }
+    None
 }
 
+/// Spawns the scan on a background thread after `delay_ms`.
+/// Returns a Receiver the app should poll via ScanTick.
 pub fn start_scan_delayed(delay_ms: u64) -> mpsc::Receiver<ScanEvent> {
     let (tx, rx) = mpsc::channel();
     thread::spawn(move || {
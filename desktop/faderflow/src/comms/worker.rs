@@ -0,0 +1,307 @@
+// worker.rs - One thread per device that does all of its serial I/O. The UI
+// queues packets by priority and gets what the device sends back as events,
+// so nothing on the update thread ever waits on the port. Icons for firmware
+// that takes them in rows go out a row at a time between other traffic.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures::channel::mpsc::UnboundedSender;
+
+use crate::comms::codec::{DevicePacket, HostPacket, ICON_BYTES, ICON_ROW_BYTES, ICON_SIZE};
+use crate::comms::scanner::SharedPort;
+
+/// How often the port is read when nothing is queued
+const POLL_MS: u64 = 5;
+/// Quiet time after a name, while the firmware redraws the screen
+const NAME_SETTLE_MS: u64 = 60;
+/// Quiet time after an icon, while the firmware pushes it to the screen
+const ICON_SETTLE_MS: u64 = 50;
+
+/// Reports from a device's worker, tagged with the port they came from
+#[derive(Debug, Clone)]
+pub enum DeviceEvent {
+    /// What the device sent in one read
+    Packets { port_name: String, packets: Vec<DevicePacket> },
+    /// How far a channel's icon upload is, 0.0 to 1.0; None once it's
+    /// finished or cancelled
+    IconProgress { port_name: String, channel: u8, progress: Option<f32> },
+}
+
+/// Where workers deliver device events; the app drains it in a task
+pub type EventSink = UnboundedSender<DeviceEvent>;
+
+/// Queue order, most urgent last. Control traffic (faders, mute, balance,
+/// calibration) never waits behind a name or an icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Priority {
+    /// Icons, whole or a row at a time
+    Bulk,
+    /// Names and meter frames: a redraw each
+    Display,
+    Control,
+}
+
+impl Priority {
+    fn of(packet: &HostPacket) -> Self {
+        match packet {
+            HostPacket::Icon { .. } | HostPacket::IconRle { .. } | HostPacket::IconRow { .. } => Priority::Bulk,
+            HostPacket::AppName { .. } | HostPacket::Meters(_) => Priority::Display,
+            _ => Priority::Control,
+        }
+    }
+}
+
+/// An icon going out row by row
+struct Upload {
+    channel: u8,
+    rgb565: Vec<u8>,
+    next_row: u8,
+}
+
+#[derive(Default)]
+struct Queue {
+    packets: VecDeque<(Priority, HostPacket)>,
+    uploads: VecDeque<Upload>,
+    closed: bool,
+}
+
+impl Queue {
+    /// A packet for a slot that's already queued replaces it in place; the
+    /// device only needs the latest value.
+    fn push(&mut self, packet: HostPacket) {
+        if let Some(slot) = packet.slot() {
+            if let Some(queued) = self.packets.iter_mut().find(|(_, p)| p.slot() == Some(slot)) {
+                queued.1 = packet;
+                return;
+            }
+        }
+        self.packets.push_back((Priority::of(&packet), packet));
+    }
+
+    /// Start sending a channel's icon, dropping the rest of an older one
+    fn start_upload(&mut self, channel: u8, rgb565: Vec<u8>) {
+        self.cancel_upload(channel);
+        self.uploads.push_back(Upload { channel, rgb565, next_row: 0 });
+    }
+
+    /// Whether there was an upload to cancel
+    fn cancel_upload(&mut self, channel: u8) -> bool {
+        let before = self.uploads.len();
+        self.uploads.retain(|u| u.channel != channel);
+        self.uploads.len() != before
+    }
+
+    /// Oldest packet of the highest waiting priority. Upload rows are bulk
+    /// traffic queued ahead of any whole icon.
+    fn pop(&mut self) -> Option<HostPacket> {
+        let top = self.packets.iter().map(|(prio, _)| *prio).max();
+        if top.is_none_or(|prio| prio == Priority::Bulk) {
+            if let Some(row) = self.next_row() {
+                return Some(row);
+            }
+        }
+        let top = top?;
+        let idx = self.packets.iter().position(|(prio, _)| *prio == top)?;
+        self.packets.remove(idx).map(|(_, p)| p)
+    }
+
+    fn next_row(&mut self) -> Option<HostPacket> {
+        let upload = self.uploads.front_mut()?;
+        let row = upload.next_row;
+        let start = row as usize * ICON_ROW_BYTES;
+        let packet = HostPacket::IconRow {
+            channel: upload.channel,
+            row,
+            rgb565: upload.rgb565[start..start + ICON_ROW_BYTES].to_vec(),
+        };
+        upload.next_row += 1;
+        if upload.next_row as usize == ICON_SIZE {
+            self.uploads.pop_front();
+        }
+        Some(packet)
+    }
+}
+
+struct Shared {
+    port_name: String,
+    /// Progress goes out with the queue locked, so it arrives in the order
+    /// the queue changed
+    events: EventSink,
+    queue: Mutex<Queue>,
+    wake: Condvar,
+}
+
+impl Shared {
+    fn progress(&self, channel: u8, progress: Option<f32>) {
+        let _ = self.events.unbounded_send(DeviceEvent::IconProgress {
+            port_name: self.port_name.clone(),
+            channel,
+            progress,
+        });
+    }
+}
+
+/// Handle to a device's I/O thread. Dropping it stops the thread.
+pub struct DeviceIo {
+    shared: Arc<Shared>,
+}
+
+impl DeviceIo {
+    pub fn spawn(port_name: String, port: SharedPort, events: EventSink) -> Self {
+        let shared = Arc::new(Shared {
+            port_name,
+            events,
+            queue: Mutex::default(),
+            wake: Condvar::new(),
+        });
+        let worker = Arc::clone(&shared);
+        thread::spawn(move || run(port, worker));
+        Self { shared }
+    }
+
+    /// Queue a packet for the device; returns immediately.
+    pub fn send(&self, packet: HostPacket) {
+        if let Ok(mut queue) = self.shared.queue.lock() {
+            queue.push(packet);
+        }
+        self.shared.wake.notify_one();
+    }
+
+    /// Send an icon a row at a time (CMD_DISPLAY_UPDATE_ICON_ROW), in place
+    /// of whatever the channel's previous upload hadn't sent yet.
+    pub fn upload_icon(&self, channel: u8, rgb565: Vec<u8>) {
+        if rgb565.len() != ICON_BYTES { return; }
+        if let Ok(mut queue) = self.shared.queue.lock() {
+            queue.start_upload(channel, rgb565);
+            self.shared.progress(channel, Some(0.0));
+        }
+        self.shared.wake.notify_one();
+    }
+
+    /// Stop a channel's upload where it is.
+    pub fn cancel_icon(&self, channel: u8) {
+        if let Ok(mut queue) = self.shared.queue.lock() {
+            if queue.cancel_upload(channel) {
+                self.shared.progress(channel, None);
+            }
+        }
+    }
+}
+
+impl Drop for DeviceIo {
+    fn drop(&mut self) {
+        if let Ok(mut queue) = self.shared.queue.lock() {
+            queue.closed = true;
+        }
+        self.shared.wake.notify_one();
+    }
+}
+
+fn run(port: SharedPort, shared: Arc<Shared>) {
+    let mut quiet_until = Instant::now();
+    loop {
+        let (packets, backlogged) = match port.lock() {
+            Ok(mut link) => (link.poll(), link.backlogged()),
+            Err(_) => return,
+        };
+        if !packets.is_empty() {
+            let port_name = shared.port_name.clone();
+            let _ = shared.events.unbounded_send(DeviceEvent::Packets { port_name, packets });
+        }
+
+        let next = {
+            let Ok(mut queue) = shared.queue.lock() else { return };
+            if queue.closed { return; }
+            // A device behind on acknowledgements gets nothing new; queued
+            // packets keep being superseded meanwhile
+            let ready = !backlogged && Instant::now() >= quiet_until;
+            let next = if ready { queue.pop() } else { None };
+            if let Some(HostPacket::IconRow { channel, row, .. }) = &next {
+                let done = *row as usize + 1;
+                shared.progress(*channel, (done < ICON_SIZE).then(|| done as f32 / ICON_SIZE as f32));
+            }
+            next
+        };
+
+        let Some(packet) = next else {
+            // Sleep until something is queued or it's time to read again
+            let Ok(queue) = shared.queue.lock() else { return };
+            if (queue.packets.is_empty() && queue.uploads.is_empty()) || backlogged || Instant::now() < quiet_until {
+                let _ = shared.wake.wait_timeout(queue, Duration::from_millis(POLL_MS));
+            }
+            continue;
+        };
+
+        let settle = match packet {
+            HostPacket::AppName { .. } => NAME_SETTLE_MS,
+            HostPacket::Icon { .. } | HostPacket::IconRle { .. } => ICON_SETTLE_MS,
+            _ => 0,
+        };
+        if let Ok(mut link) = port.lock() {
+            link.send(&packet);
+        }
+        quiet_until = Instant::now() + Duration::from_millis(settle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_jumps_ahead_of_bulk() {
+        let mut queue = Queue::default();
+        queue.push(HostPacket::Icon { channel: 0, rgb565: vec![] });
+        queue.push(HostPacket::AppName { channel: 0, name: "a".into() });
+        queue.push(HostPacket::Volume { channel: 1, volume: 40 });
+        queue.push(HostPacket::Meters([0; 5]));
+        assert!(matches!(queue.pop(), Some(HostPacket::Volume { .. })));
+        assert!(matches!(queue.pop(), Some(HostPacket::AppName { .. })));
+        assert!(matches!(queue.pop(), Some(HostPacket::Meters(_))));
+        assert!(matches!(queue.pop(), Some(HostPacket::Icon { .. })));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn volume_goes_out_between_icon_rows() {
+        let mut queue = Queue::default();
+        queue.start_upload(3, (0..ICON_BYTES).map(|i| i as u8).collect());
+        let Some(HostPacket::IconRow { channel: 3, row: 0, rgb565 }) = queue.pop() else { panic!() };
+        assert_eq!(rgb565, (0..ICON_ROW_BYTES).map(|i| i as u8).collect::<Vec<_>>());
+        queue.push(HostPacket::Volume { channel: 0, volume: 50 });
+        assert!(matches!(queue.pop(), Some(HostPacket::Volume { .. })));
+        assert!(matches!(queue.pop(), Some(HostPacket::IconRow { row: 1, .. })));
+        for row in 2..ICON_SIZE as u8 {
+            assert!(matches!(queue.pop(), Some(HostPacket::IconRow { row: r, .. }) if r == row));
+        }
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn newer_icon_restarts_the_upload() {
+        let mut queue = Queue::default();
+        queue.start_upload(1, vec![0; ICON_BYTES]);
+        queue.pop();
+        queue.pop();
+        queue.start_upload(1, vec![1; ICON_BYTES]);
+        let Some(HostPacket::IconRow { row: 0, rgb565, .. }) = queue.pop() else { panic!() };
+        assert_eq!(rgb565[0], 1);
+        assert!(queue.cancel_upload(1));
+        assert_eq!(queue.pop(), None);
+        assert!(!queue.cancel_upload(1));
+    }
+
+    #[test]
+    fn newer_value_replaces_queued_one() {
+        let mut queue = Queue::default();
+        queue.push(HostPacket::Volume { channel: 1, volume: 10 });
+        queue.push(HostPacket::Volume { channel: 2, volume: 20 });
+        queue.push(HostPacket::Volume { channel: 1, volume: 30 });
+        assert_eq!(queue.pop(), Some(HostPacket::Volume { channel: 1, volume: 30 }));
+        assert_eq!(queue.pop(), Some(HostPacket::Volume { channel: 2, volume: 20 }));
+        assert_eq!(queue.pop(), None);
+    }
+}
@@ -3,21 +3,7 @@ mod ui;
 mod audio;
 mod utils;
 
-use serialport::{self};
-use std::io::{Read, Write, BufRead};
 use ui::resources::load_icon;
-use std::time::Duration;
-use std::thread;
-use std::sync::{Arc, Mutex};
-use iced::application::TitleFn;
-use iced::Settings;
-use comms::protocol;
-use crate::comms::protocol::{
-    FaderMessage, HandshakeResponse,
-    CMD_FADER_UPDATE, CMD_HANDSHAKE_REQUEST, CMD_HANDSHAKE_ACK, CMD_HANDSHAKE_RESPONSE,
-    DisplayUpdateAppCommand, DisplayUpdateVolumeCommand,
-    CMD_DISPLAY_UPDATE_APP_NAME, CMD_DISPLAY_UPDATE_APP_VOLUME
-};
 
 pub const INIT_WAIT_TIME_MS: u64 = 100;
 
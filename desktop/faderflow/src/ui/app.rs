@@ -6,23 +6,36 @@ use iced::futures;
 use iced::widget::{button, column, container, row, text};
 use iced::{Element, Subscription, Task};
 
-use crate::audio::{create_backend, AudioBackend, AudioSession, AudioUpdate};
+use crate::audio::{
+    create_backend, grouping_rules, session_labels, set_grouping_rules,
+    AudioBackend, Ducker, DuckingSettings, AudioError, AudioLevel, AudioSession, AudioUpdate, DeviceState, Grouping, SessionKey, SessionKind, MASTER_LABEL,
+};
 use crate::comms::scanner::{self, ScanEvent, RESCAN_DELAY_SECS};
-use crate::comms::device_info::{DeviceInfo, DeviceStatus};
+use crate::comms::device_info::{DeviceInfo, DeviceStatus, EncoderMode};
 use crate::utils::config::{
-    load_device_renames, load_device_assignments,
-    save_device_renames, save_device_assignments,
-    send_app_name, send_volume, send_icon,
+    load_device_renames, load_device_assignments, load_device_tapers, load_encoder_modes, load_grouping_rules, load_routes,
+    load_ducking,
+    save_device_renames, save_device_assignments, save_device_tapers, save_encoder_modes, save_grouping_rules, save_routes,
+    save_ducking,
+    send_app_name, send_volume, send_icon, send_icon_rle, send_meters, send_balance, send_encoder_mode, send_mute,
     send_calibration_start, send_calibration_cancel,
 };
+use crate::utils::channel_group::{ChannelGroup, GroupMode};
+use crate::utils::taper::Taper;
 use crate::ui::views;
+use crate::ui::views::devices::SessionChoice;
+use crate::ui::views::settings::AppChoice;
 use crate::ui::views::no_devices::NoDevicesReason;
 use crate::ui::views::scanning::{LogKind, ScanningState};
 
+use crate::comms::capabilities::Capabilities;
+use crate::comms::codec::{position_percent, ButtonEvent, DevicePacket, ICON_BYTES};
+use crate::comms::link::Framing;
+use crate::comms::worker::{DeviceEvent, DeviceIo, EventSink};
 use crate::comms::protocol::{
-    FaderMessage, HandshakeResponse,
-    CMD_FADER_UPDATE, CMD_HANDSHAKE_RESPONSE,
-    CMD_CALIBRATION_STATUS, CMD_CALIBRATION_DEBUG,
+    CMD_DISPLAY_UPDATE_APP_NAME, CMD_DISPLAY_UPDATE_BALANCE,
+    CMD_DISPLAY_UPDATE_ICON_RLE, CMD_DISPLAY_UPDATE_ICON_ROW, CMD_DISPLAY_UPDATE_MUTE,
+    CMD_ENCODER_MODE,
 };
 
 // ── App screens ──────────────────────────────────────────────────────────────
@@ -43,7 +56,7 @@ pub enum AppScreen {
 
 pub struct ReadyState {
     pub devices: Vec<DeviceInfo>,
-    pub sessions: HashMap<String, AudioSession>,
+    pub sessions: HashMap<SessionKey, AudioSession>,
     pub current_view: View,
     pub rename_drafts: Vec<String>,
     pub debug_open: Vec<bool>,
@@ -54,6 +67,14 @@ pub struct ReadyState {
 
 // ── App ──────────────────────────────────────────────────────────────────────
 
+/// Full session re-enumeration interval, a safety net behind backend events
+const SESSION_RESYNC_SECS: u64 = 30;
+/// How often to try the audio system again while it's unreachable
+const AUDIO_RECONNECT_SECS: u64 = 3;
+/// Meter frames to the displays are capped at 10 Hz: 60 B/s of a ~11.5 kB/s
+/// link, and the firmware draws them at its lowest priority
+const METER_FRAME_MS: u64 = 100;
+
 pub struct VolumeApp {
     screen: AppScreen,
     audio_rx: mpsc::Receiver<AudioUpdate>,
@@ -61,6 +82,16 @@ pub struct VolumeApp {
     scan_rx: Option<Arc<Mutex<mpsc::Receiver<ScanEvent>>>>,
     watchdog_tx: mpsc::Sender<ScanEvent>,
     watchdog_rx: Arc<Mutex<mpsc::Receiver<ScanEvent>>>,
+    /// exe → output device for apps routed away from the default output
+    routes: HashMap<String, String>,
+    /// Last backend-level failure, shown as a banner until a retry succeeds
+    audio_error: Option<AudioError>,
+    /// Turns other sessions down while a voice app talks
+    ducker: Ducker,
+    /// Hands device workers their way back to `update`. The receiving end
+    /// is drained by a task started with the app, so nothing sent before
+    /// the runtime gets to it is lost.
+    device_events: EventSink,
 }
 
 // ── Messages ─────────────────────────────────────────────────────────────────
@@ -71,9 +102,14 @@ pub enum Message {
     ShowSettings,
     ShowAbout,
     ShowDevices,
-    VolumeChanged(String, f32),
-    ToggleMute(String),
+    VolumeChanged(SessionKey, f32),
+    BalanceChanged(SessionKey, f32),
+    ToggleMute(SessionKey),
+    SetGrouping(String, Grouping), // exe, rule
+    RouteSession(SessionKey, Option<String>), // None = follow the system default
+    SetDucking(DuckingSettings),
     RefreshSessions,
+    RetryAudio,
     SessionsUpdated(Vec<AudioSession>),
     PollAudioReceiver,
     StartScan,
@@ -84,10 +120,15 @@ pub enum Message {
     DeviceRenameCommit(usize),
     DeviceToggleDebug(usize),
     DeviceDisconnect(usize),
-    DeviceChannelAssign(usize, usize, String), // device_idx, channel, session_name
+    DeviceChannelAssign(usize, usize, Option<SessionKey>), // device_idx, channel, member to add (None = clear)
+    DeviceChannelUnassign(usize, usize, SessionKey),       // device_idx, channel, member
+    DeviceChannelGroupMode(usize, usize, GroupMode),
+    DeviceChannelTrim(usize, usize, SessionKey, f32),      // device_idx, channel, member, trim
+    DeviceChannelTaper(usize, usize, Taper),               // device_idx, channel, curve
+    DeviceEncoderMode(usize, usize, EncoderMode),          // device_idx, channel, mode
     DeviceSync(usize),
     SelectOutput(String),
-    PollSerial,
+    DeviceEvent(DeviceEvent),
     DeviceCalibrate(usize),
     DeviceCalibrateCancel(usize),
 }
@@ -97,11 +138,15 @@ pub enum Message {
 impl VolumeApp {
     pub fn new() -> (Self, Task<Message>) {
         let (audio_tx, audio_rx) = mpsc::channel();
+        set_grouping_rules(load_grouping_rules());
         let mut backend = create_backend();
-        let _ = backend.initialize();
-        let _ = backend.start_listening(audio_tx);
+        let audio_error = Self::start_backend(backend.as_mut(), audio_tx).err();
+        if let Some(e) = &audio_error {
+            eprintln!("Audio backend failed to start: {e}");
+        }
 
         let (watchdog_tx, watchdog_rx) = mpsc::channel();
+        let (device_events, device_events_rx) = futures::channel::mpsc::unbounded();
 
         let app = Self {
             screen: AppScreen::Scanning(ScanningState::default()),
@@ -110,9 +155,17 @@ impl VolumeApp {
             scan_rx: None,
             watchdog_tx,
             watchdog_rx: Arc::new(Mutex::new(watchdog_rx)),
+            routes: load_routes(),
+            audio_error,
+            ducker: Ducker::new(load_ducking()),
+            device_events,
         };
 
-        (app, Task::done(Message::StartScan))
+        let tasks = Task::batch([
+            Task::run(device_events_rx, Message::DeviceEvent),
+            Task::done(Message::StartScan),
+        ]);
+        (app, tasks)
     }
 }
 
@@ -145,8 +198,71 @@ impl VolumeApp {
                     if let Some(session) = s.sessions.get_mut(&id) {
                         session.volume = volume;
                         session.last_local_change = Some(Instant::now());
-                        let _ = self.backend.set_volume(&id, volume);
-                        Self::push_volume_to_devices(&mut s.devices, &id, volume);  // ← ADD
+                        let _ = Self::set_target_volume(self.backend.as_mut(), &id, self.ducker.backend_volume(&id, volume));
+                        Self::push_volume_to_devices(&mut s.devices, &id, self.ducker.fader_volume(&id, volume), session.is_muted);
+                    }
+                }
+                Task::none()
+            }
+            Message::BalanceChanged(id, balance) => {
+                if let AppScreen::Ready(s) = &mut self.screen {
+                    if let Some(session) = s.sessions.get_mut(&id) {
+                        session.balance = balance;
+                        session.last_local_change = Some(Instant::now());
+                        if let Err(e) = self.backend.set_balance(&id, balance) {
+                            eprintln!("Failed to set balance of {id}: {e}");
+                        }
+                        Self::push_balance_to_devices(&mut s.devices, &id, balance);
+                    }
+                }
+                Task::none()
+            }
+            Message::SetGrouping(exe, grouping) => {
+                let mut rules = grouping_rules();
+                if grouping == Grouping::default() {
+                    rules.remove(&exe);
+                } else {
+                    rules.insert(exe, grouping);
+                }
+                save_grouping_rules(&rules);
+                set_grouping_rules(rules);
+                // Backends re-key on the next enumeration; assignments follow
+                // the app via rebind_assignments
+                Task::done(Message::RefreshSessions)
+            }
+            Message::RouteSession(id, device) => {
+                let target = match device {
+                    Some(device) => {
+                        self.routes.insert(id.exe.clone(), device.clone());
+                        Some(device)
+                    }
+                    None => {
+                        self.routes.remove(&id.exe);
+                        match &self.screen {
+                            AppScreen::Ready(s) => s.current_output.clone(),
+                            _ => None,
+                        }
+                    }
+                };
+                save_routes(&self.routes);
+                // The route is per app, so every instance of it follows
+                if let (Some(device), AppScreen::Ready(s)) = (target, &self.screen) {
+                    for key in s.sessions.keys().filter(|k| k.kind == SessionKind::Playback && k.exe == id.exe) {
+                        if let Err(e) = self.backend.move_session_to_device(key, &device) {
+                            eprintln!("Failed to route {key} to {device}: {e}");
+                        }
+                    }
+                }
+                Task::none()
+            }
+            Message::SetDucking(settings) => {
+                let faders_changed = settings.move_faders != self.ducker.settings.move_faders;
+                self.ducker.settings = settings;
+                save_ducking(&self.ducker.settings);
+                // Faders switch between the set and the ducked volume
+                if let (true, AppScreen::Ready(s)) = (faders_changed, &mut self.screen) {
+                    for (id, session) in &s.sessions {
+                        Self::push_volume_to_devices(&mut s.devices, id, self.ducker.fader_volume(id, session.volume), session.is_muted);
                     }
                 }
                 Task::none()
@@ -156,15 +272,47 @@ impl VolumeApp {
                     if let Some(session) = s.sessions.get_mut(&id) {
                         session.is_muted = !session.is_muted;
                         session.last_local_change = Some(Instant::now());
-                        let _ = self.backend.set_mute(&id, session.is_muted);
+                        let _ = Self::set_target_mute(self.backend.as_mut(), &id, session.is_muted);
+                        Self::push_volume_to_devices(&mut s.devices, &id, self.ducker.fader_volume(&id, session.volume), session.is_muted);
                     }
                 }
                 Task::none()
             }
-            Message::RefreshSessions => match self.backend.get_sessions() {
-                Ok(sessions) => Task::done(Message::SessionsUpdated(sessions)),
-                Err(_) => Task::done(Message::SessionsUpdated(Vec::new())),
-            },
+            Message::RefreshSessions => {
+                let mut sessions = match self.backend.get_sessions() {
+                    Ok(sessions) => sessions,
+                    Err(e) => {
+                        eprintln!("Failed to list audio sessions: {e}");
+                        if e.is_backend_failure() { self.audio_error = Some(e); }
+                        return Task::none();
+                    }
+                };
+                sessions.extend(self.backend.get_recording_streams().unwrap_or_default());
+                sessions.extend(self.backend.get_input_devices().unwrap_or_default());
+                // The default output's master volume rides along as a session
+                if let Ok((volume, muted)) = self.backend.get_endpoint_volume() {
+                    sessions.push(AudioSession::new(SessionKey::master(), MASTER_LABEL.into(), volume, muted, 0));
+                }
+                Task::done(Message::SessionsUpdated(sessions))
+            }
+            Message::RetryAudio => {
+                let _ = self.backend.stop_metering();
+                let _ = self.backend.stop_listening();
+                // The old listener's sender is gone with it; start a fresh channel
+                let (audio_tx, audio_rx) = mpsc::channel();
+                self.audio_rx = audio_rx;
+                if let Err(e) = Self::start_backend(self.backend.as_mut(), audio_tx) {
+                    eprintln!("Audio backend failed to start: {e}");
+                    self.audio_error = Some(e);
+                    return Task::none();
+                }
+                self.audio_error = None;
+                if let AppScreen::Ready(s) = &mut self.screen {
+                    s.output_devices = self.backend.get_output_devices().unwrap_or_default();
+                    s.current_output = self.backend.get_default_output_device();
+                }
+                Task::done(Message::RefreshSessions)
+            }
             Message::SessionsUpdated(sessions) => {
                 if let AppScreen::Ready(s) = &mut self.screen {
                     for session in &sessions {
@@ -177,18 +325,27 @@ impl VolumeApp {
                                 .last_external_change
                                 .map(|t| t.elapsed() > Duration::from_millis(500))
                                 .unwrap_or(true);
-                            if should_update {
-                                existing.volume = session.volume;
-                                existing.is_muted = session.is_muted;
+                            if should_update && !self.ducker.settling(&session.id) {
+                                existing.volume = self.ducker.user_volume(&session.id, session.volume);
+                                existing.balance = session.balance;
+                                if existing.is_muted != session.is_muted {
+                                    existing.is_muted = session.is_muted;
+                                    Self::push_volume_to_devices(
+                                        &mut s.devices, &session.id,
+                                        self.ducker.fader_volume(&session.id, existing.volume), existing.is_muted,
+                                    );
+                                }
                             }
                             if existing.icon_handle.is_none() && session.icon_handle.is_some() {
                                 existing.icon_handle = session.icon_handle.clone();
                             }
                         } else {
+                            Self::apply_route(self.backend.as_mut(), &self.routes, &session.id);
                             s.sessions.insert(session.id.clone(), session.clone());
                         }
                     }
                     s.sessions.retain(|id, _| sessions.iter().any(|s| &s.id == id));
+                    let rebound = Self::rebind_assignments(&mut s.devices, &s.sessions);
 
                     // First session load after connect: push full state to devices
                     if s.needs_initial_sync && !s.devices.is_empty() {
@@ -198,13 +355,22 @@ impl VolumeApp {
                             (0..n).map(|i| Task::done(Message::DeviceSync(i))),
                         );
                     }
+                    if !rebound.is_empty() {
+                        return Task::batch(
+                            rebound.into_iter().map(|i| Task::done(Message::DeviceSync(i))),
+                        );
+                    }
                 }
                 Task::none()
             }
             Message::PollAudioReceiver => {
                 if let AppScreen::Ready(s) = &mut self.screen {
-                    let mut last_updates: HashMap<String, (Option<f32>, Option<bool>)> =
+                    let mut last_updates: HashMap<SessionKey, (Option<f32>, Option<bool>)> =
                         HashMap::new();
+                    let mut last_balances: HashMap<SessionKey, f32> = HashMap::new();
+                    let mut added = false;
+                    let mut returned_outputs = Vec::new();
+                    let mut levels_changed = false;
                     while let Ok(update) = self.audio_rx.try_recv() {
                         match update {
                             AudioUpdate::VolumeChanged(ref id, v) => {
@@ -213,15 +379,66 @@ impl VolumeApp {
                             AudioUpdate::MuteChanged(ref id, m) => {
                                 last_updates.entry(id.clone()).or_default().1 = Some(m);
                             }
+                            AudioUpdate::BalanceChanged(id, b) => {
+                                last_balances.insert(id, b);
+                            }
+                            AudioUpdate::EndpointVolumeChanged(v) => {
+                                last_updates.entry(SessionKey::master()).or_default().0 = Some(v);
+                            }
+                            AudioUpdate::EndpointMuteChanged(m) => {
+                                last_updates.entry(SessionKey::master()).or_default().1 = Some(m);
+                            }
                             AudioUpdate::DefaultDeviceChanged(name) => {
                                 s.current_output = Some(name);
                                 return Task::done(Message::RefreshSessions);
                             }
-                            AudioUpdate::SessionAdded(_) | AudioUpdate::SessionRemoved(_) => {
-                                return Task::done(Message::RefreshSessions);
+                            AudioUpdate::SessionAdded(session) => {
+                                Self::apply_route(self.backend.as_mut(), &self.routes, &session.id);
+                                s.sessions.entry(session.id.clone()).or_insert(session);
+                                added = true;
+                            }
+                            AudioUpdate::SessionRemoved(id) => {
+                                self.ducker.forget(&id);
+                                s.sessions.remove(&id);
+                            }
+                            AudioUpdate::OutputDeviceAdded(name)
+                            | AudioUpdate::OutputDeviceStateChanged(name, DeviceState::Active) => {
+                                if !s.output_devices.contains(&name) {
+                                    s.output_devices.push(name.clone());
+                                    returned_outputs.push(name);
+                                }
+                            }
+                            AudioUpdate::OutputDeviceRemoved(name)
+                            | AudioUpdate::OutputDeviceStateChanged(name, DeviceState::Unplugged) => {
+                                s.output_devices.retain(|d| *d != name);
+                            }
+                            AudioUpdate::SessionLevels(levels) => {
+                                levels_changed = true;
+                                for (id, level) in levels {
+                                    if let Some(session) = s.sessions.get_mut(&id) { session.level = level; }
+                                }
+                            }
+                            AudioUpdate::EndpointLevel(level) => {
+                                levels_changed = true;
+                                if let Some(session) = s.sessions.get_mut(&SessionKey::master()) {
+                                    session.level = level;
+                                }
+                            }
+                            AudioUpdate::BackendLost(e) => {
+                                eprintln!("Audio backend lost: {e}");
+                                self.audio_error = Some(e);
                             }
                         }
                     }
+                    if levels_changed {
+                        Self::push_meters_to_devices(&mut s.devices, &s.sessions);
+                    }
+                    // Apps routed to a device that just came back return to it
+                    for key in s.sessions.keys() {
+                        if self.routes.get(&key.exe).is_some_and(|d| returned_outputs.contains(d)) {
+                            Self::apply_route(self.backend.as_mut(), &self.routes, key);
+                        }
+                    }
                     for (id, (vol, mute)) in last_updates {
                         if let Some(session) = s.sessions.get_mut(&id) {
                             let ignore = session
@@ -229,15 +446,56 @@ impl VolumeApp {
                                 .map(|t| t.elapsed() < Duration::from_millis(50))
                                 .unwrap_or(false);
                             if !ignore {
-                                if let Some(v) = vol  {
-                                    session.volume = v;
-                                    Self::push_volume_to_devices(&mut s.devices, &id, v);
+                                // Mid-ramp reports are our own duck steps
+                                let vol = vol.filter(|_| !self.ducker.settling(&id));
+                                if let Some(v) = vol {
+                                    session.volume = self.ducker.user_volume(&id, v);
                                 }
                                 if let Some(m) = mute { session.is_muted = m; }
+                                if vol.is_some() || mute.is_some() {
+                                    Self::push_volume_to_devices(&mut s.devices, &id, self.ducker.fader_volume(&id, session.volume), session.is_muted);
+                                }
                                 session.last_external_change = Some(Instant::now());
                             }
                         }
                     }
+                    for (id, balance) in last_balances {
+                        if let Some(session) = s.sessions.get_mut(&id) {
+                            // Our own writes echo back; the knob is still turning
+                            if session.last_local_change.is_some_and(|t| t.elapsed() < Duration::from_millis(300)) {
+                                continue;
+                            }
+                            session.balance = balance;
+                            Self::push_balance_to_devices(&mut s.devices, &id, balance);
+                        }
+                    }
+                    self.ducker.tick(&s.sessions);
+                    let stale: Vec<SessionKey> = self.ducker.stale(&s.sessions).cloned().collect();
+                    for id in stale {
+                        let volume = s.sessions[&id].volume;
+                        let _ = Self::set_target_volume(self.backend.as_mut(), &id, self.ducker.backend_volume(&id, volume));
+                        if self.ducker.settings.move_faders {
+                            let muted = s.sessions[&id].is_muted;
+                            Self::push_volume_to_devices(&mut s.devices, &id, self.ducker.fader_volume(&id, volume), muted);
+                        }
+                    }
+                    if added && !s.needs_initial_sync {
+                        let rebound = Self::rebind_assignments(&mut s.devices, &s.sessions);
+                        if !rebound.is_empty() {
+                            return Task::batch(
+                                rebound.into_iter().map(|i| Task::done(Message::DeviceSync(i))),
+                            );
+                        }
+                    }
+                } else {
+                    // Meters stream constantly; entering Ready re-reads
+                    // everything, so there's nothing to keep meanwhile
+                    while let Ok(update) = self.audio_rx.try_recv() {
+                        if let AudioUpdate::BackendLost(e) = update {
+                            eprintln!("Audio backend lost: {e}");
+                            self.audio_error = Some(e);
+                        }
+                    }
                 }
                 Task::none()
             }
@@ -248,7 +506,7 @@ impl VolumeApp {
                 if let AppScreen::Ready(state) = &mut self.screen {
                     for dev in state.devices.drain(..) {
                         dev.cancel_watchdog();
-                        drop(dev.port);
+                        drop(dev.io);
                     }
                 }
                 self.screen = AppScreen::Scanning(ScanningState::default());
@@ -267,9 +525,14 @@ impl VolumeApp {
                         std::iter::from_fn(|| rx.try_recv().ok()).collect()
                     })
                     .unwrap_or_default();
+                let was_ready = matches!(self.screen, AppScreen::Ready(_));
                 for ev in events {
                     self.handle_scan_event(ev);
                 }
+                // Sessions load right away; after that, updates arrive as events
+                if !was_ready && matches!(self.screen, AppScreen::Ready(_)) {
+                    return Task::done(Message::RefreshSessions);
+                }
                 Task::none()
             }
             Message::WatchdogTick => {
@@ -335,57 +598,157 @@ impl VolumeApp {
                 Task::none()
             }
             Message::SelectOutput(name) => {
-                if let AppScreen::Ready(s) = &mut self.screen {
-                    s.current_output = Some(name);
+                // current_output only moves once the OS confirms the switch
+                // with DefaultDeviceChanged
+                if let Err(e) = self.backend.set_default_output_device(&name) {
+                    eprintln!("Failed to switch output to {name}: {e}");
                 }
-                Task::done(Message::RefreshSessions)
+                Task::none()
             }
             Message::DeviceChannelAssign(dev_idx, ch, session) => {
                 if let AppScreen::Ready(state) = &mut self.screen {
                     if let Some(dev) = state.devices.get_mut(dev_idx) {
-                        if ch < 5 {
-                            dev.channel_assignments[ch] = session;
+                        if ch < dev.channel_count() {
+                            match session {
+                                Some(key) => dev.channel_assignments[ch].add(key),
+                                None => dev.channel_assignments[ch] = ChannelGroup::default(),
+                            }
+                            save_device_assignments(&state.devices);
+                        }
+                    }
+                }
+                Task::none()
+            }
+            Message::DeviceChannelUnassign(dev_idx, ch, key) => {
+                if let AppScreen::Ready(state) = &mut self.screen {
+                    if let Some(dev) = state.devices.get_mut(dev_idx) {
+                        if ch < dev.channel_count() {
+                            dev.channel_assignments[ch].remove(&key);
                             save_device_assignments(&state.devices);
                         }
                     }
                 }
                 Task::none()
             }
+            Message::DeviceChannelGroupMode(dev_idx, ch, mode) => {
+                if let AppScreen::Ready(state) = &mut self.screen {
+                    let Some(dev) = state.devices.get_mut(dev_idx) else { return Task::none() };
+                    if ch >= dev.channel_count() { return Task::none(); }
+                    let level = Self::group_level(&dev.channel_assignments[ch], &state.sessions);
+                    dev.channel_assignments[ch].set_mode(mode);
+                    // Neutral trims: every member jumps to the fader's level
+                    if let Some(level) = level {
+                        Self::apply_group_level(self.backend.as_mut(), &mut self.ducker, &dev.channel_assignments[ch], &mut state.sessions, level);
+                    }
+                    save_device_assignments(&state.devices);
+                }
+                Task::none()
+            }
+            Message::DeviceChannelTrim(dev_idx, ch, key, trim) => {
+                if let AppScreen::Ready(state) = &mut self.screen {
+                    let Some(dev) = state.devices.get_mut(dev_idx) else { return Task::none() };
+                    if ch >= dev.channel_count() { return Task::none(); }
+                    // The fader stays put; the member moves to its new trim
+                    let level = Self::group_level(&dev.channel_assignments[ch], &state.sessions);
+                    dev.channel_assignments[ch].set_trim(&key, trim);
+                    if let Some(level) = level {
+                        Self::apply_group_level(self.backend.as_mut(), &mut self.ducker, &dev.channel_assignments[ch], &mut state.sessions, level);
+                    }
+                    save_device_assignments(&state.devices);
+                }
+                Task::none()
+            }
 
+            Message::DeviceChannelTaper(dev_idx, ch, taper) => {
+                if let AppScreen::Ready(state) = &mut self.screen {
+                    let Some(dev) = state.devices.get_mut(dev_idx) else { return Task::none() };
+                    if ch >= dev.channel_count() { return Task::none(); }
+                    dev.channel_tapers[ch] = taper;
+                    // Same volume, new curve: the motor has to move
+                    let volume = Self::group_level(&dev.channel_assignments[ch], &state.sessions);
+                    if let (Some(volume), None) = (volume, dev.cal_state) {
+                        let pct = Self::fader_percent(&dev.channel_tapers[ch], volume);
+                        send_volume(&dev.io, ch as u8, pct);
+                        dev.channel_volumes[ch] = pct;
+                    }
+                    save_device_tapers(&state.devices);
+                }
+                Task::none()
+            }
+            Message::DeviceEncoderMode(dev_idx, ch, mode) => {
+                if let AppScreen::Ready(state) = &mut self.screen {
+                    let Some(dev) = state.devices.get_mut(dev_idx) else { return Task::none() };
+                    if ch >= dev.channel_count() || !dev.supports(CMD_ENCODER_MODE) { return Task::none(); }
+                    dev.encoder_modes[ch] = mode;
+                    let balance = dev.channel_assignments[ch].lead()
+                        .and_then(|k| state.sessions.get(k))
+                        .map_or(0, |s| Self::balance_wire(s.balance));
+                    if dev.cal_state.is_none() {
+                        send_encoder_mode(&dev.io, ch as u8, mode);
+                        if dev.supports(CMD_DISPLAY_UPDATE_BALANCE) {
+                            send_balance(&dev.io, ch as u8, balance);
+                            dev.channel_balances[ch] = balance;
+                        }
+                    }
+                    save_encoder_modes(&state.devices);
+                }
+                Task::none()
+            }
             Message::DeviceSync(dev_idx) => {
                 if let AppScreen::Ready(state) = &mut self.screen {
+                    let labels = session_labels(state.sessions.values());
                     if let Some(dev) = state.devices.get_mut(dev_idx) {
                         if dev.cal_state.is_some() { return Task::none(); }
-                        let port = Arc::clone(&dev.port);
-                        for (ch, session_name) in dev.channel_assignments.iter().enumerate() {
-                            if let Ok(mut p) = port.lock() {
-                                send_app_name(&mut **p, ch as u8, session_name);
-                                std::thread::sleep(Duration::from_millis(60)); // let the redraw finish
-
-                                if !session_name.is_empty() {
-                                    if let Some(sess) = state.sessions.get(session_name) {
-                                        if let Some(exe) = &sess.exe_path {
-                                            if let Some((w, h, rgba)) =
-                                                crate::utils::icon::extract_icon_rgba(exe)
-                                            {
-                                                let data = crate::utils::icon::rgba_to_rgb565_icon(w, h, &rgba);
-                                                send_icon(&mut **p, ch as u8, &data);
-                                                std::thread::sleep(Duration::from_millis(50));
-                                            }
-                                        }
+                        // Only what the device says it understands; old firmware gets the 1.0 set
+                        let caps = dev.caps();
+                        let channels = caps.channel_count();
+                        for (ch, assigned) in dev.channel_assignments.iter().enumerate().take(channels) {
+                            if caps.has_display() && caps.supports(CMD_DISPLAY_UPDATE_APP_NAME) {
+                                let name = Self::group_label(assigned, &labels);
+                                send_app_name(&dev.io, ch as u8, &name);
+                            }
+
+                            // A group shows its lead's icon
+                            if caps.shows_icons() {
+                                let icon = assigned.lead()
+                                    .and_then(|key| state.sessions.get(key))
+                                    .and_then(|sess| sess.exe_path.as_ref())
+                                    .and_then(|exe| crate::utils::icon::cached_icon(exe));
+                                match icon {
+                                    Some(icon) => {
+                                        let data = crate::utils::icon::rgba_to_rgb565_icon(
+                                            icon.width, icon.height, &icon.rgba,
+                                        );
+                                        Self::push_icon(&dev.io, &caps, ch as u8, data);
                                     }
+                                    // Don't finish drawing the previous app's icon
+                                    None => dev.io.cancel_icon(ch as u8),
                                 }
+                            }
 
-                                let vol = if session_name.is_empty() {
-                                    0
-                                } else {
-                                    state.sessions.get(session_name)
-                                        .map(|s| (s.volume * 100.0) as u8)
-                                        .unwrap_or(0)
-                                };
-                                send_volume(&mut **p, ch as u8, vol);
-                                std::thread::sleep(Duration::from_millis(60));
-                                dev.channel_volumes[ch] = vol;
+                            let vol = Self::group_level(assigned, &state.sessions)
+                                .map(|level| Self::fader_percent(&dev.channel_tapers[ch], level))
+                                .unwrap_or(0);
+                            send_volume(&dev.io, ch as u8, vol);
+                            dev.channel_volumes[ch] = vol;
+
+                            let muted = assigned.lead()
+                                .and_then(|k| state.sessions.get(k))
+                                .is_some_and(|s| s.is_muted);
+                            if caps.supports(CMD_DISPLAY_UPDATE_MUTE) {
+                                send_mute(&dev.io, ch as u8, muted);
+                                dev.channel_mutes[ch] = muted;
+                            }
+
+                            let balance = assigned.lead()
+                                .and_then(|k| state.sessions.get(k))
+                                .map_or(0, |s| Self::balance_wire(s.balance));
+                            if caps.supports(CMD_ENCODER_MODE) {
+                                send_encoder_mode(&dev.io, ch as u8, dev.encoder_modes[ch]);
+                            }
+                            if caps.supports(CMD_DISPLAY_UPDATE_BALANCE) {
+                                send_balance(&dev.io, ch as u8, balance);
+                                dev.channel_balances[ch] = balance;
                             }
                         }
                     }
@@ -397,7 +760,7 @@ impl VolumeApp {
                     if idx < state.devices.len() {
                         let dev = state.devices.remove(idx);
                         dev.cancel_watchdog();
-                        drop(dev.port);
+                        drop(dev.io);
                         state.rename_drafts.remove(idx);
                         state.debug_open.remove(idx);
                     }
@@ -410,10 +773,8 @@ impl VolumeApp {
             Message::DeviceCalibrate(idx) => {
                 if let AppScreen::Ready(state) = &mut self.screen {
                     if let Some(dev) = state.devices.get_mut(idx) {
-                        if let Ok(mut p) = dev.port.lock() {
-                            send_calibration_start(&mut **p);
-                            dev.cal_state = Some((0, 0));
-                        }
+                        send_calibration_start(&dev.io);
+                        dev.cal_state = Some((0, 0));
                     }
                 }
                 Task::none()
@@ -421,9 +782,7 @@ impl VolumeApp {
             Message::DeviceCalibrateCancel(idx) => {
                 if let AppScreen::Ready(state) = &mut self.screen {
                     if let Some(dev) = state.devices.get_mut(idx) {
-                        if let Ok(mut p) = dev.port.lock() {
-                            send_calibration_cancel(&mut **p);
-                        }
+                        send_calibration_cancel(&dev.io);
                         // Device confirms with status 3 (and we resync then);
                         // clear now so the UI unlocks even if that gets lost
                         dev.cal_state = None;
@@ -431,96 +790,151 @@ impl VolumeApp {
                 }
                 Task::none()
             }
-            Message::PollSerial => {
-                let mut volume_writes: Vec<(String, f32)> = vec![];
+            Message::DeviceEvent(DeviceEvent::IconProgress { port_name, channel, progress }) => {
+                if let AppScreen::Ready(state) = &mut self.screen {
+                    let dev = state.devices.iter_mut().find(|d| d.port_name == port_name);
+                    if let Some(slot) = dev.and_then(|d| d.icon_progress.get_mut(channel as usize)) {
+                        *slot = progress;
+                    }
+                }
+                Task::none()
+            }
+            Message::DeviceEvent(DeviceEvent::Packets { port_name, packets }) => {
+                let mut volume_writes: Vec<(SessionKey, f32)> = vec![];
+                let mut balance_writes: Vec<(SessionKey, f32)> = vec![];
+                let mut button_events: Vec<(usize, usize, ButtonEvent)> = vec![];
                 let mut resync: Vec<usize> = vec![];
 
                 if let AppScreen::Ready(state) = &mut self.screen {
-                    for (di, dev) in state.devices.iter_mut().enumerate() {
-                        let chunk = {
-                            let Ok(mut p) = dev.port.lock() else { continue };
-                            let avail = p.bytes_to_read().unwrap_or(0) as usize;
-                            if avail == 0 { continue; }
-                            let mut buf = vec![0u8; avail];
-                            match p.read(&mut buf) {
-                                Ok(n) => { buf.truncate(n); buf }
-                                Err(_) => continue,
-                            }
-                        };
-                        dev.rx_buf.extend_from_slice(&chunk);
-
-                        loop {
-                            let Some(&cmd) = dev.rx_buf.first() else { break };
-                            let len = match cmd {
-                                CMD_FADER_UPDATE => std::mem::size_of::<FaderMessage>(),
-                                CMD_CALIBRATION_STATUS => 3,
-                                CMD_CALIBRATION_DEBUG => 7,
-                                // stray beacon between REQUEST and ACK — skip whole
-                                CMD_HANDSHAKE_RESPONSE => std::mem::size_of::<HandshakeResponse>(),
-                                _ => { dev.rx_buf.remove(0); continue } // resync
-                            };
-                            if dev.rx_buf.len() < len { break; }
-
-                            if cmd == CMD_FADER_UPDATE {
-                                let msg: FaderMessage = unsafe {
-                                    std::ptr::read(dev.rx_buf.as_ptr() as *const _)
-                                };
-                                let ch = msg.channel as usize;
-                                if ch < 5 {
-                                    dev.channel_volumes[ch] = msg.position_percent();
+                    // A worker can outlive its device by one read
+                    if let Some((di, dev)) = state.devices.iter_mut()
+                        .enumerate()
+                        .find(|(_, d)| d.port_name == port_name)
+                    {
+                        for packet in packets {
+                            match packet {
+                                DevicePacket::Fader { channel, position } => {
+                                    let ch = channel as usize;
+                                    if ch >= dev.channel_count() { continue; }
+                                    dev.channel_volumes[ch] = position_percent(position);
                                     dev.last_fader_rx[ch] = Some(Instant::now());
-                                    let session = &dev.channel_assignments[ch];
-                                    if !session.is_empty() {
-                                        volume_writes.push((
-                                            session.clone(),
-                                            msg.position_percent() as f32 / 100.0,
-                                        ));
+                                    let level = dev.channel_tapers[ch].volume_at(position as f32 / 255.0);
+                                    volume_writes.extend(dev.channel_assignments[ch].volumes_at(level)
+                                        .map(|(session, v)| (session.clone(), v)));
+                                }
+                                DevicePacket::Balance { channel, balance } => {
+                                    let ch = channel as usize;
+                                    if ch >= dev.channel_count() { continue; }
+                                    dev.channel_balances[ch] = balance;
+                                    balance_writes.extend(dev.channel_assignments[ch].keys()
+                                        .map(|session| (session.clone(), balance as f32 / 100.0)));
+                                }
+                                DevicePacket::Button { channel, event } => {
+                                    let ch = channel as usize;
+                                    if ch < dev.channel_count() && !dev.channel_assignments[ch].is_empty() {
+                                        button_events.push((di, ch, event));
                                     }
                                 }
-                            }
-                            else if cmd == CMD_CALIBRATION_STATUS {
-                                let ch = dev.rx_buf[1];
-                                let phase = dev.rx_buf[2];
-                                if phase >= 2 {
-                                    // done (2) or cancelled (3): restore screens
-                                    dev.cal_state = None;
-                                    resync.push(di);
-                                } else {
-                                    dev.cal_state = Some((ch, phase));
+                                DevicePacket::CalibrationStatus { channel, phase } => {
+                                    if phase >= 2 {
+                                        // done (2) or cancelled (3): restore screens
+                                        dev.cal_state = None;
+                                        resync.push(di);
+                                    } else {
+                                        dev.cal_state = Some((channel, phase));
+                                    }
                                 }
+                                DevicePacket::CalibrationDebug { channel: ch, kind, v1, v2 } => {
+                                    let line = match kind {
+                                        0 => format!("ch{ch} bottom raw={v1}"),
+                                        1 => format!("ch{ch} ACCEPT  min={v1} max={v2} travel={}", v2.saturating_sub(v1)),
+                                        2 => format!("ch{ch} REJECT  travel={v1} (<=200, kept old)"),
+                                        _ => format!("ch{ch} ? kind={kind} {v1} {v2}"),
+                                    };
+                                    dev.cal_debug.push(line);
+                                    // keep one full 5-channel pass visible
+                                    let overflow = dev.cal_debug.len().saturating_sub(12);
+                                    if overflow > 0 { dev.cal_debug.drain(..overflow); }
+                                }
+                                // stray beacon between REQUEST and ACK; the link
+                                // settles framing replies itself and the scan
+                                // already read the capabilities
+                                DevicePacket::Handshake { .. }
+                                | DevicePacket::FramingAccepted { .. }
+                                | DevicePacket::FrameAck { .. }
+                                | DevicePacket::FrameNak { .. }
+                                | DevicePacket::Capabilities(_) => {}
                             }
-                            else if cmd == CMD_CALIBRATION_DEBUG {
-                                let ch = dev.rx_buf[1];
-                                let kind = dev.rx_buf[2];
-                                let v1 = u16::from_le_bytes([dev.rx_buf[3], dev.rx_buf[4]]);
-                                let v2 = u16::from_le_bytes([dev.rx_buf[5], dev.rx_buf[6]]);
-                                let line = match kind {
-                                    0 => format!("ch{ch} bottom raw={v1}"),
-                                    1 => format!("ch{ch} ACCEPT  min={v1} max={v2} travel={}", v2.saturating_sub(v1)),
-                                    2 => format!("ch{ch} REJECT  travel={v1} (<=200, kept old)"),
-                                    _ => format!("ch{ch} ? kind={kind} {v1} {v2}"),
-                                };
-                                dev.cal_debug.push(line);
-                                // keep one full 5-channel pass visible
-                                let overflow = dev.cal_debug.len().saturating_sub(12);
-                                if overflow > 0 { dev.cal_debug.drain(..overflow); }
-                            }
-                            dev.rx_buf.drain(..len);
                         }
                     }
 
-                    if !resync.is_empty() {
-                        return Task::batch(
-                            resync.into_iter().map(|i| Task::done(Message::DeviceSync(i))),
-                        );
-                    }
-
                     for (session, vol) in volume_writes {
-                        let _ = self.backend.set_volume(&session, vol);
+                        // A fader showing the duck is moved in ducked terms
+                        let vol = self.ducker.volume_for_fader(&session, vol);
+                        let _ = Self::set_target_volume(self.backend.as_mut(), &session, self.ducker.backend_volume(&session, vol));
                         if let Some(s) = state.sessions.get_mut(&session) {
                             s.volume = vol;
                         }
                     }
+                    for (session, balance) in balance_writes {
+                        if session.is_master() { continue; }
+                        if let Err(e) = self.backend.set_balance(&session, balance) {
+                            eprintln!("Failed to set balance of {session}: {e}");
+                        }
+                        if let Some(s) = state.sessions.get_mut(&session) {
+                            s.balance = balance;
+                            s.last_local_change = Some(Instant::now());
+                        }
+                    }
+
+                    // Calibration finished: restore the screens once the rest
+                    // of the batch is applied
+                    let mut tasks: Vec<_> = resync.into_iter()
+                        .map(|i| Task::done(Message::DeviceSync(i)))
+                        .collect();
+                    for (di, ch, event) in button_events {
+                        let group = state.devices[di].channel_assignments[ch].clone();
+                        let Some(lead) = group.lead() else { continue };
+                        match event {
+                            // Mute toggle; a group follows its lead so it never ends up split
+                            ButtonEvent::Press => {
+                                let Some((volume, muted)) = state.sessions.get(lead).map(|s| (s.volume, !s.is_muted)) else { continue };
+                                for key in group.keys() {
+                                    let Some(s) = state.sessions.get_mut(key) else { continue };
+                                    s.is_muted = muted;
+                                    s.last_local_change = Some(Instant::now());
+                                    if let Err(e) = Self::set_target_mute(self.backend.as_mut(), key, muted) {
+                                        eprintln!("Failed to mute {key}: {e}");
+                                    }
+                                }
+                                Self::push_volume_to_devices(&mut state.devices, lead, self.ducker.fader_volume(lead, volume), muted);
+                            }
+                            // Swap what the knob turns
+                            ButtonEvent::LongPress => {
+                                let mode = match state.devices[di].encoder_modes[ch] {
+                                    EncoderMode::Volume => EncoderMode::Balance,
+                                    EncoderMode::Balance => EncoderMode::Volume,
+                                };
+                                tasks.push(Task::done(Message::DeviceEncoderMode(di, ch, mode)));
+                            }
+                            // Re-centre the balance while the knob turns it
+                            ButtonEvent::DoublePress => {
+                                if state.devices[di].encoder_modes[ch] != EncoderMode::Balance { continue; }
+                                for key in group.keys() {
+                                    if key.is_master() { continue; }
+                                    let Some(s) = state.sessions.get_mut(key) else { continue };
+                                    if let Err(e) = self.backend.set_balance(key, 0.0) {
+                                        eprintln!("Failed to set balance of {key}: {e}");
+                                        continue;
+                                    }
+                                    s.balance = 0.0;
+                                    s.last_local_change = Some(Instant::now());
+                                }
+                                Self::push_balance_to_devices(&mut state.devices, lead, 0.0);
+                            }
+                        }
+                    }
+                    return Task::batch(tasks);
                 }
                 Task::none()
             }
@@ -547,15 +961,22 @@ impl VolumeApp {
                     s.push_log(format!("{name}: {reason}"), LogKind::Failure);
                 }
             }
-            ScanEvent::DeviceFound { port_name, port, uuid, version } => {
+            ScanEvent::DeviceFound { port_name, port, uuid, version, capabilities } => {
                 if let AppScreen::Scanning(s) = &mut self.screen {
-                    s.push_log(format!("{port_name}: FaderFlow ✓"), LogKind::Success);
+                    if capabilities.is_some() {
+                        s.push_log(format!("{port_name}: FaderFlow ✓"), LogKind::Success);
+                    } else {
+                        s.push_log(
+                            format!("{port_name}: FaderFlow ✓ (firmware v{}.{} is too old, limited features)", version.0, version.1),
+                            LogKind::Success,
+                        );
+                    }
                     let watchdog_cancel = scanner::start_watchdog(
                         port_name.clone(),
                         Arc::clone(&port),
                         self.watchdog_tx.clone(),
                     );
-                    s.found_devices.push((port_name, port, uuid, version, watchdog_cancel));
+                    s.found_devices.push((port_name, port, uuid, version, capabilities, watchdog_cancel));
                 }
             }
             ScanEvent::ScanComplete { found } => {
@@ -575,24 +996,34 @@ impl VolumeApp {
 
                 let saved_renames = load_device_renames();
                 let saved_assignments = load_device_assignments();
+                let saved_tapers = load_device_tapers();
+                let saved_modes = load_encoder_modes();
                 let n = raw_devices.len();
                 let devices: Vec<DeviceInfo> = raw_devices
                     .into_iter()
-                    .map(|(port_name, port, uuid, version, watchdog_cancel)| {
+                    .map(|(port_name, port, uuid, version, capabilities, watchdog_cancel)| {
                         let uuid_str = DeviceInfo::uuid_str(&uuid);
                         let rename = saved_renames.get(&uuid_str).cloned();
                         let channel_assignments = saved_assignments
                             .get(&uuid_str)
                             .cloned()
                             .unwrap_or_default();
+                        let framing = port.lock().map_or(Framing::V1, |p| p.framing());
+                        let io = DeviceIo::spawn(port_name.clone(), port, self.device_events.clone());
                         DeviceInfo {
-                            port_name, port, uuid, version, rename,
+                            port_name, io, uuid, version, rename, framing, capabilities,
                             status: DeviceStatus::Connected,
                             watchdog_cancel,
                             channel_assignments,
-                            rx_buf: Vec::new(),
+                            channel_tapers: saved_tapers.get(&uuid_str).cloned().unwrap_or_default(),
+                            encoder_modes: saved_modes.get(&uuid_str).copied().unwrap_or_default(),
                             channel_volumes: [255; 5],
+                            channel_balances: [0; 5],
+                            channel_mutes: [false; 5],
+                            icon_progress: [None; 5],
                             last_fader_rx: [None; 5],
+                            meter_levels: [0; 5],
+                            last_meter_tx: None,
                             cal_state: None,
                             cal_debug: Vec::new(),
                         }
@@ -628,7 +1059,7 @@ impl VolumeApp {
 // ── View ─────────────────────────────────────────────────────────────────────
 
 impl VolumeApp {
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         match &self.screen {
             AppScreen::Scanning(s)  => views::scanning::view(s),
             AppScreen::NoDevices(r) => views::no_devices::view(r),
@@ -654,22 +1085,58 @@ impl VolumeApp {
                 ..Default::default()
             });
 
-        let main_content = container(match state.current_view {
-            View::Sessions => views::sessions::view(&state.sessions),
-            View::Settings => views::settings::view(),
+        let page = match state.current_view {
+            View::Sessions => views::sessions::view(
+                &state.sessions,
+                grouping_rules(),
+                &self.routes,
+                &state.output_devices,
+                self.audio_error.is_some(),
+            ),
+            View::Settings => {
+                let labels = session_labels(state.sessions.values());
+                let mut apps: Vec<AppChoice> = Vec::new();
+                for key in state.sessions.keys().filter(|k| k.kind == SessionKind::Playback && !k.is_master()) {
+                    if apps.iter().any(|a| a.exe == key.exe) { continue; }
+                    apps.push(AppChoice { exe: key.exe.clone(), label: labels[key].clone() });
+                }
+                apps.sort_by(|a, b| a.label.cmp(&b.label));
+                views::settings::view(&self.ducker.settings, self.ducker.is_ducking(), apps)
+            }
             View::About    => views::about::view(),
             View::Devices  => {
-                let session_names: Vec<String> = state.sessions.keys().cloned().collect();
+                let mut choices: Vec<SessionChoice> = session_labels(state.sessions.values())
+                    .into_iter()
+                    .map(|(key, label)| {
+                        let label = match key.kind {
+                            SessionKind::Playback => label,
+                            SessionKind::Recording => format!("{label} (recording)"),
+                            SessionKind::Input => format!("🎤 {label}"),
+                        };
+                        SessionChoice { key, label }
+                    })
+                    .collect();
+                // Master first, then playback, recording and inputs, by name
+                choices.sort_by(|a, b| b.key.is_master().cmp(&a.key.is_master())
+                    .then_with(|| a.key.kind.cmp(&b.key.kind))
+                    .then_with(|| a.label.cmp(&b.label)));
                 views::devices::view(
                     &state.devices,
                     &state.rename_drafts,
                     &state.debug_open,
-                    session_names,
+                    choices,
                     &state.output_devices,
                     state.current_output.clone(),
                 )
             }
-        })
+        };
+        // Backend failures stay on screen above whichever page is open
+        let page = match &self.audio_error {
+            Some(e) => column![views::audio_error::banner(e), page].spacing(12).into(),
+            None => page,
+        };
+
+        let main_content = container(page)
             .width(iced::Length::Fill)
             .height(iced::Length::Fill)
             .padding(20);
@@ -707,7 +1174,27 @@ impl VolumeApp {
 
 impl VolumeApp {
     pub fn subscription(&self) -> Subscription<Message> {
-        let mut subs = vec![];
+        // Audio updates are drained on every screen, see PollAudioReceiver
+        let mut subs = vec![Subscription::run(|| {
+            use iced::stream;
+            stream::channel(
+                100,
+                |mut output: futures::channel::mpsc::Sender<Message>| async move {
+                    loop {
+                        tokio::time::sleep(Duration::from_millis(16)).await;
+                        let _ = output.try_send(Message::PollAudioReceiver);
+                    }
+                },
+            )
+        })];
+
+        // A sound server that went away usually comes back on its own
+        if self.audio_error.as_ref().is_some_and(|e| matches!(e, AudioError::BackendUnavailable(_))) {
+            subs.push(
+                iced::time::every(Duration::from_secs(AUDIO_RECONNECT_SECS))
+                    .map(|_| Message::RetryAudio),
+            );
+        }
 
         match &self.screen {
             AppScreen::Scanning(_) => {
@@ -717,32 +1204,15 @@ impl VolumeApp {
                 );
             }
             AppScreen::Ready(_) => {
+                // Backends push session changes; this only catches anything missed
                 subs.push(
-                    Subscription::run(|| {
-                        use iced::stream;
-                        stream::channel(
-                            100,
-                            |mut output: futures::channel::mpsc::Sender<Message>| async move {
-                                loop {
-                                    tokio::time::sleep(Duration::from_millis(16)).await;
-                                    let _ = output.try_send(Message::PollAudioReceiver);
-                                }
-                            },
-                        )
-                    })
-                );
-                subs.push(
-                    iced::time::every(Duration::from_secs(2))
+                    iced::time::every(Duration::from_secs(SESSION_RESYNC_SECS))
                         .map(|_| Message::RefreshSessions),
                 );
                 subs.push(
                     iced::time::every(Duration::from_millis(500))
                         .map(|_| Message::WatchdogTick),
                 );
-                subs.push(
-                    iced::time::every(Duration::from_millis(30))
-                        .map(|_| Message::PollSerial),
-                );
             }
             AppScreen::NoDevices(NoDevicesReason::Lost { .. }) => {
                 subs.push(
@@ -756,12 +1226,204 @@ impl VolumeApp {
         Subscription::batch(subs)
     }
 
-    fn push_volume_to_devices(devices: &mut [DeviceInfo], session_id: &str, volume: f32) {
-        let pct = (volume * 100.0).round().clamp(0.0, 100.0) as u8;
+    /// Point assignments whose session is gone at a live session of the same
+    /// app: a restarted process (new pid), a changed grouping rule, or a
+    /// name-only key from an old config. Returns the devices that changed;
+    /// the new keys are saved so old configs migrate on first sight.
+    fn rebind_assignments(
+        devices: &mut [DeviceInfo],
+        sessions: &HashMap<SessionKey, AudioSession>,
+    ) -> Vec<usize> {
+        let mut changed = vec![];
+        for (di, dev) in devices.iter_mut().enumerate() {
+            for ch in 0..dev.channel_count() {
+                let stale: Vec<SessionKey> = dev.channel_assignments[ch].keys()
+                    .filter(|k| !sessions.contains_key(*k))
+                    .cloned()
+                    .collect();
+                for key in stale {
+                    let replacement = key.rebind_target(
+                        sessions.values(),
+                        |id| dev.channel_assignments.iter().any(|g| g.contains(id)),
+                    );
+                    if let Some(new_key) = replacement {
+                        dev.channel_assignments[ch].rekey(&key, new_key);
+                        if !changed.contains(&di) { changed.push(di); }
+                    }
+                }
+            }
+        }
+        if !changed.is_empty() {
+            save_device_assignments(devices);
+        }
+        changed
+    }
+
+    /// Bring the backend up and attach its event stream.
+    fn start_backend(backend: &mut dyn AudioBackend, audio_tx: mpsc::Sender<AudioUpdate>) -> Result<(), AudioError> {
+        backend.initialize()?;
+        backend.start_listening(audio_tx.clone())?;
+        // Meters are a nicety; without them the rows just don't show levels
+        match backend.start_metering(audio_tx) {
+            Ok(()) | Err(AudioError::NotSupported(_)) => {}
+            Err(e) => eprintln!("Level metering unavailable: {e}"),
+        }
+        Ok(())
+    }
+
+    /// Send a newly seen playback session to its app's saved output device.
+    fn apply_route(backend: &mut dyn AudioBackend, routes: &HashMap<String, String>, key: &SessionKey) {
+        if key.kind != SessionKind::Playback { return; }
+        if let Some(device) = routes.get(&key.exe) {
+            if let Err(e) = backend.move_session_to_device(key, device) {
+                eprintln!("Failed to route {key} to {device}: {e}");
+            }
+        }
+    }
+
+    /// Route a volume change to the session or, for the master target, to the
+    /// default output device.
+    fn set_target_volume(backend: &mut dyn AudioBackend, key: &SessionKey, volume: f32) -> Result<(), AudioError> {
+        if key.is_master() {
+            backend.set_endpoint_volume(volume)
+        } else {
+            backend.set_volume(key, volume)
+        }
+    }
+
+    fn set_target_mute(backend: &mut dyn AudioBackend, key: &SessionKey, muted: bool) -> Result<(), AudioError> {
+        if key.is_master() {
+            backend.set_endpoint_mute(muted)
+        } else {
+            backend.set_mute(key, muted)
+        }
+    }
+
+    /// Send each device one frame with the peak level of every assigned
+    /// channel, rate-limited so it never crowds out fader or volume traffic.
+    fn push_meters_to_devices(devices: &mut [DeviceInfo], sessions: &HashMap<SessionKey, AudioSession>) {
+        for dev in devices.iter_mut() {
+            if dev.cal_state.is_some() { continue; }  // screens show instructions
+            if !dev.shows_meters() { continue; }
+            if dev.last_meter_tx.is_some_and(|t| t.elapsed() < Duration::from_millis(METER_FRAME_MS)) {
+                continue;
+            }
+            // A hand on a fader means a stream of fader updates; stay out of the way
+            if dev.last_fader_rx.iter().flatten().any(|t| t.elapsed() < Duration::from_millis(300)) {
+                continue;
+            }
+            let mut levels = [0u8; 5];
+            for (ch, level) in levels.iter_mut().enumerate() {
+                // A group meters its loudest member
+                let peak = dev.channel_assignments[ch].keys()
+                    .filter_map(|k| sessions.get(k))
+                    .map(|s| s.level.peak)
+                    .fold(0.0, f32::max);
+                *level = (AudioLevel::meter_position(peak) * 255.0).round() as u8;
+            }
+            // Small wobbles aren't worth a frame; a drop to silence always is
+            let changed = levels.iter().zip(dev.meter_levels)
+                .any(|(&new, old)| new.abs_diff(old) > 3 || (new == 0) != (old == 0));
+            if !changed { continue; }
+            send_meters(&dev.io, levels);
+            dev.meter_levels = levels;
+            dev.last_meter_tx = Some(Instant::now());
+        }
+    }
+
+    /// Send a channel's icon the cheapest way the device takes it: run-length
+    /// encoded if that at least halves it, else a row at a time in the
+    /// background, else raw in one go.
+    fn push_icon(io: &DeviceIo, caps: &Capabilities, channel: u8, rgb565: Vec<u8>) {
+        if caps.supports(CMD_DISPLAY_UPDATE_ICON_RLE) {
+            let runs = crate::utils::icon::rgb565_to_rle_icon(&rgb565);
+            if runs.len() <= ICON_BYTES / 2 {
+                io.cancel_icon(channel);  // a row upload would draw over it
+                send_icon_rle(io, channel, runs);
+                return;
+            }
+        }
+        if caps.supports(CMD_DISPLAY_UPDATE_ICON_ROW) {
+            io.upload_icon(channel, rgb565);
+        } else {
+            send_icon(io, channel, &rgb565);
+        }
+    }
+
+    /// Fader position (percent, as the device speaks it) for a volume on a
+    /// channel's taper.
+    fn fader_percent(taper: &Taper, volume: f32) -> u8 {
+        (taper.position_for(volume) * 100.0).round().clamp(0.0, 100.0) as u8
+    }
+
+    /// Group level a channel's fader stands for, read back from its lead.
+    fn group_level(group: &ChannelGroup, sessions: &HashMap<SessionKey, AudioSession>) -> Option<f32> {
+        let lead = sessions.get(group.lead()?)?;
+        group.level_for_lead(lead.volume)
+    }
+
+    /// Set every member of a group to its volume at `level`.
+    fn apply_group_level(
+        backend: &mut dyn AudioBackend,
+        ducker: &mut Ducker,
+        group: &ChannelGroup,
+        sessions: &mut HashMap<SessionKey, AudioSession>,
+        level: f32,
+    ) {
+        for (key, volume) in group.volumes_at(level) {
+            let Some(session) = sessions.get_mut(key) else { continue };
+            if (session.volume - volume).abs() < 0.001 { continue; }
+            let _ = Self::set_target_volume(backend, key, ducker.backend_volume(key, volume));
+            session.volume = volume;
+            session.last_local_change = Some(Instant::now());
+        }
+    }
+
+    /// Name for a channel's display: the app, or the lead and a count.
+    fn group_label(group: &ChannelGroup, labels: &HashMap<SessionKey, String>) -> String {
+        let Some(lead) = group.lead() else { return String::new() };
+        let name = labels.get(lead).cloned().unwrap_or_else(|| lead.app_name().to_string());
+        match group.members.len() {
+            1 => name,
+            n => format!("{name} +{}", n - 1),
+        }
+    }
+
+    /// Balance as the device speaks it, -100 to 100.
+    fn balance_wire(balance: f32) -> i8 {
+        (balance * 100.0).round().clamp(-100.0, 100.0) as i8
+    }
+
+    /// Show a session's balance on every channel whose knob controls it.
+    fn push_balance_to_devices(devices: &mut [DeviceInfo], session_id: &SessionKey, balance: f32) {
+        let wire = Self::balance_wire(balance);
+        for dev in devices.iter_mut() {
+            if dev.cal_state.is_some() || !dev.supports(CMD_DISPLAY_UPDATE_BALANCE) { continue; }
+            for ch in 0..dev.channel_count() {
+                if dev.channel_assignments[ch].lead() != Some(session_id) { continue; }
+                if dev.encoder_modes[ch] != EncoderMode::Balance { continue; }
+                if dev.channel_balances[ch] == wire { continue; }
+                send_balance(&dev.io, ch as u8, wire);
+                dev.channel_balances[ch] = wire;
+            }
+        }
+    }
+
+    /// Move the faders a session leads and show its mute state on them.
+    fn push_volume_to_devices(devices: &mut [DeviceInfo], session_id: &SessionKey, volume: f32, muted: bool) {
         for dev in devices.iter_mut() {
             if dev.cal_state.is_some() { continue; }  // calibration in progress
-            for ch in 0..5 {
-                if dev.channel_assignments[ch] != session_id { continue; }
+            let send_mutes = dev.supports(CMD_DISPLAY_UPDATE_MUTE);
+            for ch in 0..dev.channel_count() {
+                // Only the lead moves a group's fader
+                let group = &dev.channel_assignments[ch];
+                if group.lead() != Some(session_id) { continue; }
+                if send_mutes && dev.channel_mutes[ch] != muted {
+                    send_mute(&dev.io, ch as u8, muted);
+                    dev.channel_mutes[ch] = muted;
+                }
+                let Some(level) = group.level_for_lead(volume) else { continue };
+                let pct = Self::fader_percent(&dev.channel_tapers[ch], level);
                 // Touch hold-off: hand is on the fader — it's the source of truth
                 if dev.last_fader_rx[ch]
                     .map(|t| t.elapsed() < Duration::from_millis(300))
@@ -769,10 +1431,8 @@ impl VolumeApp {
                 // Echo/dedupe guard: device already shows this value
                 if dev.channel_volumes[ch] != 255
                     && dev.channel_volumes[ch].abs_diff(pct) <= 1 { continue; }
-                if let Ok(mut p) = dev.port.lock() {
-                    send_volume(&mut **p, ch as u8, pct);
-                    dev.channel_volumes[ch] = pct;
-                }
+                send_volume(&dev.io, ch as u8, pct);
+                dev.channel_volumes[ch] = pct;
             }
         }
     }
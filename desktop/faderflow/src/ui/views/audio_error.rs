@@ -0,0 +1,41 @@
+use iced::widget::{button, container, row, text, Space};
+use iced::{Alignment, Color, Element, Length};
+
+use crate::audio::AudioError;
+use crate::ui::app::Message;
+
+/// Persistent strip above the main view while the audio backend is failing.
+pub fn banner(error: &AudioError) -> Element<'_, Message> {
+    let hint = match error {
+        AudioError::BackendUnavailable(_) => "Is the sound server running?",
+        AudioError::PermissionDenied(_) => "Check that FaderFlow may access audio devices.",
+        _ => "",
+    };
+
+    let retry_btn = button(text("Retry").size(12))
+        .on_press(Message::RetryAudio)
+        .padding([4, 12]);
+
+    container(
+        row![
+            text("⚠").size(16).color(Color::from_rgb(1.0, 0.8, 0.3)),
+            text(if hint.is_empty() { error.to_string() } else { format!("{error}. {hint}") }).size(13),
+            Space::new().width(Length::Fill),
+            retry_btn,
+        ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+    )
+        .width(Length::Fill)
+        .padding([8, 12])
+        .style(|_theme: &iced::Theme| container::Style {
+            background: Some(iced::Background::Color(Color::from_rgb(0.25, 0.12, 0.08))),
+            border: iced::Border {
+                color: Color::from_rgb(0.6, 0.3, 0.15),
+                width: 1.0,
+                radius: 4.0.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+}
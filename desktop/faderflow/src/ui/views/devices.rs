@@ -1,14 +1,37 @@
-use iced::widget::{button, column, container, pick_list, row, scrollable, text, text_input, toggler, Space};
+use iced::widget::{button, column, container, pick_list, progress_bar, row, scrollable, slider, text, text_input, toggler, Space};
 use iced::{Alignment, Color, Element, Length};
 
-use crate::comms::device_info::{DeviceInfo, DeviceStatus};
+use crate::audio::{SessionKey, SessionKind};
+use crate::comms::device_info::{DeviceInfo, DeviceStatus, EncoderMode};
+use crate::comms::protocol::{CMD_CALIBRATION_START, CMD_ENCODER_MODE};
 use crate::ui::app::Message;
+use crate::utils::channel_group::GroupMode;
+use crate::utils::taper::Taper;
+
+/// Pick-list entry for a channel assignment: the key, shown by its label.
+#[derive(Debug, Clone)]
+pub struct SessionChoice {
+    pub key: SessionKey,
+    pub label: String,
+}
+
+impl PartialEq for SessionChoice {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl std::fmt::Display for SessionChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
 
 pub fn view<'a>(
     devices: &'a [DeviceInfo],
     rename_drafts: &'a [String],
     debug_open: &'a [bool],
-    session_names: Vec<String>,
+    sessions: Vec<SessionChoice>,
     output_devices: &'a [String],
     current_output: Option<String>,
 ) -> Element<'a, Message> {
@@ -41,8 +64,7 @@ pub fn view<'a>(
         .iter()
         .enumerate()
         .map(|(i, dev)| {
-            let names = session_names.clone();
-            device_card(i, dev, &rename_drafts[i], debug_open[i], names)
+            device_card(i, dev, &rename_drafts[i], debug_open[i], sessions.clone())
         })
         .collect();
 
@@ -65,7 +87,7 @@ fn device_card<'a>(
     dev: &'a DeviceInfo,
     rename_draft: &'a str,
     debug_open: bool,
-    session_names: Vec<String>,
+    sessions: Vec<SessionChoice>,
 ) -> Element<'a, Message> {
     let status_color = match dev.status {
         DeviceStatus::Connected => Color::from_rgb(0.2, 0.85, 0.4),
@@ -81,6 +103,8 @@ fn device_card<'a>(
         row![label("Port"),     text(&dev.port_name).size(13)].spacing(8),
         row![label("UUID"),     text(dev.uuid_string()).size(13)].spacing(8),
         row![label("Firmware"), text(dev.version_string()).size(13)].spacing(8),
+        row![label("Link"),     text(dev.link_string()).size(13)].spacing(8),
+        row![label("Hardware"), text(dev.capabilities_string()).size(13)].spacing(8),
     ].spacing(6);
 
     // ── Rename row ───────────────────────────────────────────────────────
@@ -99,35 +123,119 @@ fn device_card<'a>(
         .align_y(Alignment::Center);
 
     // ── Channel assignments ──────────────────────────────────────────────
-    let mut options: Vec<String> = session_names;
+    let options: Vec<SessionChoice> = sessions;
 
-    let channel_rows: Vec<Element<Message>> = (0..5)
+    let encoder_modes = dev.supports(CMD_ENCODER_MODE);
+    let channel_rows: Vec<Element<Message>> = (0..dev.channel_count())
         .map(|ch| {
-            let selected = dev.channel_assignments[ch].clone();
-            let selected_opt: Option<String> = if selected.is_empty() {
-                None
-            } else {
-                Some(selected)
-            };
-            let opts = options.clone();
+            let group = &dev.channel_assignments[ch];
+            // Anything not already on this channel can join it
+            let opts: Vec<SessionChoice> = options.iter()
+                .filter(|c| !group.contains(&c.key))
+                .cloned()
+                .collect();
             let ch_label = format!("Ch {}", ch + 1);
             let clear_btn = button(text("✕").size(11))
-                .on_press(Message::DeviceChannelAssign(idx, ch, String::new()))
+                .on_press(Message::DeviceChannelAssign(idx, ch, None))
                 .padding([3, 7]);
-            row![
+
+            // Fader curve; a custom one (config file only) stays selectable
+            let taper = &dev.channel_tapers[ch];
+            let mut tapers = Taper::PRESETS.to_vec();
+            if matches!(taper, Taper::Custom(_)) { tapers.push(taper.clone()); }
+            let taper_pick = pick_list(tapers, Some(taper.clone()), move |t| {
+                Message::DeviceChannelTaper(idx, ch, t)
+            })
+                .text_size(12)
+                .padding([3, 6]);
+            let mode_pick = pick_list(EncoderMode::ALL, Some(dev.encoder_modes[ch]), move |m| {
+                Message::DeviceEncoderMode(idx, ch, m)
+            })
+                .text_size(12)
+                .padding([3, 6]);
+
+            let mut channel_row = row![
                 text(ch_label).size(12)
                     .color(Color::from_rgb(0.5, 0.5, 0.5))
                     .width(Length::Fixed(70.0)),
-                pick_list(opts, selected_opt, move |s| {
-                    Message::DeviceChannelAssign(idx, ch, s)
+                pick_list(opts, None::<SessionChoice>, move |c: SessionChoice| {
+                    Message::DeviceChannelAssign(idx, ch, Some(c.key))
                 })
-                .placeholder("— none —")
+                .placeholder(if group.is_empty() { "— none —" } else { "+ add app…" })
                 .text_size(13)
                 .width(Length::Fixed(200.0)),
                 clear_btn,
+                taper_pick,
             ]
                 .spacing(8)
-                .align_y(Alignment::Center)
+                .align_y(Alignment::Center);
+            if encoder_modes {
+                channel_row = channel_row.push(mode_pick);
+            }
+            if let Taper::Decibel { floor_db } = *taper {
+                channel_row = channel_row.push(
+                    slider(-90.0..=-20.0, floor_db, move |f| {
+                        Message::DeviceChannelTaper(idx, ch, Taper::Decibel { floor_db: f })
+                    })
+                        .step(6.0)
+                        .width(Length::Fixed(100.0)),
+                );
+            }
+            if let Some(progress) = dev.icon_progress[ch] {
+                channel_row = channel_row.push(
+                    row![
+                        text("Icon").size(11).color(Color::from_rgb(0.5, 0.5, 0.5)),
+                        progress_bar(0.0..=1.0, progress)
+                            .length(Length::Fixed(60.0))
+                            .girth(Length::Fixed(6.0)),
+                    ]
+                        .spacing(4)
+                        .align_y(Alignment::Center),
+                );
+            }
+            if group.members.len() > 1 {
+                channel_row = channel_row.push(
+                    pick_list(GroupMode::ALL, Some(group.mode), move |m| {
+                        Message::DeviceChannelGroupMode(idx, ch, m)
+                    })
+                        .text_size(12)
+                        .padding([3, 6]),
+                );
+            }
+
+            // Members, lead first; trims only matter once there's a group
+            let member_rows: Vec<Element<Message>> = group.members.iter()
+                .map(|m| {
+                    let mut member_row = row![
+                        text(choice_for(&m.key, &options).label).size(12)
+                            .width(Length::Fixed(200.0)),
+                    ]
+                        .spacing(8)
+                        .align_y(Alignment::Center);
+                    if group.members.len() > 1 {
+                        let key = m.key.clone();
+                        member_row = member_row
+                            .push(
+                                slider(group.mode.trim_range(), m.trim, move |t| {
+                                    Message::DeviceChannelTrim(idx, ch, key.clone(), t)
+                                })
+                                    .step(0.05)
+                                    .width(Length::Fixed(120.0)),
+                            )
+                            .push(text(trim_label(group.mode, m.trim)).size(11).width(Length::Fixed(50.0)));
+                    }
+                    member_row
+                        .push(
+                            button(text("✕").size(10))
+                                .on_press(Message::DeviceChannelUnassign(idx, ch, m.key.clone()))
+                                .padding([2, 6]),
+                        )
+                        .into()
+                })
+                .collect();
+
+            column![channel_row, container(column(member_rows).spacing(4)).padding([0, 78])]
+                .spacing(4)
                 .into()
         })
         .collect();
@@ -159,12 +267,27 @@ fn device_card<'a>(
             text(status_label).size(12).color(status_color),
         ].align_y(Alignment::Center),
         info,
-        rename_row,
-        channels_section,
-        debug_toggle,
     ]
         .spacing(10);
 
+    if dev.firmware_too_old() {
+        card_col = card_col.push(
+            text(format!(
+                "Firmware {} is too old to report its features; only faders, names, icons \
+                 and calibration are used. Update the firmware for meters, mute, balance and \
+                 knob modes.",
+                dev.version_string(),
+            ))
+                .size(12)
+                .color(Color::from_rgb(1.0, 0.8, 0.3)),
+        );
+    }
+
+    card_col = card_col
+        .push(rename_row)
+        .push(channels_section)
+        .push(debug_toggle);
+
     if debug_open {
         let raw_uuid = dev.uuid.iter()
             .map(|b| format!("{b:02X}"))
@@ -237,14 +360,15 @@ fn device_card<'a>(
                 .spacing(10),
         );
     } else {
-        let calibrate_btn = button(text("Calibrate faders").size(12))
-            .on_press(Message::DeviceCalibrate(idx))
-            .padding([5, 12]);
-        card_col = card_col.push(row![
-            Space::new().width(Length::Fill),
-            calibrate_btn,
-            disconnect_btn,
-        ].spacing(8));
+        let mut footer = row![Space::new().width(Length::Fill)].spacing(8);
+        if dev.supports(CMD_CALIBRATION_START) {
+            footer = footer.push(
+                button(text("Calibrate faders").size(12))
+                    .on_press(Message::DeviceCalibrate(idx))
+                    .padding([5, 12]),
+            );
+        }
+        card_col = card_col.push(footer.push(disconnect_btn));
     }
 
     container(card_col)
@@ -262,7 +386,30 @@ fn device_card<'a>(
         .into()
 }
 
-fn label(s: &str) -> Element<Message> {
+/// Choice for an assigned key; an app that isn't running still shows, by exe name.
+fn choice_for(key: &SessionKey, options: &[SessionChoice]) -> SessionChoice {
+    options.iter()
+        .find(|c| &c.key == key)
+        .cloned()
+        .unwrap_or_else(|| SessionChoice {
+            key: key.clone(),
+            label: match key.kind {
+                SessionKind::Playback => format!("{} (not running)", key.app_name()),
+                SessionKind::Recording => format!("{} (recording, not running)", key.app_name()),
+                SessionKind::Input => format!("🎤 {} (disconnected)", key.app_name()),
+            },
+        })
+}
+
+/// "×0.75" or "-10%", as the trim applies in its mode.
+fn trim_label(mode: GroupMode, trim: f32) -> String {
+    match mode {
+        GroupMode::Proportional => format!("×{trim:.2}"),
+        GroupMode::Offset => format!("{:+.0}%", trim * 100.0),
+    }
+}
+
+fn label(s: &str) -> Element<'_, Message> {
     text(format!("{s}:"))
         .size(12)
         .color(Color::from_rgb(0.5, 0.5, 0.5))
@@ -4,3 +4,4 @@ pub mod about;
 pub mod scanning;
 pub mod no_devices;
 pub mod devices;
+pub mod audio_error;
@@ -9,7 +9,7 @@ pub enum NoDevicesReason {
     Lost { port_name: String, retry_in_secs: u64 },
 }
 
-pub fn view(reason: &NoDevicesReason) -> Element<Message> {
+pub fn view(reason: &NoDevicesReason) -> Element<'_, Message> {
     let (headline, sub, show_countdown) = match reason {
         NoDevicesReason::NoneFound => (
             "No FaderFlow devices found",
@@ -4,15 +4,19 @@ use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use iced::{Alignment, Element, Length};
 
+use crate::comms::capabilities::Capabilities;
 use crate::comms::scanner::SharedPort;
 use crate::ui::app::Message;
 
+/// Port name, shared port, UUID, firmware version, capabilities and watchdog cancel flag
+pub type FoundDevice = (String, SharedPort, [u8; 16], (u8, u8), Option<Capabilities>, Arc<AtomicBool>);
+
 #[derive(Default)]
 pub struct ScanningState {
     pub status: String,
     pub progress: f32,                              // 0.0 – 1.0
     pub log: Vec<LogEntry>,
-    pub found_devices: Vec<(String, SharedPort, [u8; 16], (u8, u8), Arc<AtomicBool>)>,
+    pub found_devices: Vec<FoundDevice>,
 }
 
 #[derive(Debug, Clone)]
@@ -34,7 +38,7 @@ impl ScanningState {
     }
 }
 
-pub fn view(state: &ScanningState) -> Element<Message> {
+pub fn view(state: &ScanningState) -> Element<'_, Message> {
     let title = text("Scanning for FaderFlow devices")
         .size(22);
 
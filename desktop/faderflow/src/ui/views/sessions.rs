@@ -1,64 +1,214 @@
-use crate::audio::AudioSession;
+use crate::audio::{session_labels, AudioLevel, AudioSession, Grouping, SessionKey, SessionKind};
 use crate::ui::app::Message;
-use iced::widget::{button, column, container, row, slider, text, Column, Image};
-use iced::Element;
+use iced::widget::{button, column, container, pick_list, row, slider, text, Column, Image, Space};
+use iced::{Color, Element, Length};
 use std::collections::HashMap;
 
-pub fn view<'a>(sessions: &'a HashMap<String, AudioSession>) -> Element<'a, Message> {
+const METER_WIDTH: f32 = 80.0;
+
+/// Pick-list entry for an app's output route.
+#[derive(Debug, Clone, PartialEq)]
+enum OutputChoice {
+    Default,
+    Device(String),
+}
+
+impl std::fmt::Display for OutputChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputChoice::Default => f.write_str("System default"),
+            OutputChoice::Device(name) => f.write_str(name),
+        }
+    }
+}
+
+pub fn view<'a>(
+    sessions: &'a HashMap<SessionKey, AudioSession>,
+    grouping: HashMap<String, Grouping>,
+    routes: &HashMap<String, String>,
+    output_devices: &[String],
+    backend_failed: bool,
+) -> Element<'a, Message> {
+    let outputs: Vec<OutputChoice> = std::iter::once(OutputChoice::Default)
+        .chain(output_devices.iter().cloned().map(OutputChoice::Device))
+        .collect();
+
     let mut content: Column<Message> = column![text("Audio Sessions").size(24)].spacing(20);
 
-    if sessions.is_empty() {
+    // A failing backend has its own banner; an empty list says nothing then
+    if !backend_failed && sessions.keys().all(|k| k.is_master() || k.kind != SessionKind::Playback) {
         content = content.push(text("No audio sessions found. Play some audio..."));
     }
 
-    for (id, session) in sessions {
-        let slider_widget = slider(0.0..=1.0, session.volume, {
-            let id = id.clone();
-            move |v| Message::VolumeChanged(id.clone(), v)
-        })
-            .step(0.01);
+    let labels = session_labels(sessions.values());
+    let mut ordered: Vec<(&SessionKey, &AudioSession)> = sessions.iter().collect();
+    // System master on top, apps by name
+    ordered.sort_by(|a, b| b.0.is_master().cmp(&a.0.is_master()).then_with(|| labels[a.0].cmp(&labels[b.0])));
 
-        let mute_button = button(text(if session.is_muted { "🔇" } else { "🔊" })).on_press({
-            let id = id.clone();
-            Message::ToggleMute(id)
-        });
+    // Playback first, then recording streams and inputs under their own headings
+    for kind in SessionKind::ALL {
+        let mut section = ordered.iter().filter(|(id, _)| id.kind == kind).peekable();
+        if section.peek().is_none() { continue; }
+        match kind {
+            SessionKind::Playback => {}
+            SessionKind::Recording => content = content.push(text("Recording").size(18)),
+            SessionKind::Input => content = content.push(text("Inputs").size(18)),
+        }
+        for (id, session) in section {
+            content = content.push(session_row(id, session, labels[*id].clone(), &grouping, routes, &outputs));
+        }
+    }
 
-        let header = if let Some(icon_handle) = &session.icon_handle {
-            row![
-                Image::new(icon_handle.as_ref().clone())
-                    .width(24)
-                    .height(24),
-                text(&session.display_name)
-            ]
-                .spacing(10)
-                .align_y(iced::Alignment::Center)
-        } else {
-            row![text(&session.display_name)]
-        };
-
-        let volume_control = row![
-            slider_widget,
-            text(format!("{}%", (session.volume * 100.0) as i32)).width(50),
-            mute_button
+    column![content].into()
+}
+
+fn session_row<'a>(
+    id: &SessionKey,
+    session: &'a AudioSession,
+    label: String,
+    grouping: &HashMap<String, Grouping>,
+    routes: &HashMap<String, String>,
+    outputs: &[OutputChoice],
+) -> Element<'a, Message> {
+    let slider_widget = slider(0.0..=1.0, session.volume, {
+        let id = id.clone();
+        move |v| Message::VolumeChanged(id.clone(), v)
+    })
+        .step(0.01);
+
+    let unmuted = if id.kind == SessionKind::Playback { "🔊" } else { "🎤" };
+    let mute_button = button(text(if session.is_muted { "🔇" } else { unmuted })).on_press({
+        let id = id.clone();
+        Message::ToggleMute(id)
+    });
+
+    let header = if let Some(icon_handle) = &session.icon_handle {
+        row![
+            Image::new(icon_handle.as_ref().clone())
+                .width(24)
+                .height(24),
+            text(label)
         ]
             .spacing(10)
-            .align_y(iced::Alignment::Center);
-
-        content = content.push(
-            container(column![header, volume_control].spacing(5))
-                .padding(10)
-                .style(|_theme: &iced::Theme| container::Style {
-                    background: Some(iced::Background::Color(iced::Color::from_rgb(
-                        0.1, 0.1, 0.1,
-                    ))),
-                    border: iced::Border {
-                        radius: 5.0.into(),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                }),
+            .align_y(iced::Alignment::Center)
+    } else {
+        row![text(label)].spacing(10).align_y(iced::Alignment::Center)
+    };
+
+    // How this app's processes/streams fold into sessions
+    let header = if id.is_master() || id.kind == SessionKind::Input {
+        header
+    } else {
+        let exe = id.exe.clone();
+        let current = grouping.get(&exe).copied().unwrap_or_default();
+        header.push(Space::new().width(Length::Fill)).push(
+            pick_list(Grouping::ALL, Some(current), move |g| Message::SetGrouping(exe.clone(), g))
+                .text_size(11)
+                .padding([2, 6]),
+        )
+    };
+
+    // Which output device the app plays through
+    let header = if id.is_master() || id.kind != SessionKind::Playback {
+        header
+    } else {
+        let current = routes.get(&id.exe)
+            .map_or(OutputChoice::Default, |d| OutputChoice::Device(d.clone()));
+        let id = id.clone();
+        header.push(
+            pick_list(outputs.to_vec(), Some(current), move |c| Message::RouteSession(
+                id.clone(),
+                match c {
+                    OutputChoice::Default => None,
+                    OutputChoice::Device(name) => Some(name),
+                },
+            ))
+                .text_size(11)
+                .padding([2, 6]),
+        )
+    };
+
+    let volume_control = row![
+        slider_widget,
+        meter(session.level),
+        text(format!("{}%", (session.volume * 100.0) as i32)).width(50),
+        mute_button
+    ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center);
+
+    let mut body = column![header, volume_control].spacing(5);
+    // Left/right balance; only playback streams have one to set
+    if !id.is_master() && id.kind == SessionKind::Playback {
+        let balance_slider = slider(-1.0..=1.0, session.balance, {
+            let id = id.clone();
+            move |b| Message::BalanceChanged(id.clone(), b)
+        })
+            .step(0.05)
+            .width(Length::Fixed(160.0));
+        body = body.push(
+            row![
+                text("L").size(11),
+                balance_slider,
+                text("R").size(11),
+                text(balance_label(session.balance)).size(11).width(50),
+            ]
+                .spacing(6)
+                .align_y(iced::Alignment::Center),
         );
     }
 
-    column![content].into()
-}
\ No newline at end of file
+    container(body)
+        .padding(10)
+        .style(|_theme: &iced::Theme| container::Style {
+            background: Some(iced::Background::Color(iced::Color::from_rgb(
+                0.1, 0.1, 0.1,
+            ))),
+            border: iced::Border {
+                radius: 5.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .into()
+}
+
+/// "C", or how far off centre and which way ("L 40", "R 15").
+fn balance_label(balance: f32) -> String {
+    let pct = (balance * 100.0).round() as i32;
+    match pct {
+        0 => "C".into(),
+        p if p < 0 => format!("L {}", -p),
+        p => format!("R {p}"),
+    }
+}
+
+/// Level bar: RMS solid, peak as a dimmer tail past it.
+fn meter<'a>(level: AudioLevel) -> Element<'a, Message> {
+    let rms = AudioLevel::meter_position(level.rms);
+    let peak = AudioLevel::meter_position(level.peak).max(rms);
+    let segment = |fraction: f32, color: Color| {
+        container(Space::new())
+            .width(METER_WIDTH * fraction)
+            .height(Length::Fill)
+            .style(move |_theme: &iced::Theme| container::Style {
+                background: Some(iced::Background::Color(color)),
+                ..Default::default()
+            })
+    };
+    container(row![
+        segment(rms, Color::from_rgb(0.3, 0.8, 0.4)),
+        segment(peak - rms, Color::from_rgb(0.2, 0.45, 0.25)),
+    ])
+        .width(METER_WIDTH)
+        .height(8)
+        .style(|_theme: &iced::Theme| container::Style {
+            background: Some(iced::Background::Color(Color::from_rgb(0.2, 0.2, 0.2))),
+            border: iced::Border {
+                radius: 2.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .into()
+}
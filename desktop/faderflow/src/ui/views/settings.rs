@@ -1,12 +1,156 @@
+use crate::audio::{DuckingSettings, SessionKey};
 use crate::ui::app::Message;
-use iced::widget::{column, text};
-use iced::Element;
+use iced::widget::{button, column, container, pick_list, row, slider, text, toggler, Column};
+use iced::{Alignment, Color, Element, Length};
 
-pub fn view<'a>() -> Element<'a, Message> {
+/// Pick-list entry for a trigger app: the exe, shown by its app name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppChoice {
+    pub exe: String,
+    pub label: String,
+}
+
+impl std::fmt::Display for AppChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+/// `apps` are the running playback apps; `active` is whether a duck is on now.
+pub fn view<'a>(ducking: &DuckingSettings, active: bool, apps: Vec<AppChoice>) -> Element<'a, Message> {
     column![
         text("Settings").size(24),
-        text("Settings options will go here...").size(16),
+        ducking_section(ducking, active, apps),
     ]
         .spacing(20)
         .into()
-}
\ No newline at end of file
+}
+
+fn ducking_section<'a>(ducking: &DuckingSettings, active: bool, apps: Vec<AppChoice>) -> Element<'a, Message> {
+    // Every control sends back a modified copy of the settings
+    let with = {
+        let ducking = ducking.clone();
+        move |f: &dyn Fn(&mut DuckingSettings)| {
+            let mut s = ducking.clone();
+            f(&mut s);
+            Message::SetDucking(s)
+        }
+    };
+
+    let status = if !ducking.enabled {
+        "Off"
+    } else if active {
+        "Ducking now"
+    } else {
+        "Listening"
+    };
+    let header = row![
+        toggler(ducking.enabled).on_toggle({
+            let with = with.clone();
+            move |on| with(&|s| s.enabled = on)
+        }),
+        text("Duck other apps while a voice app is talking").size(16),
+        text(status).size(12).color(Color::from_rgb(0.55, 0.55, 0.55)),
+    ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+    let mut section: Column<Message> = column![
+        header,
+        text("Needs level metering; backends without it never trigger.").size(12)
+            .color(Color::from_rgb(0.5, 0.5, 0.5)),
+    ].spacing(10);
+    if !ducking.enabled {
+        return section.into();
+    }
+
+    // Trigger apps, including ones that aren't running right now
+    let label_for = |exe: &str| apps.iter()
+        .find(|a| a.exe == exe)
+        .map(|a| a.label.clone())
+        .unwrap_or_else(|| format!("{} (not running)", SessionKey::new(exe).app_name()));
+    for exe in &ducking.triggers {
+        let exe_owned = exe.clone();
+        section = section.push(
+            row![
+                text(label_for(exe)).size(13).width(Length::Fixed(200.0)),
+                button(text("✕").size(10))
+                    .on_press(with(&|s| s.triggers.retain(|t| *t != exe_owned)))
+                    .padding([2, 6]),
+            ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+        );
+    }
+    let candidates: Vec<AppChoice> = apps.iter()
+        .filter(|a| !ducking.triggers.contains(&a.exe))
+        .cloned()
+        .collect();
+    section = section.push(
+        pick_list(candidates, None::<AppChoice>, {
+            let with = with.clone();
+            move |a: AppChoice| with(&|s| s.triggers.push(a.exe.clone()))
+        })
+            .placeholder("+ add voice app…")
+            .text_size(13)
+            .width(Length::Fixed(200.0)),
+    );
+
+    section = section
+        .push(setting_row(
+            "Amount",
+            format!("{:.0} dB", ducking.amount_db),
+            slider(-40.0..=-3.0, ducking.amount_db, {
+                let with = with.clone();
+                move |v| with(&|s| s.amount_db = v)
+            }).step(1.0),
+        ))
+        .push(setting_row(
+            "Threshold",
+            format!("{:.0} dBFS", ducking.threshold_db),
+            slider(-60.0..=-10.0, ducking.threshold_db, {
+                let with = with.clone();
+                move |v| with(&|s| s.threshold_db = v)
+            }).step(1.0),
+        ))
+        .push(setting_row(
+            "Attack",
+            format!("{} ms", ducking.attack_ms),
+            slider(10.0..=1000.0, ducking.attack_ms as f32, {
+                let with = with.clone();
+                move |v| with(&|s| s.attack_ms = v as u32)
+            }).step(10.0),
+        ))
+        .push(setting_row(
+            "Release",
+            format!("{} ms", ducking.release_ms),
+            slider(100.0..=5000.0, ducking.release_ms as f32, {
+                let with = with.clone();
+                move |v| with(&|s| s.release_ms = v as u32)
+            }).step(50.0),
+        ))
+        .push(
+            row![
+                toggler(ducking.move_faders).on_toggle(move |on| with(&|s| s.move_faders = on)),
+                text("Move the faders with the duck").size(13),
+            ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+        );
+    section.into()
+}
+
+fn setting_row<'a>(
+    name: &'a str,
+    value: String,
+    control: impl Into<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    row![
+        text(name).size(13).width(Length::Fixed(80.0)),
+        container(control).width(Length::Fixed(220.0)),
+        text(value).size(12).width(Length::Fixed(70.0)),
+    ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+        .into()
+}
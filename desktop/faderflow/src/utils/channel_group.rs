@@ -0,0 +1,227 @@
+// Several sessions on one fader. The fader sets a group level (0.0–1.0)
+// and each member derives its own volume from it through a per-member trim,
+// so "all browsers" can move together while one of them stays quieter.
+
+use crate::audio::SessionKey;
+
+/// How a member's trim relates its volume to the group level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupMode {
+    /// volume = level × trim (trim 0.0–1.0): members keep their ratio and
+    /// all reach silence together
+    #[default]
+    Proportional,
+    /// volume = level + trim (trim -1.0–1.0): members keep a fixed distance
+    Offset,
+}
+
+impl GroupMode {
+    pub const ALL: [GroupMode; 2] = [GroupMode::Proportional, GroupMode::Offset];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GroupMode::Proportional => "proportional",
+            GroupMode::Offset => "offset",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == s)
+    }
+
+    /// Trim that makes a member follow the group level exactly
+    pub fn neutral_trim(self) -> f32 {
+        match self {
+            GroupMode::Proportional => 1.0,
+            GroupMode::Offset => 0.0,
+        }
+    }
+
+    /// Allowed trim values
+    pub fn trim_range(self) -> std::ops::RangeInclusive<f32> {
+        match self {
+            GroupMode::Proportional => 0.0..=1.0,
+            GroupMode::Offset => -1.0..=1.0,
+        }
+    }
+}
+
+impl std::fmt::Display for GroupMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GroupMode::Proportional => "Proportional",
+            GroupMode::Offset => "Offset",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupMember {
+    pub key: SessionKey,
+    pub trim: f32,
+}
+
+/// What one channel controls. Empty is an unassigned channel; the first
+/// member leads: its volume is what the fader shows.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ChannelGroup {
+    pub members: Vec<GroupMember>,
+    pub mode: GroupMode,
+}
+
+impl ChannelGroup {
+    pub fn single(key: SessionKey) -> Self {
+        let mut group = Self::default();
+        group.add(key);
+        group
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn lead(&self) -> Option<&SessionKey> {
+        self.members.first().map(|m| &m.key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &SessionKey> {
+        self.members.iter().map(|m| &m.key)
+    }
+
+    pub fn contains(&self, key: &SessionKey) -> bool {
+        self.keys().any(|k| k == key)
+    }
+
+    /// Add a member following the group level exactly; no-op if present.
+    pub fn add(&mut self, key: SessionKey) {
+        if self.contains(&key) { return; }
+        self.members.push(GroupMember { key, trim: self.mode.neutral_trim() });
+    }
+
+    pub fn remove(&mut self, key: &SessionKey) {
+        self.members.retain(|m| &m.key != key);
+    }
+
+    /// Switch modes; trims mean something else afterwards, so they reset.
+    pub fn set_mode(&mut self, mode: GroupMode) {
+        if mode == self.mode { return; }
+        self.mode = mode;
+        for m in &mut self.members {
+            m.trim = mode.neutral_trim();
+        }
+    }
+
+    pub fn set_trim(&mut self, key: &SessionKey, trim: f32) {
+        let range = self.mode.trim_range();
+        if let Some(m) = self.members.iter_mut().find(|m| &m.key == key) {
+            m.trim = trim.clamp(*range.start(), *range.end());
+        }
+    }
+
+    /// Volume of every member at a group level.
+    pub fn volumes_at(&self, level: f32) -> impl Iterator<Item = (&SessionKey, f32)> {
+        let mode = self.mode;
+        self.members.iter().map(move |m| {
+            let v = match mode {
+                GroupMode::Proportional => level * m.trim,
+                GroupMode::Offset => level + m.trim,
+            };
+            (&m.key, v.clamp(0.0, 1.0))
+        })
+    }
+
+    /// Group level that gives the lead `volume`, or None when the lead's
+    /// trim can't produce it (a zero proportional trim).
+    pub fn level_for_lead(&self, volume: f32) -> Option<f32> {
+        let lead = self.members.first()?;
+        let level = match self.mode {
+            GroupMode::Proportional => {
+                if lead.trim <= 0.0 { return None; }
+                volume / lead.trim
+            }
+            GroupMode::Offset => volume - lead.trim,
+        };
+        Some(level.clamp(0.0, 1.0))
+    }
+
+    /// Swap a member's key for another, keeping its trim and position.
+    pub fn rekey(&mut self, old: &SessionKey, new: SessionKey) {
+        if let Some(m) = self.members.iter_mut().find(|m| &m.key == old) {
+            m.key = new;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(mode: GroupMode, members: &[(&str, f32)]) -> ChannelGroup {
+        ChannelGroup {
+            members: members.iter()
+                .map(|&(exe, trim)| GroupMember { key: SessionKey::new(exe), trim })
+                .collect(),
+            mode,
+        }
+    }
+
+    fn volumes(group: &ChannelGroup, level: f32) -> Vec<f32> {
+        group.volumes_at(level).map(|(_, v)| v).collect()
+    }
+
+    #[test]
+    fn members_follow_the_level() {
+        let g = group(GroupMode::Proportional, &[("a", 1.0), ("b", 0.5)]);
+        assert_eq!(volumes(&g, 0.8), [0.8, 0.4]);
+        assert_eq!(volumes(&g, 0.0), [0.0, 0.0]);
+
+        // Offsets are clamped to the volume range
+        let g = group(GroupMode::Offset, &[("a", 0.0), ("b", -0.25), ("c", 0.25)]);
+        assert_eq!(volumes(&g, 0.125), [0.125, 0.0, 0.375]);
+        assert_eq!(volumes(&g, 0.875), [0.875, 0.625, 1.0]);
+    }
+
+    #[test]
+    fn level_comes_back_from_the_lead() {
+        let g = group(GroupMode::Proportional, &[("a", 0.5), ("b", 1.0)]);
+        assert_eq!(g.level_for_lead(0.25), Some(0.5));
+        assert_eq!(g.level_for_lead(0.9), Some(1.0));
+
+        let g = group(GroupMode::Offset, &[("a", -0.25)]);
+        assert_eq!(g.level_for_lead(0.5), Some(0.75));
+        assert_eq!(g.level_for_lead(0.0), Some(0.25));
+
+        // A silenced lead can't tell where the group is
+        let g = group(GroupMode::Proportional, &[("a", 0.0), ("b", 1.0)]);
+        assert_eq!(g.level_for_lead(0.5), None);
+        assert_eq!(ChannelGroup::default().level_for_lead(0.5), None);
+    }
+
+    #[test]
+    fn trims_are_clamped_to_the_mode() {
+        let mut g = group(GroupMode::Proportional, &[("a", 1.0)]);
+        let a = SessionKey::new("a");
+        g.set_trim(&a, 1.5);
+        assert_eq!(g.members[0].trim, 1.0);
+        g.set_trim(&a, -0.5);
+        assert_eq!(g.members[0].trim, 0.0);
+
+        g.set_mode(GroupMode::Offset);
+        g.set_trim(&a, -0.5);
+        assert_eq!(g.members[0].trim, -0.5);
+        g.set_trim(&a, -2.0);
+        assert_eq!(g.members[0].trim, -1.0);
+    }
+
+    #[test]
+    fn switching_mode_resets_trims() {
+        let mut g = group(GroupMode::Proportional, &[("a", 0.3), ("b", 0.6)]);
+        g.set_mode(GroupMode::Offset);
+        assert!(g.members.iter().all(|m| m.trim == 0.0));
+        g.set_trim(&SessionKey::new("b"), 0.2);
+        g.set_mode(GroupMode::Offset);
+        assert_eq!(g.members[1].trim, 0.2);
+        g.set_mode(GroupMode::Proportional);
+        assert!(g.members.iter().all(|m| m.trim == 1.0));
+    }
+}
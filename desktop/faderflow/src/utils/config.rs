@@ -1,15 +1,13 @@
 use std::collections::HashMap;
-use std::io::Write;
 
-use serialport::SerialPort;
+use crate::comms::worker::DeviceIo;
 
-use crate::comms::device_info::DeviceInfo;
-use crate::comms::protocol::{
-    CMD_DISPLAY_UPDATE_APP_NAME, CMD_DISPLAY_UPDATE_APP_VOLUME,
-    CMD_DISPLAY_UPDATE_ICON,
-    CMD_CALIBRATION_START, CMD_CALIBRATION_CANCEL,
-    DisplayUpdateAppCommand, DisplayUpdateVolumeCommand,
-};
+use crate::audio::{DuckingSettings, Grouping, SessionKey, SessionKind};
+use crate::comms::device_info::{DeviceInfo, EncoderMode};
+use crate::utils::channel_group::{ChannelGroup, GroupMode};
+use crate::utils::taper::{Taper, DEFAULT_FLOOR_DB};
+use crate::comms::codec::{HostPacket, ICON_BYTES, ICON_RLE_MAX};
+use crate::comms::protocol::METER_CHANNELS;
 
 // ── Renames ───────────────────────────────────────────────────────────────────
 
@@ -34,26 +32,102 @@ pub fn load_device_renames() -> HashMap<String, String> {
 
 // ── Channel assignments ───────────────────────────────────────────────────────
 
+// Each channel is a table { exe, instance?, stream?, kind? }; an empty table is an
+// unassigned channel. Configs from before session keys stored the display
+// name as a plain string — those load as legacy keys and get rewritten once
+// they resolve against a live session.
+//
+// A channel controlling a group is { mode, members = [{ exe, …, trim }, …] }
+// instead. A lone member at the neutral trim is written the plain way.
+
+fn session_key_table(key: &SessionKey) -> toml::map::Map<String, toml::Value> {
+    let mut t = toml::map::Map::new();
+    t.insert("exe".into(), toml::Value::String(key.exe.clone()));
+    if let Some(pid) = key.instance {
+        t.insert("instance".into(), toml::Value::Integer(pid as i64));
+    }
+    if let Some(stream) = &key.stream {
+        t.insert("stream".into(), toml::Value::String(stream.clone()));
+    }
+    if key.kind != SessionKind::Playback {
+        t.insert("kind".into(), toml::Value::String(key.kind.as_str().into()));
+    }
+    t
+}
+
+fn channel_group_to_toml(group: &ChannelGroup) -> toml::Value {
+    let plain = group.mode == GroupMode::default()
+        && group.members.len() <= 1
+        && group.members.iter().all(|m| m.trim == group.mode.neutral_trim());
+    if plain {
+        return toml::Value::Table(group.lead().map(session_key_table).unwrap_or_default());
+    }
+    let members = group.members.iter()
+        .map(|m| {
+            let mut t = session_key_table(&m.key);
+            t.insert("trim".into(), toml::Value::Float(m.trim as f64));
+            toml::Value::Table(t)
+        })
+        .collect();
+    let mut t = toml::map::Map::new();
+    t.insert("mode".into(), toml::Value::String(group.mode.as_str().into()));
+    t.insert("members".into(), toml::Value::Array(members));
+    toml::Value::Table(t)
+}
+
+fn channel_group_from_toml(v: toml::Value) -> ChannelGroup {
+    let toml::Value::Table(mut t) = v else {
+        return session_key_from_toml(v).map(ChannelGroup::single).unwrap_or_default();
+    };
+    let Some(toml::Value::Array(members)) = t.remove("members") else {
+        return session_key_from_toml(toml::Value::Table(t)).map(ChannelGroup::single).unwrap_or_default();
+    };
+    let mode = t.get("mode").and_then(|v| v.as_str()).and_then(GroupMode::parse).unwrap_or_default();
+    let mut group = ChannelGroup { members: Vec::new(), mode };
+    for m in members {
+        let trim = m.get("trim").and_then(toml_f32).unwrap_or(mode.neutral_trim());
+        let Some(key) = session_key_from_toml(m) else { continue };
+        if group.contains(&key) { continue; }
+        group.add(key.clone());
+        group.set_trim(&key, trim);
+    }
+    group
+}
+
+fn session_key_from_toml(v: toml::Value) -> Option<SessionKey> {
+    match v {
+        toml::Value::String(s) if !s.is_empty() => Some(SessionKey::new(s)),
+        toml::Value::Table(t) => {
+            let mut key = SessionKey::new(t.get("exe")?.as_str()?);
+            key.instance = t.get("instance").and_then(|v| v.as_integer()).map(|p| p as u32);
+            key.stream = t.get("stream").and_then(|v| v.as_str()).map(str::to_string);
+            key.kind = t.get("kind").and_then(|v| v.as_str()).and_then(SessionKind::parse).unwrap_or_default();
+            Some(key)
+        }
+        _ => None,
+    }
+}
+
 pub fn save_device_assignments(devices: &[DeviceInfo]) {
     let mut table = toml::map::Map::new();
     for dev in devices {
         let arr: Vec<toml::Value> = dev.channel_assignments.iter()
-            .map(|s| toml::Value::String(s.clone()))
+            .map(channel_group_to_toml)
             .collect();
         table.insert(DeviceInfo::uuid_str(&dev.uuid), toml::Value::Array(arr));
     }
     save_section("assignments", table);
 }
 
-pub fn load_device_assignments() -> HashMap<String, [String; 5]> {
+pub fn load_device_assignments() -> HashMap<String, [ChannelGroup; 5]> {
     load_section("assignments")
         .and_then(|v| if let toml::Value::Table(t) = v { Some(t) } else { None })
         .map(|t| t.into_iter()
             .filter_map(|(k, v)| {
                 if let toml::Value::Array(arr) = v {
-                    let mut slots: [String; 5] = Default::default();
+                    let mut slots: [ChannelGroup; 5] = Default::default();
                     for (i, val) in arr.into_iter().take(5).enumerate() {
-                        if let toml::Value::String(s) = val { slots[i] = s; }
+                        slots[i] = channel_group_from_toml(val);
                     }
                     Some((k, slots))
                 } else {
@@ -64,42 +138,234 @@ pub fn load_device_assignments() -> HashMap<String, [String; 5]> {
         .unwrap_or_default()
 }
 
+// ── Volume tapers ─────────────────────────────────────────────────────────────
+
+// Per device, one table per channel: { curve = "linear" | "audio" | "db" |
+// "custom" }, with floor_db (negative) for "db" and points = [[pos, vol], ...]
+// for "custom". Anything that doesn't parse loads as linear.
+
+fn taper_to_toml(taper: &Taper) -> toml::Value {
+    let mut t = toml::map::Map::new();
+    t.insert("curve".into(), toml::Value::String(taper.as_str().into()));
+    match taper {
+        Taper::Decibel { floor_db } => {
+            t.insert("floor_db".into(), toml::Value::Float(*floor_db as f64));
+        }
+        Taper::Custom(points) => {
+            let points = points.iter()
+                .map(|&(p, v)| toml::Value::Array(vec![
+                    toml::Value::Float(p as f64),
+                    toml::Value::Float(v as f64),
+                ]))
+                .collect();
+            t.insert("points".into(), toml::Value::Array(points));
+        }
+        Taper::Linear | Taper::Audio => {}
+    }
+    toml::Value::Table(t)
+}
+
+fn toml_f32(v: &toml::Value) -> Option<f32> {
+    v.as_float().or_else(|| v.as_integer().map(|i| i as f64)).map(|f| f as f32)
+}
+
+fn taper_from_toml(v: &toml::Value) -> Option<Taper> {
+    let t = v.as_table()?;
+    match t.get("curve")?.as_str()? {
+        "linear" => Some(Taper::Linear),
+        "audio" => Some(Taper::Audio),
+        "db" => {
+            let floor_db = t.get("floor_db").and_then(toml_f32).unwrap_or(DEFAULT_FLOOR_DB);
+            (floor_db < 0.0).then_some(Taper::Decibel { floor_db })
+        }
+        "custom" => {
+            let points = t.get("points")?.as_array()?.iter()
+                .map(|p| {
+                    let pair = p.as_array()?;
+                    Some((toml_f32(pair.first()?)?, toml_f32(pair.get(1)?)?))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Taper::custom(points)
+        }
+        _ => None,
+    }
+}
+
+pub fn save_device_tapers(devices: &[DeviceInfo]) {
+    let mut table = toml::map::Map::new();
+    for dev in devices {
+        let arr: Vec<toml::Value> = dev.channel_tapers.iter().map(taper_to_toml).collect();
+        table.insert(DeviceInfo::uuid_str(&dev.uuid), toml::Value::Array(arr));
+    }
+    save_section("tapers", table);
+}
+
+pub fn load_device_tapers() -> HashMap<String, [Taper; 5]> {
+    load_section("tapers")
+        .and_then(|v| if let toml::Value::Table(t) = v { Some(t) } else { None })
+        .map(|t| t.into_iter()
+            .filter_map(|(k, v)| {
+                let toml::Value::Array(arr) = v else { return None };
+                let mut slots: [Taper; 5] = Default::default();
+                for (i, val) in arr.iter().take(5).enumerate() {
+                    slots[i] = taper_from_toml(val).unwrap_or_default();
+                }
+                Some((k, slots))
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+// ── Encoder modes ─────────────────────────────────────────────────────────────
+
+pub fn save_encoder_modes(devices: &[DeviceInfo]) {
+    let mut table = toml::map::Map::new();
+    for dev in devices {
+        let arr: Vec<toml::Value> = dev.encoder_modes.iter()
+            .map(|m| toml::Value::String(m.as_str().into()))
+            .collect();
+        table.insert(DeviceInfo::uuid_str(&dev.uuid), toml::Value::Array(arr));
+    }
+    save_section("encoders", table);
+}
+
+pub fn load_encoder_modes() -> HashMap<String, [EncoderMode; 5]> {
+    load_section("encoders")
+        .and_then(|v| if let toml::Value::Table(t) = v { Some(t) } else { None })
+        .map(|t| t.into_iter()
+            .filter_map(|(k, v)| {
+                let toml::Value::Array(arr) = v else { return None };
+                let mut slots = [EncoderMode::default(); 5];
+                for (i, val) in arr.iter().take(5).enumerate() {
+                    slots[i] = val.as_str().and_then(EncoderMode::parse).unwrap_or_default();
+                }
+                Some((k, slots))
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+// ── Session grouping ──────────────────────────────────────────────────────────
+
+pub fn save_grouping_rules(rules: &HashMap<String, Grouping>) {
+    let mut table = toml::map::Map::new();
+    for (exe, grouping) in rules {
+        table.insert(exe.clone(), toml::Value::String(grouping.as_str().into()));
+    }
+    save_section("grouping", table);
+}
+
+pub fn load_grouping_rules() -> HashMap<String, Grouping> {
+    load_section("grouping")
+        .and_then(|v| if let toml::Value::Table(t) = v { Some(t) } else { None })
+        .map(|t| t.into_iter()
+            .filter_map(|(k, v)| Some((k, Grouping::parse(v.as_str()?)?)))
+            .collect())
+        .unwrap_or_default()
+}
+
+// ── Output routes ─────────────────────────────────────────────────────────────
+
+// exe → output device name, for apps routed away from the default output
+
+pub fn save_routes(routes: &HashMap<String, String>) {
+    let mut table = toml::map::Map::new();
+    for (exe, device) in routes {
+        table.insert(exe.clone(), toml::Value::String(device.clone()));
+    }
+    save_section("routes", table);
+}
+
+pub fn load_routes() -> HashMap<String, String> {
+    load_section("routes")
+        .and_then(|v| if let toml::Value::Table(t) = v { Some(t) } else { None })
+        .map(|t| t.into_iter()
+            .filter_map(|(k, v)| if let toml::Value::String(s) = v { Some((k, s)) } else { None })
+            .collect())
+        .unwrap_or_default()
+}
+
+// ── Ducking ───────────────────────────────────────────────────────────────────
+
+pub fn save_ducking(settings: &DuckingSettings) {
+    let mut table = toml::map::Map::new();
+    table.insert("enabled".into(), toml::Value::Boolean(settings.enabled));
+    table.insert("triggers".into(), toml::Value::Array(
+        settings.triggers.iter().cloned().map(toml::Value::String).collect(),
+    ));
+    table.insert("amount_db".into(), toml::Value::Float(settings.amount_db as f64));
+    table.insert("threshold_db".into(), toml::Value::Float(settings.threshold_db as f64));
+    table.insert("attack_ms".into(), toml::Value::Integer(settings.attack_ms as i64));
+    table.insert("release_ms".into(), toml::Value::Integer(settings.release_ms as i64));
+    table.insert("move_faders".into(), toml::Value::Boolean(settings.move_faders));
+    save_section("ducking", table);
+}
+
+pub fn load_ducking() -> DuckingSettings {
+    let defaults = DuckingSettings::default();
+    let Some(toml::Value::Table(t)) = load_section("ducking") else { return defaults };
+    let ms = |key: &str, default: u32| t.get(key)
+        .and_then(|v| v.as_integer())
+        .map_or(default, |i| i.clamp(0, 60_000) as u32);
+    DuckingSettings {
+        enabled: t.get("enabled").and_then(|v| v.as_bool()).unwrap_or(defaults.enabled),
+        triggers: t.get("triggers")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default(),
+        amount_db: t.get("amount_db").and_then(toml_f32).map_or(defaults.amount_db, |db| db.min(0.0)),
+        threshold_db: t.get("threshold_db").and_then(toml_f32).unwrap_or(defaults.threshold_db),
+        attack_ms: ms("attack_ms", defaults.attack_ms),
+        release_ms: ms("release_ms", defaults.release_ms),
+        move_faders: t.get("move_faders").and_then(|v| v.as_bool()).unwrap_or(defaults.move_faders),
+    }
+}
+
 // ── Serial send helpers ───────────────────────────────────────────────────────
 
-pub fn send_app_name(port: &mut dyn SerialPort, channel: u8, name: &str) {
-    let mut cmd = DisplayUpdateAppCommand {
-        cmd: CMD_DISPLAY_UPDATE_APP_NAME,
-        channel,
-        name: [0; 64],
-    };
-    let bytes = name.as_bytes();
-    let len = bytes.len().min(63);
-    cmd.name[..len].copy_from_slice(&bytes[..len]);
-
-    let raw = unsafe {
-        std::slice::from_raw_parts(
-            &cmd as *const _ as *const u8,
-            std::mem::size_of::<DisplayUpdateAppCommand>(),
-        )
-    };
-    let _ = port.write_all(raw);
-    let _ = port.flush();
+pub fn send_app_name(io: &DeviceIo, channel: u8, name: &str) {
+    io.send(HostPacket::AppName { channel, name: name.to_string() });
 }
 
-pub fn send_volume(port: &mut dyn SerialPort, channel: u8, volume: u8) {
-    let cmd = DisplayUpdateVolumeCommand {
-        cmd: CMD_DISPLAY_UPDATE_APP_VOLUME,
-        channel,
-        volume: volume.min(100),
-    };
-    let raw = unsafe {
-        std::slice::from_raw_parts(
-            &cmd as *const _ as *const u8,
-            std::mem::size_of::<DisplayUpdateVolumeCommand>(),
-        )
-    };
-    let _ = port.write_all(raw);
-    let _ = port.flush();
+pub fn send_volume(io: &DeviceIo, channel: u8, volume: u8) {
+    io.send(HostPacket::Volume { channel, volume });
+}
+
+pub fn send_meters(io: &DeviceIo, levels: [u8; METER_CHANNELS]) {
+    io.send(HostPacket::Meters(levels));
+}
+
+/// Balance to show on a channel's display, -100 (left) to 100 (right).
+pub fn send_balance(io: &DeviceIo, channel: u8, balance: i8) {
+    io.send(HostPacket::Balance { channel, balance });
+}
+
+pub fn send_mute(io: &DeviceIo, channel: u8, muted: bool) {
+    io.send(HostPacket::Mute { channel, muted });
+}
+
+pub fn send_encoder_mode(io: &DeviceIo, channel: u8, mode: EncoderMode) {
+    io.send(HostPacket::EncoderMode { channel, mode });
+}
+
+pub fn send_icon(io: &DeviceIo, channel: u8, rgb565: &[u8]) {
+    if rgb565.len() != ICON_BYTES { return; }
+    // ~0.75s on the wire; the device's worker sends it after any control traffic
+    io.send(HostPacket::Icon { channel, rgb565: rgb565.to_vec() });
+}
+
+/// Runs from `rgb565_to_rle_icon`; the firmware decodes them as they arrive.
+pub fn send_icon_rle(io: &DeviceIo, channel: u8, runs: Vec<u8>) {
+    if runs.len() > ICON_RLE_MAX { return; }
+    io.send(HostPacket::IconRle { channel, runs });
+}
+
+pub fn send_calibration_start(io: &DeviceIo) {
+    io.send(HostPacket::CalibrationStart);
+}
+
+pub fn send_calibration_cancel(io: &DeviceIo) {
+    io.send(HostPacket::CalibrationCancel);
 }
 
 // ── Internal ──────────────────────────────────────────────────────────────────
@@ -134,20 +400,51 @@ fn save_section(section: &str, value: toml::map::Map<String, toml::Value>) {
     save_full(full);
 }
 
-pub fn send_icon(port: &mut dyn SerialPort, channel: u8, rgb565: &[u8]) {
-    if rgb565.len() != 64 * 64 * 2 { return; }
-    let _ = port.write_all(&[CMD_DISPLAY_UPDATE_ICON, channel]);
-    let _ = port.write_all(rgb565);  // UART paces this (~0.75s)
-    let _ = port.flush();
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    fn groups(text: &str) -> Vec<ChannelGroup> {
+        let value: toml::Value = toml::from_str(text).unwrap();
+        value["channels"].as_array().unwrap().iter().cloned().map(channel_group_from_toml).collect()
+    }
 
-pub fn send_calibration_start(port: &mut dyn SerialPort) {
-    let _ = port.write_all(&[CMD_CALIBRATION_START]);
-    let _ = port.flush();
-}
+    #[test]
+    fn old_assignments_load_as_single_groups() {
+        // Name-keyed strings from before session keys, "" for a free channel
+        let loaded = groups(r#"channels = ["Spotify", "", "firefox", "", ""]"#);
+        assert_eq!(loaded.len(), 5);
+        assert_eq!(loaded[0], ChannelGroup::single(SessionKey::new("Spotify")));
+        assert!(loaded[1].is_empty());
+        assert_eq!(loaded[2].lead(), Some(&SessionKey::new("firefox")));
+        assert_eq!(loaded[2].mode, GroupMode::Proportional);
+        assert_eq!(loaded[2].members[0].trim, 1.0);
+    }
+
+    #[test]
+    fn key_tables_and_groups_load() {
+        let loaded = groups(r#"channels = [
+            { exe = "/usr/bin/game", instance = 42 },
+            { mode = "offset", members = [
+                { exe = "/usr/bin/firefox", trim = 0.0 },
+                { exe = "/usr/bin/chromium", trim = -0.25 },
+                { exe = "/usr/bin/chromium", trim = 0.5 },
+            ] },
+        ]"#);
+        assert_eq!(loaded[0].lead().and_then(|k| k.instance), Some(42));
+        assert_eq!(loaded[1].mode, GroupMode::Offset);
+        // Duplicates keep the first entry
+        assert_eq!(loaded[1].members.len(), 2);
+        assert_eq!(loaded[1].members[1].trim, -0.25);
+    }
 
-pub fn send_calibration_cancel(port: &mut dyn SerialPort) {
-    let _ = port.write_all(&[CMD_CALIBRATION_CANCEL]);
-    let _ = port.flush();
-}
\ No newline at end of file
+    #[test]
+    fn groups_survive_a_round_trip() {
+        let mut group = ChannelGroup::single(SessionKey::new("/usr/bin/firefox"));
+        group.add(SessionKey::new("/usr/bin/chromium"));
+        group.set_trim(&SessionKey::new("/usr/bin/chromium"), 0.4);
+        for group in [group, ChannelGroup::single(SessionKey::new("mpv")), ChannelGroup::default()] {
+            assert_eq!(channel_group_from_toml(channel_group_to_toml(&group)), group);
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
 #[cfg(target_os = "windows")]
 pub fn extract_icon_rgba(exe_path: &str) -> Option<(u32, u32, Vec<u8>)> {
     use windows::Win32::UI::Shell::*;
@@ -101,9 +104,34 @@ pub fn extract_icon_rgba(_exe_path: &str) -> Option<(u32, u32, Vec<u8>)> {
     None
 }
 
-pub fn extract_icon_to_handle(exe_path: &str) -> Option<iced::widget::image::Handle> {
-    extract_icon_rgba(exe_path)
-        .map(|(w, h, rgba)| iced::widget::image::Handle::from_rgba(w, h, rgba))
+/// An executable's icon, extracted once and shared by the UI and the
+/// device icon upload.
+#[derive(Clone)]
+pub struct CachedIcon {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Arc<Vec<u8>>,
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub handle: Arc<iced::widget::image::Handle>,
+}
+
+static ICON_CACHE: OnceLock<Mutex<HashMap<String, Option<CachedIcon>>>> = OnceLock::new();
+
+/// Icon for `exe_path`, extracting it on first use. Misses are cached too,
+/// so exes without an icon aren't re-read on every session refresh.
+pub fn cached_icon(exe_path: &str) -> Option<CachedIcon> {
+    let cache = ICON_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(hit) = cache.lock().ok()?.get(exe_path) {
+        return hit.clone();
+    }
+    let icon = extract_icon_rgba(exe_path).map(|(width, height, rgba)| CachedIcon {
+        width,
+        height,
+        handle: Arc::new(iced::widget::image::Handle::from_rgba(width, height, rgba.clone())),
+        rgba: Arc::new(rgba),
+    });
+    cache.lock().ok()?.insert(exe_path.to_string(), icon.clone());
+    icon
 }
 
 /// Nearest-neighbor resize to 64x64, alpha-blend onto the device icon
@@ -130,4 +158,120 @@ pub fn rgba_to_rgb565_icon(w: u32, h: u32, rgba: &[u8]) -> Vec<u8> {
         }
     }
     out
-}
\ No newline at end of file
+}
+
+/// Run-length encode `rgba_to_rgb565_icon` output for
+/// CMD_DISPLAY_UPDATE_ICON_RLE. Each run starts with a control byte c:
+/// with the top bit set, the next pixel repeats (c & 0x7F) + 2 times;
+/// otherwise c + 1 literal pixels follow. Pixels stay big-endian.
+pub fn rgb565_to_rle_icon(rgb565: &[u8]) -> Vec<u8> {
+    const MAX_REPEAT: usize = 0x7F + 2;
+    const MAX_LITERAL: usize = 0x7F + 1;
+
+    fn flush_literal(px: &[&[u8]], out: &mut Vec<u8>) {
+        for chunk in px.chunks(MAX_LITERAL) {
+            out.push((chunk.len() - 1) as u8);
+            chunk.iter().for_each(|p| out.extend_from_slice(p));
+        }
+    }
+
+    let px: Vec<&[u8]> = rgb565.chunks_exact(2).collect();
+    let mut out = Vec::new();
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i < px.len() {
+        let run = px[i..].iter().take(MAX_REPEAT).take_while(|p| **p == px[i]).count();
+        if run < 2 {
+            i += 1;
+            continue;
+        }
+        flush_literal(&px[literal_start..i], &mut out);
+        out.push(0x80 | (run - 2) as u8);
+        out.extend_from_slice(px[i]);
+        i += run;
+        literal_start = i;
+    }
+    flush_literal(&px[literal_start..], &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comms::codec::{ICON_BYTES, ICON_RLE_MAX, ICON_SIZE};
+
+    /// Straightforward decoder to check the encoder against; the firmware's
+    /// streaming one follows the same rules.
+    fn rle_decode(mut runs: &[u8]) -> Option<Vec<u8>> {
+        let mut out = Vec::new();
+        while let Some((&c, rest)) = runs.split_first() {
+            if c & 0x80 != 0 {
+                let px = rest.get(..2)?;
+                for _ in 0..(c & 0x7F) as usize + 2 { out.extend_from_slice(px); }
+                runs = &rest[2..];
+            } else {
+                let len = (c as usize + 1) * 2;
+                out.extend_from_slice(rest.get(..len)?);
+                runs = &rest[len..];
+            }
+        }
+        Some(out)
+    }
+
+    fn round_trip(rgb565: &[u8]) -> usize {
+        let runs = rgb565_to_rle_icon(rgb565);
+        assert_eq!(rle_decode(&runs).as_deref(), Some(rgb565));
+        runs.len()
+    }
+
+    /// A filled circle on a transparent background, like most app icons
+    fn circle_rgba() -> Vec<u8> {
+        let size = ICON_SIZE as i32;
+        (0..size * size)
+            .flat_map(|i| {
+                let (x, y) = (i % size - size / 2, i / size - size / 2);
+                if x * x + y * y < 24 * 24 { [30, 144, 255, 255] } else { [0, 0, 0, 0] }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flat_icons_shrink_a_lot() {
+        let solid = rgba_to_rgb565_icon(1, 1, &[200, 50, 10, 255]);
+        assert!(round_trip(&solid) <= 3 * ICON_BYTES.div_ceil(2 * 129));
+
+        let circle = rgba_to_rgb565_icon(ICON_SIZE as u32, ICON_SIZE as u32, &circle_rgba());
+        assert!(round_trip(&circle) < ICON_BYTES / 8);
+    }
+
+    #[test]
+    fn noise_round_trips_with_little_overhead() {
+        // xorshift; no two neighbours alike often enough to matter
+        let mut state = 0x2545_F491u32;
+        let rgba: Vec<u8> = (0..ICON_BYTES * 2)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8 | 0x01
+            })
+            .collect();
+        let noise = rgba_to_rgb565_icon(ICON_SIZE as u32, ICON_SIZE as u32, &rgba);
+        assert!(round_trip(&noise) <= ICON_RLE_MAX);
+    }
+
+    #[test]
+    fn runs_split_at_their_limits() {
+        for len in [1, 2, 3, 128, 129, 130, 131, 257, 258, 259] {
+            let mut px = [0x12, 0x34].repeat(len);
+            round_trip(&px);
+            // Literals of the same lengths
+            px = (0..len as u16).flat_map(|i| i.to_be_bytes()).collect();
+            round_trip(&px);
+        }
+        // Alternating short runs and singles
+        let px: Vec<u8> = (0..600u16).flat_map(|i| ((i / 3) * (i % 2)).to_be_bytes()).collect();
+        round_trip(&px);
+        assert!(rgb565_to_rle_icon(&[]).is_empty());
+    }
+}
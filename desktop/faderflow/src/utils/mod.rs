@@ -1,2 +1,4 @@
 pub mod icon;
-pub mod config;
\ No newline at end of file
+pub mod config;
+pub mod taper;
+pub mod channel_group;
\ No newline at end of file
@@ -0,0 +1,160 @@
+// Fader position ↔ volume curves. Positions and volumes are both 0.0–1.0;
+// every curve maps 0 to 0 and 1 to 1 and is monotonic, so it can be run
+// backwards to put a motorised fader where a volume belongs.
+
+/// Bottom of the dB taper unless configured otherwise
+pub const DEFAULT_FLOOR_DB: f32 = -60.0;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Taper {
+    /// Position is the volume
+    #[default]
+    Linear,
+    /// Logarithmic: the lower travel climbs quickly, leaving most of the
+    /// fader for fine control near the top (half travel ≈ 74%)
+    Audio,
+    /// Linear in dB from `floor_db` at the bottom to 0 dB at the top; the
+    /// very bottom is silence
+    Decibel { floor_db: f32 },
+    /// Piecewise linear through (position, volume) breakpoints, sorted by
+    /// position with volumes never decreasing
+    Custom(Vec<(f32, f32)>),
+}
+
+impl Taper {
+    /// Choices offered in the UI; custom curves only come from the config file
+    pub const PRESETS: [Taper; 3] = [
+        Taper::Linear,
+        Taper::Audio,
+        Taper::Decibel { floor_db: DEFAULT_FLOOR_DB },
+    ];
+
+    /// Volume for a fader position.
+    pub fn volume_at(&self, position: f32) -> f32 {
+        let p = position.clamp(0.0, 1.0);
+        let v = match self {
+            Taper::Linear => p,
+            Taper::Audio => (1.0 + 9.0 * p).log10(),
+            Taper::Decibel { floor_db } => {
+                if p <= 0.0 { 0.0 } else { 10f32.powf(floor_db * (1.0 - p) / 20.0) }
+            }
+            Taper::Custom(points) => interpolate(points.iter().copied(), p),
+        };
+        v.clamp(0.0, 1.0)
+    }
+
+    /// Fader position that produces `volume`; the inverse of `volume_at`.
+    pub fn position_for(&self, volume: f32) -> f32 {
+        let v = volume.clamp(0.0, 1.0);
+        let p = match self {
+            Taper::Linear => v,
+            Taper::Audio => (10f32.powf(v) - 1.0) / 9.0,
+            Taper::Decibel { floor_db } => {
+                if v <= 0.0 { 0.0 } else { 1.0 - 20.0 * v.log10() / floor_db }
+            }
+            Taper::Custom(points) => interpolate(points.iter().map(|&(p, v)| (v, p)), v),
+        };
+        p.clamp(0.0, 1.0)
+    }
+
+    /// Build a custom curve, or None if the points can't form one. The 0 and
+    /// 1 ends are added when missing.
+    pub fn custom(mut points: Vec<(f32, f32)>) -> Option<Taper> {
+        if points.iter().any(|&(p, v)| !(0.0..=1.0).contains(&p) || !(0.0..=1.0).contains(&v)) {
+            return None;
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if points.first().is_none_or(|&(p, _)| p > 0.0) { points.insert(0, (0.0, 0.0)); }
+        if points.last().is_some_and(|&(p, _)| p < 1.0) { points.push((1.0, 1.0)); }
+        if points.windows(2).any(|w| w[1].1 < w[0].1) { return None; }
+        Some(Taper::Custom(points))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Taper::Linear => "linear",
+            Taper::Audio => "audio",
+            Taper::Decibel { .. } => "db",
+            Taper::Custom(_) => "custom",
+        }
+    }
+}
+
+impl std::fmt::Display for Taper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Taper::Linear => f.write_str("Linear"),
+            Taper::Audio => f.write_str("Audio (log)"),
+            Taper::Decibel { floor_db } => write!(f, "dB ({floor_db:.0} dB floor)"),
+            Taper::Custom(points) => write!(f, "Custom ({} points)", points.len()),
+        }
+    }
+}
+
+/// Piecewise-linear lookup over points sorted by x. On a flat stretch of x
+/// (possible when a custom curve is run backwards) the first match wins.
+fn interpolate(points: impl Iterator<Item = (f32, f32)>, x: f32) -> f32 {
+    let mut prev: Option<(f32, f32)> = None;
+    for (px, py) in points {
+        if x <= px {
+            return match prev {
+                Some((qx, qy)) if px > qx => qy + (py - qy) * (x - qx) / (px - qx),
+                _ => py,
+            };
+        }
+        prev = Some((px, py));
+    }
+    prev.map_or(x, |(_, py)| py)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn presets_run_backwards() {
+        let custom = Taper::custom(vec![(0.25, 0.5), (0.75, 0.9)]).unwrap();
+        for taper in Taper::PRESETS.iter().chain([&custom]) {
+            for step in 0..=100 {
+                let p = step as f32 / 100.0;
+                let back = taper.position_for(taper.volume_at(p));
+                assert!(close(back, p), "{taper}: {p} came back as {back}");
+            }
+        }
+    }
+
+    #[test]
+    fn ends_are_fixed() {
+        for taper in &Taper::PRESETS {
+            assert_eq!(taper.volume_at(0.0), 0.0, "{taper}");
+            assert!(close(taper.volume_at(1.0), 1.0), "{taper}");
+            assert_eq!(taper.position_for(0.0), 0.0, "{taper}");
+            assert!(close(taper.position_for(1.0), 1.0), "{taper}");
+            // Out of range clamps to the ends
+            assert_eq!(taper.volume_at(-0.5), 0.0, "{taper}");
+            assert!(close(taper.volume_at(1.5), 1.0), "{taper}");
+        }
+    }
+
+    #[test]
+    fn custom_curves_are_checked() {
+        assert_eq!(Taper::custom(vec![(0.2, 0.6), (0.8, 0.4)]), None);
+        assert_eq!(Taper::custom(vec![(1.2, 0.5)]), None);
+        assert_eq!(Taper::custom(vec![(0.5, -0.1)]), None);
+
+        // Unsorted input is sorted and the missing ends are added
+        assert_eq!(
+            Taper::custom(vec![(0.8, 0.9), (0.2, 0.5)]),
+            Some(Taper::Custom(vec![(0.0, 0.0), (0.2, 0.5), (0.8, 0.9), (1.0, 1.0)])),
+        );
+        assert_eq!(
+            Taper::custom(vec![(0.0, 0.1), (1.0, 0.8)]),
+            Some(Taper::Custom(vec![(0.0, 0.1), (1.0, 0.8)])),
+        );
+        assert_eq!(Taper::custom(vec![]), Some(Taper::Custom(vec![(0.0, 0.0), (1.0, 1.0)])));
+    }
+}
@@ -4,6 +4,7 @@
 //
 
 #include "channel.h"
+#include "protocol.h"
 
 Channel::Channel(
   uint8_t id,
@@ -22,6 +23,13 @@ Channel::Channel(
   faderChanged = false;
   displayDirty = false;
   lastDisplayDraw = 0;
+  meterLevel = 0;
+  meterDirty = false;
+  lastMeterDraw = 0;
+  balanceMode = false;
+  balance = 0;
+  balanceChanged = false;
+  balanceDirty = false;
 }
 
 void Channel::begin() {
@@ -40,7 +48,16 @@ void Channel::update() {
   fader.update();
 
   int delta = encoder.getDelta();
-  if (delta != 0) {
+  if (delta != 0 && balanceMode) {
+    // 5% per detent: 20 clicks from centre to hard left/right
+    int next = constrain(balance + delta * 5, -100, 100);
+    if (next != balance) {
+      balance = next;
+      balanceChanged = true;
+      balanceDirty = true;
+    }
+  }
+  else if (delta != 0) {
     encoderChanged = true;
     encoderDelta += delta;
     volume = constrain(volume + delta, 0, 100);
@@ -60,6 +77,18 @@ void Channel::update() {
     lastDisplayDraw = millis();
     displayDirty = false;
   }
+  else if (balanceDirty && millis() - lastDisplayDraw >= 80) {
+    if (balanceMode) display.updateBalance(balance);
+    else display.clearBalance();
+    lastDisplayDraw = millis();
+    balanceDirty = false;
+  }
+  // Meter is the lowest priority: never in the same pass as a volume redraw
+  else if (meterDirty && millis() - lastMeterDraw >= 80) {
+    display.updateMeter(meterLevel);
+    lastMeterDraw = millis();
+    meterDirty = false;
+  }
 }
 
 void Channel::setApp(const char* appName) {
@@ -73,6 +102,36 @@ void Channel::setVolume(int volume) {
   fader.setTarget(this->volume);
 }
 
+void Channel::setMeter(uint8_t level) {
+  if (level == meterLevel) return;
+  meterLevel = level;
+  meterDirty = true;
+}
+
+void Channel::setBalance(int balance) {
+  balance = constrain(balance, -100, 100);
+  if (balance == this->balance) return;
+  this->balance = balance;
+  balanceDirty = true;
+}
+
+void Channel::setEncoderMode(uint8_t mode) {
+  bool next = mode == ENCODER_MODE_BALANCE;
+  if (next == balanceMode) return;
+  balanceMode = next;
+  balanceDirty = true;  // show or clear the indicator
+}
+
+int Channel::getBalance() {
+  return balance;
+}
+
+bool Channel::hasBalanceChanged() {
+  bool changed = balanceChanged;
+  balanceChanged = false;
+  return changed;
+}
+
 int Channel::getVolume() {
   return volume;
 }
@@ -110,6 +169,14 @@ bool Channel::hasFaderChanged() {
 bool Channel::wasButtonPressed() {
   return encoder.wasPressed();
 }
+
+ButtonGesture Channel::getButtonGesture() {
+  return encoder.getGesture();
+}
+
+void Channel::setMuted(bool muted) {
+  display.setMuted(muted);
+}
 bool Channel::receiveIcon(Stream& s) {
   display.beginIconStream();
 
@@ -140,6 +207,57 @@ bool Channel::receiveIcon(Stream& s) {
   return true;
 }
 
+// Next byte of a streamed icon, or -1 once the host has gone quiet
+static int readIconByte(Stream& s, uint32_t& lastByte) {
+  while (!s.available()) {
+    if (millis() - lastByte > 500) return -1;
+  }
+  lastByte = millis();
+  return s.read();
+}
+
+bool Channel::receiveIconRle(Stream& s, uint16_t len) {
+  display.beginIconStream();
+
+  const uint16_t total = (uint16_t)ICON_SIZE * ICON_SIZE;
+  uint16_t pixelsDone = 0;
+  uint32_t lastByte = millis();
+
+  while (len > 0) {
+    int c = readIconByte(s, lastByte);
+    if (c < 0) break;
+    len--;
+
+    // Repeats carry one pixel, literals c + 1; a run cut short by the
+    // length is just dropped
+    bool repeat = c & ICON_RLE_REPEAT;
+    uint8_t pixels = repeat ? 1 : c + 1;
+    uint8_t count = repeat ? (c & 0x7F) + 2 : 1;
+    while (pixels-- > 0 && len >= 2) {
+      int hi = readIconByte(s, lastByte);
+      int lo = hi < 0 ? -1 : readIconByte(s, lastByte);
+      if (lo < 0) { len = 0; break; }
+      len -= 2;
+      uint16_t color = ((uint16_t)hi << 8) | lo;
+      for (uint8_t i = 0; i < count && pixelsDone < total; i++) {
+        display.pushIconPixel(color);
+        pixelsDone++;
+      }
+    }
+  }
+
+  display.endIconStream();
+  if (pixelsDone < total) return false;
+  icon.markStreamed();
+  return true;
+}
+
+void Channel::setIconRow(uint8_t row, const uint8_t* pixels) {
+  if (row >= ICON_SIZE) return;
+  display.drawIconRow(row, pixels);
+  if (row == ICON_SIZE - 1) icon.markStreamed();
+}
+
 void Channel::stopFader() {
   fader.stop();
 }
@@ -162,6 +280,8 @@ void Channel::showMessage(const char* l1, const char* l2, const char* l3) {
 
 void Channel::redrawUI() {
   display.drawUI(volume, appName.c_str(), &icon);
+  meterDirty = true;  // drawUI leaves the meter track empty
+  balanceDirty = balanceMode;
 }
 
 void Channel::pollEncoderButton() {
@@ -171,5 +291,7 @@ void Channel::pollEncoderButton() {
 void Channel::flushInputs() {
   encoder.getDelta();
   encoder.wasPressed();
+  encoder.getGesture();
   fader.hasMoved();
+  balanceChanged = false;
 }
\ No newline at end of file
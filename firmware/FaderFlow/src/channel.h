@@ -39,6 +39,15 @@ public:
   // Get current volume
   int getVolume();
 
+  // Set the level meter (from host, 0-255); drawn when the screen is idle
+  void setMeter(uint8_t level);
+
+  // Balance (-100..100) and whether the encoder adjusts it instead of volume
+  void setBalance(int balance);
+  void setEncoderMode(uint8_t mode);
+  int getBalance();
+  bool hasBalanceChanged();
+
   // Get channel ID
   uint8_t getID();
 
@@ -56,7 +65,15 @@ public:
   // Check if button was pressed
   bool wasButtonPressed();
 
+  // Last completed knob gesture (GESTURE_NONE if none), cleared on read
+  ButtonGesture getButtonGesture();
+
+  // Mute state from the host
+  void setMuted(bool muted);
+
   bool receiveIcon(Stream& s);
+  bool receiveIconRle(Stream& s, uint16_t len);
+  void setIconRow(uint8_t row, const uint8_t* pixels);
   void stopFader();
   void releaseFader();  // coast (free) instead of brake
 
@@ -83,6 +100,15 @@ private:
 
   bool displayDirty;
   uint32_t lastDisplayDraw;
+
+  uint8_t meterLevel;
+  bool meterDirty;
+  uint32_t lastMeterDraw;
+
+  bool balanceMode;
+  int balance;
+  bool balanceChanged;
+  bool balanceDirty;
 };
 
 #endif //FADERFLOW_CHANNEL_H
\ No newline at end of file
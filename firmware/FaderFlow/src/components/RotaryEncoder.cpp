@@ -16,6 +16,11 @@ RotaryEncoder::RotaryEncoder(uint8_t pinDT, uint8_t pinCLK, uint8_t pinSW) {
     lastButtonState = HIGH;
     lastDebounceTime = 0;
     buttonPressed = false;
+    pressStart = 0;
+    lastRelease = 0;
+    longFired = false;
+    clickPending = false;
+    gesture = GESTURE_NONE;
 }
 
 void RotaryEncoder::begin() {
@@ -55,11 +60,33 @@ void RotaryEncoder::update() {
             // Button was pressed (LOW = pressed)
             if (buttonState == LOW) {
                 buttonPressed = true;
+                pressStart = millis();
+                longFired = false;
+            } else if (!longFired) {
+                // Released: second click of a pair, or wait for one
+                if (clickPending && millis() - lastRelease <= DOUBLE_PRESS_MS) {
+                    gesture = GESTURE_DOUBLE_PRESS;
+                    clickPending = false;
+                } else {
+                    clickPending = true;
+                    lastRelease = millis();
+                }
             }
         }
     }
 
     lastButtonState = reading;
+
+    if (buttonState == LOW && !longFired && millis() - pressStart >= LONG_PRESS_MS) {
+        gesture = GESTURE_LONG_PRESS;
+        longFired = true;
+        clickPending = false;
+    }
+
+    if (clickPending && buttonState == HIGH && millis() - lastRelease > DOUBLE_PRESS_MS) {
+        gesture = GESTURE_PRESS;
+        clickPending = false;
+    }
 }
 
 int RotaryEncoder::getDelta() {
@@ -74,8 +101,16 @@ bool RotaryEncoder::wasPressed() {
     return pressed;
 }
 
+ButtonGesture RotaryEncoder::getGesture() {
+    ButtonGesture g = gesture;
+    gesture = GESTURE_NONE;
+    return g;
+}
+
 void RotaryEncoder::reset() {
     position = 0;
     lastPosition = 0;
     buttonPressed = false;
+    clickPending = false;
+    gesture = GESTURE_NONE;
 }
\ No newline at end of file
@@ -7,6 +7,14 @@
 
 #include <Arduino.h>
 
+// Knob gestures; the values double as the CMD_BUTTON_EVENT wire codes
+enum ButtonGesture : uint8_t {
+    GESTURE_NONE = 0,
+    GESTURE_PRESS = 1,
+    GESTURE_LONG_PRESS = 2,
+    GESTURE_DOUBLE_PRESS = 3
+};
+
 class RotaryEncoder {
 public:
     RotaryEncoder(uint8_t pinDT, uint8_t pinCLK, uint8_t pinSW);
@@ -20,9 +28,14 @@ public:
     // Get the current position delta since last read
     int getDelta();
 
-    // Check if button was pressed
+    // Check if button was pressed (raw, fires on the press itself)
     bool wasPressed();
 
+    // Get the last completed gesture and clear it. A single press is only
+    // reported once the double-press window has passed; a long press fires
+    // while the knob is still held.
+    ButtonGesture getGesture();
+
     // Reset the encoder state
     void reset();
 
@@ -42,7 +55,16 @@ private:
     unsigned long lastDebounceTime;
     bool buttonPressed;
 
+    // Gesture state
+    unsigned long pressStart;
+    unsigned long lastRelease;
+    bool longFired;
+    bool clickPending;
+    ButtonGesture gesture;
+
     static const unsigned long DEBOUNCE_DELAY = 50;
+    static const unsigned long LONG_PRESS_MS = 600;
+    static const unsigned long DOUBLE_PRESS_MS = 300;
 };
 
 #endif //FADERFLOW_ROTARYENCODER_H
\ No newline at end of file
@@ -9,6 +9,8 @@ Display::Display(int8_t cs, int8_t dc, int8_t rst)
   //: tft(cs, dc, SHARED_MOSI_PIN, SHARED_SCLK_PIN, rst) {
   	: tft(cs, dc, rst) {
 	currentVolume = 0;
+	currentMeterFill = 0;
+	muted = false;
 }
 
 void Display::begin() {
@@ -49,6 +51,10 @@ void Display::drawUI(int volume, const char* appName, Icon* icon) {
 
   // Draw volume display
   drawVolumeDisplay(volume);
+
+  // Empty meter track; the channel repaints its level after a full redraw
+  tft.fillRect((SCREEN_WIDTH - 200) / 2, 218, 200, 6, BAR_BG);
+  currentMeterFill = 0;
 }
 
 void Display::updateVolume(int volume) {
@@ -91,13 +97,61 @@ void Display::updateIcon(Icon* icon) {
   }
 }
 
+void Display::updateMeter(uint8_t level) {
+  // Same width as the volume bar, just below it
+  int meterWidth = 200;
+  int meterX = (SCREEN_WIDTH - meterWidth) / 2;
+  int meterY = 218;
+  int fill = (long)meterWidth * level / 255;
+
+  if (fill > currentMeterFill) {
+    tft.fillRect(meterX + currentMeterFill, meterY, fill - currentMeterFill, 6, METER_FILL);
+  } else if (fill < currentMeterFill) {
+    tft.fillRect(meterX + fill, meterY, currentMeterFill - fill, 6, BAR_BG);
+  }
+  currentMeterFill = fill;
+}
+
+void Display::updateBalance(int balance) {
+  // Thin track across the top with a centre tick and a marker
+  int trackWidth = 200;
+  int trackX = (SCREEN_WIDTH - trackWidth) / 2;
+  int trackY = 12;
+  int centerX = SCREEN_WIDTH / 2;
+
+  tft.fillRect(0, trackY - 8, SCREEN_WIDTH, 18, BG_COLOR);
+  tft.fillRect(trackX, trackY, trackWidth, 2, BAR_BG);
+  tft.fillRect(centerX - 1, trackY - 3, 2, 8, BAR_BG);
+
+  tft.setTextColor(TEXT_COLOR);
+  tft.setTextSize(1);
+  tft.setCursor(trackX - 12, trackY - 3);
+  tft.print("L");
+  tft.setCursor(trackX + trackWidth + 6, trackY - 3);
+  tft.print("R");
+
+  int markerX = centerX + (long)(trackWidth / 2 - 3) * constrain(balance, -100, 100) / 100;
+  tft.fillRoundRect(markerX - 3, trackY - 5, 6, 12, 2, ACCENT_COLOR);
+}
+
+void Display::clearBalance() {
+  tft.fillRect(0, 4, SCREEN_WIDTH, 18, BG_COLOR);
+}
+
+void Display::setMuted(bool muted) {
+  if (muted == this->muted) return;
+  this->muted = muted;
+  updateVolume(currentVolume);
+}
+
 void Display::drawVolumeDisplay(int volume) {
   // Volume percentage - large and centered
-  tft.setTextColor(TEXT_COLOR);
+  tft.setTextColor(muted ? MUTED_FILL : TEXT_COLOR);
   tft.setTextSize(4);
 
   char volStr[8];
-  sprintf(volStr, "%d%%", volume);
+  if (muted) strcpy(volStr, "MUTED");
+  else sprintf(volStr, "%d%%", volume);
 
   int16_t x1, y1;
   uint16_t w, h;
@@ -118,7 +172,7 @@ void Display::drawVolumeDisplay(int volume) {
   // Bar fill
   int fillWidth = (barWidth - 4) * volume / 100;
   if (fillWidth > 0) {
-    tft.fillRoundRect(barX + 2, barY + 2, fillWidth, barHeight - 4, 4, BAR_FILL);
+    tft.fillRoundRect(barX + 2, barY + 2, fillWidth, barHeight - 4, 4, muted ? MUTED_FILL : BAR_FILL);
   }
 }
 
@@ -154,6 +208,17 @@ void Display::endIconStream() {
   tft.endWrite();
 }
 
+void Display::drawIconRow(uint8_t row, const uint8_t* pixels) {
+  int iconX = (SCREEN_WIDTH - ICON_SIZE) / 2;
+  int iconY = 40;
+  tft.startWrite();
+  tft.setAddrWindow(iconX, iconY + row, ICON_SIZE, 1);
+  for (uint8_t i = 0; i < ICON_SIZE; i++) {
+    tft.writeColor(((uint16_t)pixels[2 * i] << 8) | pixels[2 * i + 1], 1);
+  }
+  tft.endWrite();
+}
+
 void Display::drawMessage(const char* line1, const char* line2, const char* line3) {
   tft.fillScreen(BG_COLOR);
   int16_t x1, y1;
@@ -20,6 +20,8 @@
 #define BAR_BG        0x2124  // Dark gray
 #define BAR_FILL      0x07FF  // Cyan
 #define ACCENT_COLOR  0x07FF  // Cyan
+#define METER_FILL    0x07E0  // Green
+#define MUTED_FILL    0x7BEF  // Gray
 
 class Display {
 public:
@@ -40,12 +42,27 @@ public:
     // Update only the icon
     void updateIcon(Icon* icon);
 
+    // Update the level meter under the volume bar (0-255). Only the part
+    // that changed is repainted, so it is cheap enough to call often.
+    void updateMeter(uint8_t level);
+
+    // Draw the balance indicator above the icon (-100 left .. 100 right),
+    // or clear that strip when the encoder isn't controlling balance
+    void updateBalance(int balance);
+    void clearBalance();
+
+    // Show the session as muted: a mute label in place of the percentage
+    // and a greyed-out volume bar
+    void setMuted(bool muted);
+
     // Get the underlying TFT object if needed
     Adafruit_ST7789* getTFT();
 
     void beginIconStream();
     void pushIconPixel(uint16_t color);
     void endIconStream();
+    // One row of the icon, big-endian RGB565
+    void drawIconRow(uint8_t row, const uint8_t* pixels);
 
     // Full-screen centered message (calibration instructions etc.)
     void drawMessage(const char* line1, const char* line2, const char* line3);
@@ -53,6 +70,8 @@ public:
 private:
     Adafruit_ST7789 tft;
     int currentVolume;
+    int currentMeterFill;
+    bool muted;
 
     void drawVolumeDisplay(int volume);
     void drawPlaceholderIcon(int x, int y, int size);
@@ -9,6 +9,7 @@
 #include "protocol.h"
 #include "utils/device_id.h"
 #include "utils/comms.h"
+#include "utils/framing.h"
 #include "channel.h"
 
 #define NUM_CHANNELS 5
@@ -44,7 +45,23 @@ static void sendFaderUpdate(uint8_t channel, int volume) {
   msg.cmd = CMD_FADER_UPDATE;
   msg.channel = channel;
   msg.position = volumeToProtocol(volume);
-  Serial.write((uint8_t*)&msg, sizeof(msg));
+  sendPacket((uint8_t*)&msg, sizeof(msg));
+}
+
+static void sendBalanceUpdate(uint8_t channel, int balance) {
+  BalanceMessage msg;
+  msg.cmd = CMD_BALANCE_UPDATE;
+  msg.channel = channel;
+  msg.balance = (int8_t)balance;
+  sendPacket((uint8_t*)&msg, sizeof(msg));
+}
+
+static void sendButtonEvent(uint8_t channel, ButtonGesture gesture) {
+  ButtonEventMessage msg;
+  msg.cmd = CMD_BUTTON_EVENT;
+  msg.channel = channel;
+  msg.event = (uint8_t)gesture;
+  sendPacket((uint8_t*)&msg, sizeof(msg));
 }
 
 
@@ -67,7 +84,7 @@ static char     calMsg[NUM_CHANNELS][22];    // per-channel result, shown on the
 
 static void sendCalStatus(uint8_t channel, uint8_t phase) {
   uint8_t msg[3] = { CMD_CALIBRATION_STATUS, channel, phase };
-  Serial.write(msg, 3);
+  sendPacket(msg, 3);
 }
 
 // Raw calibration values for the host's debug panel.
@@ -79,7 +96,7 @@ static void sendCalDebug(uint8_t channel, uint8_t kind, uint16_t v1, uint16_t v2
     (uint8_t)(v1 & 0xFF), (uint8_t)(v1 >> 8),
     (uint8_t)(v2 & 0xFF), (uint8_t)(v2 >> 8)
   };
-  Serial.write(msg, 7);
+  sendPacket(msg, 7);
 }
 
 static void saveCalibration() {
@@ -132,7 +149,10 @@ static void showCalScreen(uint8_t i) {
 static void startCalibration() {
   // Coast, not brake -- the user must hand-position each fader against the
   // physical stops, and an electrical brake makes that stiff and unstable.
-  for (uint8_t i = 0; i < NUM_CONNECTED_CHANNELS; i++) channels[i]->releaseFader();
+  for (uint8_t i = 0; i < NUM_CONNECTED_CHANNELS; i++) {
+    channels[i]->releaseFader();
+    channels[i]->flushInputs();  // a knob press from before must not capture
+  }
   calMode = true;
   calChannel = 0;
   calPhase = 0;
@@ -214,13 +234,43 @@ static void runCalibration() {
 // Exception: CMD_DISPLAY_UPDATE_ICON. Its 8192-byte payload doesn't fit
 // any buffer on this chip, so the parser treats the 2-byte header as the
 // packet and the handler streams the payload straight to the display.
+//
+// Once the host negotiates v2 framing, bytes go through the frame parser
+// instead; a complete frame with a good CRC lands in rxBuf and dispatches
+// the same way.
 
-#define RX_BUF_SIZE 80  // largest buffered packet: DisplayUpdateAppCommand (66 B)
+#define RX_BUF_SIZE 136  // largest buffered packet: DisplayUpdateIconRowCommand (131 B)
 
 static uint8_t rxBuf[RX_BUF_SIZE];
 static uint8_t rxLen = 0;
 static uint8_t rxExpected = 0;
 
+// Recently dispatched v2 frames. A retransmit can arrive after later frames
+// (its ACK was lost or late), so one remembered seq isn't enough to drop it.
+// Sized like the host's window of unacknowledged frames.
+#define FRAME_SEEN 32
+static uint8_t frSeen[FRAME_SEEN];
+static uint8_t frSeenCount = 0;
+static uint8_t frSeenNext = 0;
+
+static void forgetFrames() {
+  frSeenCount = 0;
+  frSeenNext = 0;
+}
+
+static bool frameSeen(uint8_t seq) {
+  for (uint8_t i = 0; i < frSeenCount; i++) {
+    if (frSeen[i] == seq) return true;
+  }
+  return false;
+}
+
+static void rememberFrame(uint8_t seq) {
+  frSeen[frSeenNext] = seq;
+  frSeenNext = (frSeenNext + 1) % FRAME_SEEN;
+  if (frSeenCount < FRAME_SEEN) frSeenCount++;
+}
+
 // Full packet length (incl. cmd byte) for each command. 0 = unknown.
 static uint8_t packetLength(uint8_t cmd) {
   switch (cmd) {
@@ -229,31 +279,65 @@ static uint8_t packetLength(uint8_t cmd) {
     case CMD_ECHO_UUID:
     case CMD_CALIBRATION_START:
     case CMD_CALIBRATION_CANCEL:
+    case CMD_FRAMING_REQUEST:
+    case CMD_CAPABILITIES_REQUEST:
       return 1;
     case CMD_DISPLAY_UPDATE_APP_NAME:
       return sizeof(DisplayUpdateAppCommand);
     case CMD_DISPLAY_UPDATE_APP_VOLUME:
       return sizeof(DisplayUpdateVolumeCommand);
+    case CMD_DISPLAY_UPDATE_METER:
+      return sizeof(DisplayUpdateMeterCommand);
+    case CMD_DISPLAY_UPDATE_BALANCE:
+      return sizeof(BalanceMessage);
+    case CMD_ENCODER_MODE:
+      return sizeof(EncoderModeCommand);
+    case CMD_DISPLAY_UPDATE_MUTE:
+      return sizeof(DisplayUpdateMuteCommand);
+    case CMD_DISPLAY_UPDATE_ICON_ROW:
+      return sizeof(DisplayUpdateIconRowCommand);
     case CMD_DISPLAY_UPDATE_ICON:
       return 2;  // header only — payload is streamed by the handler
+    case CMD_DISPLAY_UPDATE_ICON_RLE:
+      return 4;  // likewise, the header ends with the run byte count
     default:
       if (cmd == 'h' || cmd == 'u') return 1;  // debug shortcuts
       return 0;
   }
 }
 
-static void handleIconTransfer(uint8_t ch) {
-  // Motors must not run unsupervised during the ~0.75s blocking transfer
+// Everything packetLength() accepts is a command this firmware handles
+static void sendCapabilities() {
+  CapabilitiesMessage msg;
+  msg.cmd = CMD_CAPABILITIES;
+  msg.channels = NUM_CONNECTED_CHANNELS;
+  msg.display_width = SCREEN_WIDTH;
+  msg.display_height = SCREEN_HEIGHT;
+  msg.icon_format = ICON_FORMAT_RGB565;
+  msg.framing_version = FRAMING_VERSION;
+  msg.commands = 0;
+  for (uint8_t c = 0; c < 32; c++) {
+    if (packetLength(c)) msg.commands |= 1UL << c;
+  }
+  sendPacket((uint8_t*)&msg, sizeof(msg));
+}
+
+// rleLen = 0: a raw CMD_DISPLAY_UPDATE_ICON; otherwise the run byte count
+// of a CMD_DISPLAY_UPDATE_ICON_RLE
+static void handleIconTransfer(uint8_t ch, Stream& s, uint16_t rleLen = 0) {
+  // Motors must not run unsupervised during the blocking transfer
+  // (~0.75s raw, usually far less compressed)
   for (uint8_t i = 0; i < NUM_CONNECTED_CHANNELS; i++) channels[i]->stopFader();
 
   if (!calMode && ch < NUM_CONNECTED_CHANNELS) {
-    channels[ch]->receiveIcon(Serial);
+    if (rleLen) channels[ch]->receiveIconRle(s, rleLen);
+    else channels[ch]->receiveIcon(s);
   } else {
     // Invalid channel: still consume the payload or the stream desyncs
-    uint16_t remaining = 8192;
+    uint16_t remaining = rleLen ? rleLen : 8192;
     uint32_t lastByte = millis();
     while (remaining > 0 && millis() - lastByte < 500) {
-      if (Serial.available()) { Serial.read(); remaining--; lastByte = millis(); }
+      if (s.available()) { s.read(); remaining--; lastByte = millis(); }
     }
   }
 }
@@ -292,7 +376,59 @@ static void dispatchPacket() {
     }
   }
   else if (cmd == CMD_DISPLAY_UPDATE_ICON) {
-    handleIconTransfer(rxBuf[1]);
+    handleIconTransfer(rxBuf[1], Serial);
+  }
+  else if (cmd == CMD_DISPLAY_UPDATE_ICON_RLE) {
+    uint16_t len = rxBuf[2] | ((uint16_t)rxBuf[3] << 8);
+    if (len > 0) handleIconTransfer(rxBuf[1], Serial, len);
+  }
+  else if (cmd == CMD_DISPLAY_UPDATE_ICON_ROW) {
+    // Small enough to buffer, so the motors keep running
+    DisplayUpdateIconRowCommand* c = (DisplayUpdateIconRowCommand*)rxBuf;
+    if (!calMode && c->channel < NUM_CONNECTED_CHANNELS) {
+      channels[c->channel]->setIconRow(c->row, c->pixels);
+    }
+  }
+  else if (cmd == CMD_FRAMING_REQUEST) {
+    // Answer raw, then frame everything from here on
+    uint8_t reply[2] = { CMD_FRAMING_ACCEPT, FRAMING_VERSION };
+    Serial.write(reply, 2);
+    setFramed(true);
+    forgetFrames();
+  }
+  else if (cmd == CMD_CAPABILITIES_REQUEST) {
+    sendCapabilities();
+  }
+  else if (cmd == CMD_DISPLAY_UPDATE_METER) {
+    DisplayUpdateMeterCommand c;
+    memcpy(&c, rxBuf, sizeof(c));
+    if (!calMode) {
+      // Only stores the level; Channel::update() draws it when the screen is free
+      for (uint8_t i = 0; i < NUM_CONNECTED_CHANNELS && i < METER_CHANNELS; i++) {
+        channels[i]->setMeter(c.levels[i]);
+      }
+    }
+  }
+  else if (cmd == CMD_DISPLAY_UPDATE_BALANCE) {
+    BalanceMessage c;
+    memcpy(&c, rxBuf, sizeof(c));
+    if (!calMode && c.channel < NUM_CONNECTED_CHANNELS) {
+      channels[c.channel]->setBalance(c.balance);
+    }
+  }
+  else if (cmd == CMD_ENCODER_MODE) {
+    EncoderModeCommand c;
+    memcpy(&c, rxBuf, sizeof(c));
+    if (c.channel < NUM_CONNECTED_CHANNELS) {
+      channels[c.channel]->setEncoderMode(c.mode);
+    }
+  }
+  else if (cmd == CMD_DISPLAY_UPDATE_MUTE) {
+    DisplayUpdateMuteCommand c;
+    memcpy(&c, rxBuf, sizeof(c));
+    if (!calMode && c.channel < NUM_CONNECTED_CHANNELS) {
+      channels[c.channel]->setMuted(c.muted != 0);
+    }
   }
   else if (cmd == CMD_CALIBRATION_START) {
     startCalibration();
@@ -302,10 +438,120 @@ static void dispatchPacket() {
   }
 }
 
+// ---- v2 frame parser ----
+
+#define FRAME_MAX_PAYLOAD (2 + 8192)  // an icon
+#define FRAME_BYTE_TIMEOUT_MS 50      // a stalled frame is abandoned
+
+enum FrameState : uint8_t { FR_IDLE, FR_LEN_LO, FR_LEN_HI, FR_SEQ, FR_PAYLOAD, FR_CRC_LO, FR_CRC_HI };
+
+static FrameState frState = FR_IDLE;
+static uint16_t frLen = 0;
+static uint16_t frGot = 0;
+static uint8_t frSeq = 0;
+static uint16_t frCrc = 0;
+static uint16_t frRxCrc = 0;
+static bool frIcon = false;       // payload was streamed, not buffered
+static uint32_t frLastByte = 0;
+
+static void sendFrameReply(uint8_t cmd, uint8_t seq) {
+  uint8_t msg[2] = { cmd, seq };
+  sendPacket(msg, 2);
+}
+
+static void endFrame() {
+  if (frRxCrc != frCrc) {
+    sendFrameReply(CMD_FRAME_NAK, frSeq);
+  } else {
+    // A retransmit whose ACK got lost: acknowledge again, don't re-run it
+    bool repeat = frameSeen(frSeq);
+    if (!frIcon && !repeat && packetLength(rxBuf[0]) == frLen) {
+      dispatchPacket();
+    }
+    if (!repeat) rememberFrame(frSeq);
+    sendFrameReply(CMD_FRAME_ACK, frSeq);
+  }
+  frState = FR_IDLE;
+}
+
+static void pumpFramed(uint8_t b) {
+  frLastByte = millis();
+  switch (frState) {
+    case FR_IDLE:
+      if (b == FRAME_START) {
+        frState = FR_LEN_LO;
+        frCrc = 0xFFFF;
+      } else if (b == CMD_HANDSHAKE_REQUEST || b == 'h') {
+        // A raw handshake means a new host session: back to v1
+        setFramed(false);
+        forgetFrames();
+        sendHandshake();
+        handshakeComplete = true;
+      }
+      return;
+    case FR_LEN_LO:
+      frLen = b;
+      frState = FR_LEN_HI;
+      break;
+    case FR_LEN_HI:
+      frLen |= (uint16_t)b << 8;
+      frState = (frLen == 0 || frLen > FRAME_MAX_PAYLOAD) ? FR_IDLE : FR_SEQ;
+      break;
+    case FR_SEQ:
+      frSeq = b;
+      frGot = 0;
+      frIcon = false;
+      frState = FR_PAYLOAD;
+      break;
+    case FR_PAYLOAD: {
+        if (frGot < RX_BUF_SIZE) rxBuf[frGot] = b;
+        frGot++;
+        frCrc = crc16Update(frCrc, b);
+        bool rawIcon = frGot == 2 && rxBuf[0] == CMD_DISPLAY_UPDATE_ICON
+                       && frLen == FRAME_MAX_PAYLOAD;
+        bool rleIcon = frGot == 4 && rxBuf[0] == CMD_DISPLAY_UPDATE_ICON_RLE && frLen > 4
+                       && frLen == 4 + (rxBuf[2] | ((uint16_t)rxBuf[3] << 8));
+        if (rawIcon || rleIcon) {
+          // Too big to buffer: draw it as it arrives and check the CRC after;
+          // a NAK makes the host send it again
+          CrcStream s(Serial, frCrc);
+          handleIconTransfer(rxBuf[1], s, rleIcon ? frLen - 4 : 0);
+          frCrc = s.getCrc();
+          frLastByte = millis();
+          frGot = frLen;
+          frIcon = true;
+        } else if (frGot > RX_BUF_SIZE) {
+          frState = FR_IDLE;  // nothing else is this big; the host will retry
+          return;
+        }
+        if (frGot == frLen) frState = FR_CRC_LO;
+        return;
+    }
+    case FR_CRC_LO:
+      frRxCrc = b;
+      frState = FR_CRC_HI;
+      return;
+    case FR_CRC_HI:
+      frRxCrc |= (uint16_t)b << 8;
+      endFrame();
+      return;
+  }
+  frCrc = crc16Update(frCrc, b);
+}
+
 static void pumpSerial() {
+  if (frState != FR_IDLE && millis() - frLastByte > FRAME_BYTE_TIMEOUT_MS) {
+    frState = FR_IDLE;
+  }
+
   while (Serial.available() > 0) {
     uint8_t b = Serial.read();
 
+    if (isFramed()) {
+      pumpFramed(b);
+      continue;
+    }
+
     if (rxLen == 0) {
       // Start of a packet: byte must be a known command
       rxExpected = packetLength(b);
@@ -394,8 +640,14 @@ void loop() {
       sendFaderUpdate(i, ch->getVolume());
     }
 
-    if (ch->wasButtonPressed()) {
-      // TODO: mute toggle / button CMD
+    if (ch->hasBalanceChanged()) {
+      sendBalanceUpdate(i, ch->getBalance());
+    }
+
+    // The host decides what a gesture does (press = mute toggle)
+    ButtonGesture gesture = ch->getButtonGesture();
+    if (gesture != GESTURE_NONE) {
+      sendButtonEvent(i, gesture);
     }
   }
 }
\ No newline at end of file
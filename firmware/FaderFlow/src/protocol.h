@@ -5,6 +5,7 @@
 
 #define MAGIC_STRING "FADERFLOW"
 #define UUID_SIZE 16
+#define METER_CHANNELS 5
 
 // Command bytes
 #define CMD_HANDSHAKE_REQUEST 0x01
@@ -14,8 +15,51 @@
 #define CMD_DISPLAY_UPDATE_APP_NAME 0x05
 #define CMD_DISPLAY_UPDATE_APP_VOLUME 0x06
 #define CMD_DISPLAY_UPDATE_ICON 0x07
+#define CMD_DISPLAY_UPDATE_ICON_ROW 0x1B  // host -> device, one row of an icon
+#define ICON_ROW_BYTES 128                // 64 pixels of RGB565
+
+// Run-length encoded icon: cmd, channel, run byte count (u16 LE), then the
+// runs, streamed like CMD_DISPLAY_UPDATE_ICON. Each run is a control byte c:
+//   c & 0x80: one pixel (big-endian RGB565) repeated (c & 0x7F) + 2 times
+//   else:     c + 1 literal pixels
+#define CMD_DISPLAY_UPDATE_ICON_RLE 0x1C
+#define ICON_RLE_REPEAT 0x80
+#define CMD_DISPLAY_UPDATE_METER 0x0A  // host -> device, ~10 Hz at most
+#define CMD_DISPLAY_UPDATE_BALANCE 0x0B  // host -> device
+#define CMD_DISPLAY_UPDATE_MUTE 0x0D  // host -> device
+
+// What a channel's encoder adjusts: 0 = volume, 1 = balance
+#define CMD_ENCODER_MODE 0x0C  // host -> device
+#define ENCODER_MODE_VOLUME 0
+#define ENCODER_MODE_BALANCE 1
 
 #define CMD_FADER_UPDATE 0x10
+#define CMD_BALANCE_UPDATE 0x13  // device -> host, encoder in balance mode
+
+// Knob gestures: device -> host. Codes match RotaryEncoder's ButtonGesture
+#define CMD_BUTTON_EVENT 0x14
+#define BUTTON_EVENT_PRESS 1
+#define BUTTON_EVENT_LONG_PRESS 2
+#define BUTTON_EVENT_DOUBLE_PRESS 3
+
+// v2 framing, negotiated after the handshake. The host asks with a raw
+// CMD_FRAMING_REQUEST; the device answers a raw CMD_FRAMING_ACCEPT
+// {cmd, version} and frames everything after it. Every host frame gets a
+// CMD_FRAME_ACK (or CMD_FRAME_NAK on a bad CRC) carrying its sequence number.
+#define FRAME_START 0xA5
+#define FRAMING_VERSION 2
+#define CMD_FRAMING_REQUEST 0x15  // host -> device, raw
+#define CMD_FRAMING_ACCEPT 0x16   // device -> host, raw: cmd, version
+#define CMD_FRAME_ACK 0x17        // device -> host: cmd, seq
+#define CMD_FRAME_NAK 0x18        // device -> host: cmd, seq
+
+// Capabilities, asked for once the link is up (framed or not). Firmware
+// that predates this stays silent and the host assumes the 1.0 feature set.
+#define CMD_CAPABILITIES_REQUEST 0x19  // host -> device
+#define CMD_CAPABILITIES 0x1A          // device -> host, CapabilitiesMessage
+#define DEVICE_TYPE_VOLUME_CONTROLLER 0x01
+#define ICON_FORMAT_NONE 0
+#define ICON_FORMAT_RGB565 1           // 64x64, big-endian, CMD_DISPLAY_UPDATE_ICON
 
 // Calibration. Status phases: 0 = waiting for bottom, 1 = waiting for top,
 // 2 = done & saved to EEPROM, 3 = cancelled
@@ -28,7 +72,7 @@
 typedef struct {
     uint8_t cmd;
     char magic[10];        // "FADERFLOW\0"
-    uint8_t device_type;   // 0x01 for volume controller
+    uint8_t device_type;   // DEVICE_TYPE_*
     uint8_t uuid[UUID_SIZE];
     uint8_t version_major;
     uint8_t version_minor;
@@ -59,4 +103,52 @@ typedef struct {
     // Followed by: uint8_t iconData[8192]
 } __attribute__((packed)) DisplayUpdateIconCommand;
 
+// Icons in pieces: the loop keeps running (motors, other packets) between rows
+typedef struct {
+    uint8_t cmd;        // CMD_DISPLAY_UPDATE_ICON_ROW
+    uint8_t channel;
+    uint8_t row;        // 0-63, top to bottom
+    uint8_t pixels[ICON_ROW_BYTES];  // big-endian RGB565, as CMD_DISPLAY_UPDATE_ICON
+} __attribute__((packed)) DisplayUpdateIconRowCommand;
+
+typedef struct {
+    uint8_t cmd;        // CMD_DISPLAY_UPDATE_METER
+    uint8_t levels[METER_CHANNELS];  // peak per channel, 0-255 on the host's dB scale
+} __attribute__((packed)) DisplayUpdateMeterCommand;
+
+// Both directions: CMD_DISPLAY_UPDATE_BALANCE and CMD_BALANCE_UPDATE
+typedef struct {
+    uint8_t cmd;
+    uint8_t channel;
+    int8_t balance;     // -100 (left) to 100 (right)
+} __attribute__((packed)) BalanceMessage;
+
+typedef struct {
+    uint8_t cmd;        // CMD_ENCODER_MODE
+    uint8_t channel;
+    uint8_t mode;       // ENCODER_MODE_*
+} __attribute__((packed)) EncoderModeCommand;
+
+typedef struct {
+    uint8_t cmd;        // CMD_BUTTON_EVENT
+    uint8_t channel;
+    uint8_t event;      // BUTTON_EVENT_*
+} __attribute__((packed)) ButtonEventMessage;
+
+typedef struct {
+    uint8_t cmd;        // CMD_DISPLAY_UPDATE_MUTE
+    uint8_t channel;
+    uint8_t muted;      // 0 or 1
+} __attribute__((packed)) DisplayUpdateMuteCommand;
+
+typedef struct {
+    uint8_t cmd;        // CMD_CAPABILITIES
+    uint8_t channels;
+    uint16_t display_width;   // 0 x 0 = no display
+    uint16_t display_height;
+    uint8_t icon_format;      // ICON_FORMAT_*
+    uint8_t framing_version;  // highest framing the device speaks
+    uint32_t commands;        // bit n set = host -> device command n is understood
+} __attribute__((packed)) CapabilitiesMessage;
+
 #endif
\ No newline at end of file
@@ -11,10 +11,10 @@ void sendHandshake() {
     HandshakeResponse response;
     response.cmd = CMD_HANDSHAKE_RESPONSE;  // ADD THIS LINE!
     strcpy(response.magic, MAGIC_STRING);
-    response.device_type = 0x01;
+    response.device_type = DEVICE_TYPE_VOLUME_CONTROLLER;
     getDeviceUUID(response.uuid);
     response.version_major = 1;
-    response.version_minor = 0;
+    response.version_minor = 5;  // 1.1: v2 framing, 1.2: capabilities, 1.3: icon rows, 1.4: RLE icons, 1.5: meters
 
     Serial.write((uint8_t*)&response, sizeof(response));
 }
\ No newline at end of file
@@ -0,0 +1,39 @@
+#include "framing.h"
+#include "../protocol.h"
+
+static bool framed = false;
+static uint8_t txSeq = 0;
+
+void setFramed(bool on) {
+    framed = on;
+    txSeq = 0;
+}
+
+bool isFramed() {
+    return framed;
+}
+
+uint16_t crc16Update(uint16_t crc, uint8_t b) {
+    crc ^= (uint16_t)b << 8;
+    for (uint8_t i = 0; i < 8; i++) {
+        crc = (crc & 0x8000) ? (crc << 1) ^ 0x1021 : crc << 1;
+    }
+    return crc;
+}
+
+void sendPacket(const uint8_t* data, uint16_t len) {
+    if (!framed) {
+        Serial.write(data, len);
+        return;
+    }
+
+    uint8_t header[4] = { FRAME_START, (uint8_t)(len & 0xFF), (uint8_t)(len >> 8), txSeq++ };
+    uint16_t crc = 0xFFFF;
+    for (uint8_t i = 1; i < 4; i++) crc = crc16Update(crc, header[i]);
+    for (uint16_t i = 0; i < len; i++) crc = crc16Update(crc, data[i]);
+
+    uint8_t trailer[2] = { (uint8_t)(crc & 0xFF), (uint8_t)(crc >> 8) };
+    Serial.write(header, 4);
+    Serial.write(data, len);
+    Serial.write(trailer, 2);
+}
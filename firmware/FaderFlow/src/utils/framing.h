@@ -0,0 +1,42 @@
+//
+// v2 link framing: START, len (u16 LE), seq, payload, CRC-16 (LE).
+// The CRC (CCITT-FALSE) covers len, seq and payload.
+//
+
+#ifndef FADERFLOW_FRAMING_H
+#define FADERFLOW_FRAMING_H
+
+#include <Arduino.h>
+
+// Switch outgoing packets between raw (v1) and framed (v2)
+void setFramed(bool framed);
+bool isFramed();
+
+// Send one packet, framed when the host negotiated v2
+void sendPacket(const uint8_t* data, uint16_t len);
+
+uint16_t crc16Update(uint16_t crc, uint8_t b);
+
+// Stream wrapper that folds every byte read into a running CRC, so a
+// payload too big to buffer (icons) can be checked after it streamed past
+class CrcStream : public Stream {
+public:
+    CrcStream(Stream& inner, uint16_t crc) : inner(inner), crc(crc) {}
+
+    int available() override { return inner.available(); }
+    int peek() override { return inner.peek(); }
+    int read() override {
+        int b = inner.read();
+        if (b >= 0) crc = crc16Update(crc, (uint8_t)b);
+        return b;
+    }
+    size_t write(uint8_t) override { return 0; }
+
+    uint16_t getCrc() { return crc; }
+
+private:
+    Stream& inner;
+    uint16_t crc;
+};
+
+#endif //FADERFLOW_FRAMING_H